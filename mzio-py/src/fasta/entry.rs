@@ -9,6 +9,7 @@ use mzio::fasta::entry::FastaEntry as BaseEntry;
 /// Wrapper for the rust implementation entry
 /// 
 #[pyclass]
+#[derive(Clone, Debug)]
 pub struct Entry {
     base_entry: BaseEntry
 }
@@ -49,6 +50,13 @@ impl Entry {
         Ok(self.base_entry.get_database())
     }
 
+    /// Sets the database type
+    ///
+    #[setter]
+    pub fn set_database(&mut self, database: String) {
+        self.base_entry.set_database(database);
+    }
+
     /// Returns the accession
     ///
     #[getter]
@@ -56,6 +64,13 @@ impl Entry {
         Ok(self.base_entry.get_accession())
     }
 
+    /// Sets the accession
+    ///
+    #[setter]
+    pub fn set_accession(&mut self, accession: String) {
+        self.base_entry.set_accession(accession);
+    }
+
     /// Entry name
     ///
     #[getter]
@@ -63,6 +78,13 @@ impl Entry {
         Ok(self.base_entry.get_entry_name())
     }
 
+    /// Sets the entry name
+    ///
+    #[setter]
+    pub fn set_entry_name(&mut self, entry_name: String) {
+        self.base_entry.set_entry_name(entry_name);
+    }
+
     /// Returns the protein name
     ///
     #[getter]
@@ -70,6 +92,13 @@ impl Entry {
         Ok(self.base_entry.get_protein_name())
     }
 
+    /// Sets the protein name
+    ///
+    #[setter]
+    pub fn set_protein_name(&mut self, protein_name: String) {
+        self.base_entry.set_protein_name(protein_name);
+    }
+
     /// Returns additional keyword attributes, e.g
     /// * OX = 381666
     /// * GN = acoX
@@ -81,6 +110,34 @@ impl Entry {
         Ok(self.base_entry.get_keyword_attributes().clone())
     }
 
+    /// Replaces all the keyword attributes
+    ///
+    #[setter]
+    pub fn set_keyword_attributes(&mut self, keyword_attributes: HashMap<String, String>) {
+        self.base_entry.set_keyword_attributes(keyword_attributes);
+    }
+
+    /// Sets a keyword attribute, replacing the value of an existing key
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Attribute key, e.g. `GN`
+    /// * `value` - Attribute value
+    ///
+    pub fn set_keyword_attribute(&mut self, key: String, value: String) {
+        self.base_entry.set_keyword_attribute(key, value);
+    }
+
+    /// Removes a keyword attribute and returns its value, if any
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Attribute key, e.g. `GN`
+    ///
+    pub fn remove_keyword_attribute(&mut self, key: &str) -> Option<String> {
+        self.base_entry.remove_keyword_attribute(key)
+    }
+
     /// Returns the amino acid sequence
     ///
     #[getter]
@@ -88,11 +145,57 @@ impl Entry {
         Ok(self.base_entry.get_sequence())
     }
 
+    /// Sets the amino acid sequence
+    ///
+    #[setter]
+    pub fn set_sequence(&mut self, sequence: String) {
+        self.base_entry.set_sequence(sequence);
+    }
+
     /// Returns the plain header (before parsing)
     ///
     #[getter]
-    pub fn plain_header(&self) -> Result<&String> {
-        Ok(self.base_entry.get_sequence())
+    pub fn plain_header(&self) -> Option<String> {
+        self.base_entry.get_plain_header().clone()
+    }
+
+    /// Sets the plain header
+    ///
+    #[setter]
+    pub fn set_plain_header(&mut self, plain_header: Option<String>) {
+        self.base_entry.set_plain_header(plain_header);
+    }
+
+    /// Creates a new entry from the given header and sequence,
+    /// detecting the header layout (UniProtKB, UniRef, NCBI, Ensembl or generic)
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - A FASTA header, with or without '>'
+    /// * `sequence` - Amino acid sequence
+    ///
+    #[staticmethod]
+    pub fn from_header(header: &str, sequence: &str) -> Result<Self> {
+        Ok(Self::from(BaseEntry::from_header(header, sequence)?))
+    }
+
+    /// Creates the FASTA header of the entry, as written by the writer
+    ///
+    /// # Arguments
+    ///
+    /// * `sort_keyword_attributes` - If true the keyword attributes will be sorted
+    ///
+    #[pyo3(signature = (sort_keyword_attributes = true))]
+    pub fn to_header(&self, sort_keyword_attributes: bool) -> String {
+        self.base_entry.to_header(sort_keyword_attributes)
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.base_entry == other.base_entry
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Entry('{}')", self.base_entry.to_header(true))
     }
 }
 
@@ -1,9 +1,12 @@
 // std imports
+use std::io::BufRead;
 use std::path::PathBuf;
 
 // 3rd party imports
 use anyhow::Result;
 use pyo3::prelude::*;
+use fallible_iterator::FallibleIterator;
+use mzio::fasta::header::FastaHeaderFormat;
 use mzio::fasta::reader::FastaReader as BaseReader;
 
 // internal imports
@@ -11,28 +14,24 @@ use crate::fasta::entry::Entry;
 
 #[pyclass]
 pub struct Reader {
-    base_reader: BaseReader
+    base_reader: BaseReader<Box<dyn BufRead + Send>>
 }
 
 #[pymethods]
 impl Reader {
     #[new]
-    fn new(fasta_file_path: PathBuf, buffer_size: usize, keep_plain_header: bool) -> Result<Self> {
-        match BaseReader::new(&fasta_file_path, buffer_size, keep_plain_header
-        ) {
-            Ok(base_reader) => Ok(Self{base_reader}),
-            Err(err) => Err(err)
-        }
+    #[pyo3(signature = (fasta_file_path, buffer_size, keep_plain_header, header_format="auto"))]
+    fn new(fasta_file_path: PathBuf, buffer_size: usize, keep_plain_header: bool, header_format: &str) -> Result<Self> {
+        let mut base_reader = BaseReader::new(&fasta_file_path, buffer_size, keep_plain_header)?;
+        base_reader.set_header_parser(header_format.parse::<FastaHeaderFormat>()?);
+        Ok(Self{base_reader})
     }
 
     fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
         slf
     }
 
-    fn __next__(mut slf: PyRefMut<'_, Self>) -> Option<Entry> {
-        match slf.base_reader.next() {
-            Some(base_entry) => Some(Entry::from(base_entry)),
-            None => None
-        }
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> Result<Option<Entry>> {
+        Ok(slf.base_reader.next()?.map(Entry::from))
     }
 }
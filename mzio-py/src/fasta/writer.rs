@@ -1,4 +1,6 @@
 // std imports
+use std::fs::File;
+use std::io::BufWriter;
 use std::path::PathBuf;
 
 // 3rd party imports
@@ -11,7 +13,7 @@ use crate::fasta::entry::Entry;
 
 #[pyclass]
 pub struct Writer {
-    base_writer: BaseWriter
+    base_writer: BaseWriter<BufWriter<File>>
 }
 
 #[pymethods]
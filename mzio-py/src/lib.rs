@@ -35,7 +35,9 @@ fn register_fasta_module(py: Python, parent_module: &PyModule) -> PyResult<()> {
 fn register_mgf_module(py: Python, parent_module: &PyModule) -> PyResult<()> {
     let child_module = PyModule::new(py, "mgf")?;
     child_module.add_class::<mgf::spectrum::MgfSpectrum>()?;
+    child_module.add_class::<mgf::spectrum::MgfSpectrumHeader>()?;
     child_module.add_class::<mgf::reader::MgfReader>()?;
+    child_module.add_class::<mgf::reader::MgfHeaderReader>()?;
     child_module.add_class::<mgf::writer::MgfWriter>()?;
     parent_module.add_submodule(child_module)?;
     Ok(())
@@ -1,26 +1,29 @@
 // std imports
+use std::io::BufRead;
 use std::path::PathBuf;
 
 // 3rd party imports
 use pyo3::prelude::*;
 use anyhow::Result;
 use fallible_iterator::FallibleIterator;
+use mzio::mgf::diagnostics::MgfParsePolicy;
 use mzio::mgf::reader::MgfReader as BaseMgfReader;
 
 // internal imports
-use crate::mgf::spectrum::MgfSpectrum;
+use crate::mgf::spectrum::{MgfSpectrum, MgfSpectrumHeader};
 
 #[pyclass]
 pub struct MgfReader {
-    base_reader: BaseMgfReader
+    base_reader: BaseMgfReader<Box<dyn BufRead + Send>>
 }
 
 #[pymethods]
 impl MgfReader {
     #[new]
-    #[pyo3(signature = (mgf_file_path, buffer_size=4096))]
-    fn new(mgf_file_path: PathBuf, buffer_size: usize) -> Result<Self> {
-        let base_reader = BaseMgfReader::new(&mgf_file_path, buffer_size)?;
+    #[pyo3(signature = (mgf_file_path, buffer_size=4096, parse_policy="strict"))]
+    fn new(mgf_file_path: PathBuf, buffer_size: usize, parse_policy: &str) -> Result<Self> {
+        let mut base_reader = BaseMgfReader::new(&mgf_file_path, buffer_size)?;
+        base_reader.set_parse_policy(parse_policy.parse::<MgfParsePolicy>()?);
         Ok(Self{base_reader})
         /*match BaseReader::new(&mgf_file_path, buffer_size) {
             Ok(base_reader) => Ok(Self{base_reader}),
@@ -32,10 +35,72 @@ impl MgfReader {
         slf
     }
 
-    fn __next__(mut slf: PyRefMut<'_, Self>) -> Option<MgfSpectrum> {
-        match slf.base_reader.next().ok()? {
-            Some(base_spectrum) => Some(MgfSpectrum::from(base_spectrum)),
-            None => None
-        }
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> Result<Option<MgfSpectrum>> {
+        Ok(slf.base_reader.next()?.map(MgfSpectrum::from))
+    }
+
+    /// Returns the number of spectra dropped so far by a lenient parse policy
+    ///
+    #[getter]
+    fn num_skipped_spectra(&self) -> usize {
+        self.base_reader.get_parse_report().get_num_skipped_spectra()
+    }
+
+    /// Returns the number of lines dropped so far by a lenient parse policy
+    ///
+    #[getter]
+    fn num_skipped_lines(&self) -> usize {
+        self.base_reader.get_parse_report().get_num_skipped_lines()
+    }
+
+    /// Returns the descriptions of the first 1000 drops so far, with their location
+    ///
+    #[getter]
+    fn diagnostics(&self) -> Vec<String> {
+        self.base_reader.get_parse_report().get_diagnostics().iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect()
+    }
+}
+
+/// Reader yielding only the spectrum headers, the peak lines being skipped without being parsed
+///
+#[pyclass]
+pub struct MgfHeaderReader {
+    base_reader: BaseMgfReader<Box<dyn BufRead + Send>>
+}
+
+#[pymethods]
+impl MgfHeaderReader {
+    #[new]
+    #[pyo3(signature = (mgf_file_path, buffer_size=4096, parse_policy="strict"))]
+    fn new(mgf_file_path: PathBuf, buffer_size: usize, parse_policy: &str) -> Result<Self> {
+        let mut base_reader = BaseMgfReader::new(&mgf_file_path, buffer_size)?;
+        base_reader.set_parse_policy(parse_policy.parse::<MgfParsePolicy>()?);
+        Ok(Self{base_reader})
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> Result<Option<MgfSpectrumHeader>> {
+        Ok(slf.base_reader.headers().next()?.map(MgfSpectrumHeader::from))
+    }
+
+    /// Returns the number of spectra dropped so far by a lenient parse policy
+    ///
+    #[getter]
+    fn num_skipped_spectra(&self) -> usize {
+        self.base_reader.get_parse_report().get_num_skipped_spectra()
+    }
+
+    /// Returns the descriptions of the first 1000 drops so far, with their location
+    ///
+    #[getter]
+    fn diagnostics(&self) -> Vec<String> {
+        self.base_reader.get_parse_report().get_diagnostics().iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect()
     }
 }
@@ -1,6 +1,7 @@
 // 3rd party imports
 use pyo3::prelude::*;
 use mzio::mgf::spectrum::MgfSpectrum as BaseMgfSpectrum;
+use mzio::mgf::spectrum::MgfSpectrumHeader as BaseMgfSpectrumHeader;
 
 /// Wrapper for the rust implementation spectrum
 /// 
@@ -58,6 +59,13 @@ impl MgfSpectrum {
         self.base_spectrum.header.get_precursor_mz()
     }
 
+    /// Returns the precursor intensity
+    ///
+    #[getter]
+    pub fn precursor_intensity(&self) -> Option<f64> {
+        self.base_spectrum.header.get_precursor_intensity()
+    }
+
     /// Returns the precursor charge
     ///
     #[getter]
@@ -65,6 +73,13 @@ impl MgfSpectrum {
         self.base_spectrum.header.get_precursor_charge()
     }
 
+    /// Returns all candidate precursor charges
+    ///
+    #[getter]
+    pub fn precursor_charges(&self) -> Vec<i8> {
+        self.base_spectrum.header.get_precursor_charges().to_owned()
+    }
+
     /// Returns the retention time
     ///
     #[getter]
@@ -72,6 +87,90 @@ impl MgfSpectrum {
         self.base_spectrum.header.get_retention_time()
     }
 
+    /// Returns the end of the retention time range
+    ///
+    #[getter]
+    pub fn retention_time_end(&self) -> Option<f64> {
+        self.base_spectrum.header.get_retention_time_end()
+    }
+
+    /// Returns the scan number, or the first scan of the range
+    ///
+    #[getter]
+    pub fn first_scan(&self) -> Option<u32> {
+        self.base_spectrum.header.get_first_scan()
+    }
+
+    /// Returns the last scan of the range
+    ///
+    #[getter]
+    pub fn last_scan(&self) -> Option<u32> {
+        self.base_spectrum.header.get_last_scan()
+    }
+
+    /// Returns the instrument type
+    ///
+    #[getter]
+    pub fn instrument(&self) -> Option<String> {
+        self.base_spectrum.header.get_instrument().cloned()
+    }
+
+    /// Returns the peptide mass tolerance
+    ///
+    #[getter]
+    pub fn tolerance(&self) -> Option<f64> {
+        self.base_spectrum.header.get_tolerance()
+    }
+
+    /// Returns the unit of the peptide mass tolerance
+    ///
+    #[getter]
+    pub fn tolerance_unit(&self) -> Option<String> {
+        self.base_spectrum.header.get_tolerance_unit().map(|unit| unit.to_string())
+    }
+
+    /// Returns the variable modifications (IT_MODS)
+    ///
+    #[getter]
+    pub fn variable_modifications(&self) -> Vec<String> {
+        self.base_spectrum.header.get_variable_modifications().to_owned()
+    }
+
+    /// Returns the amino acid sequence qualifiers (SEQ)
+    ///
+    #[getter]
+    pub fn sequence_qualifiers(&self) -> Vec<String> {
+        self.base_spectrum.header.get_sequence_qualifiers().to_owned()
+    }
+
+    /// Returns the amino acid composition qualifiers (COMP)
+    ///
+    #[getter]
+    pub fn composition_qualifiers(&self) -> Vec<String> {
+        self.base_spectrum.header.get_composition_qualifiers().to_owned()
+    }
+
+    /// Returns the sequence tags (TAG)
+    ///
+    #[getter]
+    pub fn sequence_tags(&self) -> Vec<String> {
+        self.base_spectrum.header.get_sequence_tags().to_owned()
+    }
+
+    /// Returns the error tolerant sequence tags (ETAG)
+    ///
+    #[getter]
+    pub fn error_tolerant_sequence_tags(&self) -> Vec<String> {
+        self.base_spectrum.header.get_error_tolerant_sequence_tags().to_owned()
+    }
+
+    /// Returns the parameters without dedicated property (e.g. RAWFILE, USER01), in reading order
+    ///
+    #[getter]
+    pub fn additional_params(&self) -> Vec<(String, String)> {
+        self.base_spectrum.header.get_additional_params().to_owned()
+    }
+
     /// Returns the mzs
     ///
     #[getter]
@@ -85,8 +184,116 @@ impl MgfSpectrum {
     pub fn intensities(&self) -> Vec<f32> {
         self.base_spectrum.get_intensity_list().to_owned()
     }
+
+    /// Returns the per-peak fragment charges, if any (0 if unknown for a peak)
+    ///
+    #[getter]
+    pub fn charges(&self) -> Option<Vec<i8>> {
+        self.base_spectrum.get_charge_list().cloned()
+    }
+
+    /// Returns the per-peak annotations, if any (empty if missing for a peak)
+    ///
+    #[getter]
+    pub fn annotations(&self) -> Option<Vec<String>> {
+        self.base_spectrum.get_annotation_list().cloned()
+    }
+}
+
+
+/// Wrapper for the rust implementation spectrum header, read without the peaks
+///
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct MgfSpectrumHeader {
+    base_header: BaseMgfSpectrumHeader
 }
 
+#[pymethods]
+impl MgfSpectrumHeader {
+    /// Returns the spectrum title
+    ///
+    #[getter]
+    pub fn title(&self) -> String {
+        self.base_header.get_title().to_owned()
+    }
+
+    /// Returns the precursor mz
+    ///
+    #[getter]
+    pub fn precursor_mz(&self) -> f64 {
+        self.base_header.get_precursor_mz()
+    }
+
+    /// Returns the precursor intensity
+    ///
+    #[getter]
+    pub fn precursor_intensity(&self) -> Option<f64> {
+        self.base_header.get_precursor_intensity()
+    }
+
+    /// Returns the precursor charge
+    ///
+    #[getter]
+    pub fn precursor_charge(&self) -> Option<i8> {
+        self.base_header.get_precursor_charge()
+    }
+
+    /// Returns all candidate precursor charges
+    ///
+    #[getter]
+    pub fn precursor_charges(&self) -> Vec<i8> {
+        self.base_header.get_precursor_charges().to_owned()
+    }
+
+    /// Returns the retention time
+    ///
+    #[getter]
+    pub fn retention_time(&self) -> Option<f64> {
+        self.base_header.get_retention_time()
+    }
+
+    /// Returns the end of the retention time range
+    ///
+    #[getter]
+    pub fn retention_time_end(&self) -> Option<f64> {
+        self.base_header.get_retention_time_end()
+    }
+
+    /// Returns the scan number, or the first scan of the range
+    ///
+    #[getter]
+    pub fn first_scan(&self) -> Option<u32> {
+        self.base_header.get_first_scan()
+    }
+
+    /// Returns the last scan of the range
+    ///
+    #[getter]
+    pub fn last_scan(&self) -> Option<u32> {
+        self.base_header.get_last_scan()
+    }
+
+    /// Returns the parameters without dedicated property, in reading order
+    ///
+    #[getter]
+    pub fn additional_params(&self) -> Vec<(String, String)> {
+        self.base_header.get_additional_params().to_owned()
+    }
+}
+
+impl From<BaseMgfSpectrumHeader> for MgfSpectrumHeader {
+    /// Convert header from the Rust implementation to the python wrapper.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_header` - Spectrum header from rust implementation
+    fn from(base_header: BaseMgfSpectrumHeader) -> Self {
+        Self {
+            base_header
+        }
+    }
+}
 
 impl From<BaseMgfSpectrum> for MgfSpectrum {
     /// Convert entry from the Rust implementation to the python wrapper.
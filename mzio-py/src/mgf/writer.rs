@@ -1,4 +1,6 @@
 // std imports
+use std::fs::File;
+use std::io::BufWriter;
 use std::path::PathBuf;
 
 // 3rd party imports
@@ -7,11 +9,12 @@ use pyo3::prelude::*;
 use crate::mgf::spectrum::MgfSpectrum;
 
 // internal imports
-use mzio::mgf::writer::MgfWriter as BaseMgfWriter;
+use mzio::mgf::number_format::NumberFormat;
+use mzio::mgf::writer::{MgfWriter as BaseMgfWriter, MgfWriterOptions};
 
 #[pyclass]
 pub struct MgfWriter {
-    base_writer: BaseMgfWriter
+    base_writer: BaseMgfWriter<BufWriter<File>>
 }
 
 #[pymethods]
@@ -21,10 +24,40 @@ impl MgfWriter {
     /// # Arguments
     ///
     /// * `mgf_file_path` - Path to MGF file
+    /// * `mz_format` - Format of the peak m/z values, e.g. `shortest`, `decimals:4`, `significant:6` or `scientific:3`
+    /// * `intensity_format` - Format of the peak intensities
+    /// * `precursor_mz_format` - Format of the precursor m/z and of the mass tolerances
+    /// * `precursor_intensity_format` - Format of the precursor intensity
+    /// * `retention_time_format` - Format of the retention times
+    /// * `skip_zero_intensities` - Whether to drop the peaks with a zero intensity
+    /// * `min_intensity` - Minimal intensity of the written peaks
+    /// * `min_relative_intensity` - Minimal intensity of the written peaks, relative to the most intense one
+    /// * `max_num_peaks` - Maximal number of written peaks, the most intense ones being kept
+    /// * `mz_range` - Lowest and highest m/z values of the written peaks
     /// 
     #[new]
-    pub fn new(mgf_file_path: PathBuf) -> Result<Self> {
-        let base_writer = BaseMgfWriter::new(&mgf_file_path)?;
+    #[pyo3(signature = (mgf_file_path, mz_format="shortest", intensity_format="shortest", precursor_mz_format="shortest",
+        precursor_intensity_format="shortest", retention_time_format="shortest", skip_zero_intensities=false,
+        min_intensity=None, min_relative_intensity=None, max_num_peaks=None, mz_range=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(mgf_file_path: PathBuf, mz_format: &str, intensity_format: &str, precursor_mz_format: &str,
+        precursor_intensity_format: &str, retention_time_format: &str, skip_zero_intensities: bool,
+        min_intensity: Option<f32>, min_relative_intensity: Option<f32>, max_num_peaks: Option<usize>,
+        mz_range: Option<(f64, f64)>) -> Result<Self> {
+        let mut options = MgfWriterOptions::new();
+        options.set_mz_format(mz_format.parse::<NumberFormat>()?)
+            .set_intensity_format(intensity_format.parse::<NumberFormat>()?)
+            .set_precursor_mz_format(precursor_mz_format.parse::<NumberFormat>()?)
+            .set_precursor_intensity_format(precursor_intensity_format.parse::<NumberFormat>()?)
+            .set_retention_time_format(retention_time_format.parse::<NumberFormat>()?)
+            .set_skip_zero_intensities(skip_zero_intensities)
+            .set_min_intensity(min_intensity)
+            .set_min_relative_intensity(min_relative_intensity)
+            .set_max_num_peaks(max_num_peaks)
+            .set_mz_range(mz_range);
+
+        let mut base_writer = BaseMgfWriter::new(&mgf_file_path)?;
+        base_writer.set_options(options);
         Ok(Self {base_writer})
     }
 
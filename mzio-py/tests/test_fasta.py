@@ -36,3 +36,18 @@ class FastaModuleTestCase(unittest.TestCase):
 
         with self.assertRaises(RuntimeError):
             fasta.Reader(self.__class__.TEST_NON_EXISTING_FASTA_FILE, 1024)
+
+    def test_entry_editing(self):
+        header = ">sp|P27748|ACOX_CUPNH Acetoin catabolism protein X OX=381666 GN=acoX"
+        entry = fasta.Entry.from_header(header, "MGHAAGASAQIA")
+        self.assertEqual(entry.plain_header, header)
+        self.assertEqual(entry.to_header(), ">sp|P27748|ACOX_CUPNH Acetoin catabolism protein X GN=acoX OX=381666")
+
+        other_entry = fasta.Entry.from_header(header, "MGHAAGASAQIA")
+        self.assertEqual(entry, other_entry)
+
+        entry.accession = "DECOY_P27748"
+        entry.set_keyword_attribute("GN", "acoY")
+        self.assertEqual(entry.remove_keyword_attribute("OX"), "381666")
+        self.assertNotEqual(entry, other_entry)
+        self.assertEqual(entry.to_header(), ">sp|DECOY_P27748|ACOX_CUPNH Acetoin catabolism protein X GN=acoY")
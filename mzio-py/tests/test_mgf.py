@@ -36,3 +36,14 @@ class MGFModuleTestCase(unittest.TestCase):
 
         with self.assertRaises(RuntimeError):
             mgf.Reader(self.__class__.TEST_NON_EXISTING_MGF_FILE)
+
+    def test_read_headers(self):
+        spectra = list(mgf.MgfReader(self.__class__.TEST_READ_MGF_FILE))
+        headers = list(mgf.MgfHeaderReader(self.__class__.TEST_READ_MGF_FILE))
+
+        self.assertEqual(len(headers), len(spectra))
+        for header, spectrum in zip(headers, spectra):
+            self.assertEqual(header.title, spectrum.title)
+            self.assertEqual(header.precursor_mz, spectrum.precursor_mz)
+            self.assertEqual(header.precursor_charge, spectrum.precursor_charge)
+            self.assertEqual(header.retention_time, spectrum.retention_time)
@@ -0,0 +1,316 @@
+//! Module for transparent (de)compression of input and output streams.
+//! Each compression format is enabled by its own cargo feature (`gzip`, `bzip2`, `zstd`, `xz`).
+
+// std imports
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+// 3rd party imports
+use anyhow::{Result, bail};
+
+/// Compression formats which can be detected and written
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CompressionFormat {
+    Uncompressed,
+    Gzip,
+    Bzip2,
+    Zstd,
+    Xz,
+}
+
+const GZIP_MAGIC_BYTES: &[u8] = &[0x1f, 0x8b];
+const BZIP2_MAGIC_BYTES: &[u8] = b"BZh";
+const ZSTD_MAGIC_BYTES: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const XZ_MAGIC_BYTES: &[u8] = &[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00];
+
+impl CompressionFormat {
+    /// Detects the compression format from the first bytes of a stream.
+    /// Returns `Uncompressed` if no known magic number is found.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - First bytes of the stream (at least 6 bytes are needed to detect every format)
+    ///
+    pub fn from_magic_bytes(bytes: &[u8]) -> Self {
+        if bytes.starts_with(GZIP_MAGIC_BYTES) {
+            Self::Gzip
+        } else if bytes.starts_with(BZIP2_MAGIC_BYTES) {
+            Self::Bzip2
+        } else if bytes.starts_with(ZSTD_MAGIC_BYTES) {
+            Self::Zstd
+        } else if bytes.starts_with(XZ_MAGIC_BYTES) {
+            Self::Xz
+        } else {
+            Self::Uncompressed
+        }
+    }
+
+    /// Guesses the compression format from the file extension, e.g. `.fasta.gz` or `.mgf.zst`.
+    /// Returns `Uncompressed` for unknown extensions.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - Path to the file
+    ///
+    pub fn from_extension(file_path: &Path) -> Self {
+        match file_path.extension().and_then(|ext| ext.to_str()) {
+            Some("gz") | Some("gzip") => Self::Gzip,
+            Some("bz2") | Some("bzip2") => Self::Bzip2,
+            Some("zst") | Some("zstd") => Self::Zstd,
+            Some("xz") => Self::Xz,
+            _ => Self::Uncompressed,
+        }
+    }
+
+    /// Returns the name of the cargo feature enabling this format
+    ///
+    fn feature_name(&self) -> &'static str {
+        match self {
+            Self::Uncompressed => "",
+            Self::Gzip => "gzip",
+            Self::Bzip2 => "bzip2",
+            Self::Zstd => "zstd",
+            Self::Xz => "xz",
+        }
+    }
+}
+
+/// Wraps the given reader into a decompressor if its content starts with a known magic number.
+/// Uncompressed content is passed through unchanged.
+///
+/// # Arguments
+///
+/// * `reader` - Buffered source, possibly compressed
+///
+pub fn decompress<'a, R: BufRead + Send + 'a>(mut reader: R) -> Result<Box<dyn BufRead + Send + 'a>> {
+    let format = CompressionFormat::from_magic_bytes(reader.fill_buf()?);
+
+    let decompressed: Box<dyn BufRead + Send + 'a> = match format {
+        CompressionFormat::Uncompressed => Box::new(reader),
+        #[cfg(feature = "gzip")]
+        CompressionFormat::Gzip => Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader))),
+        #[cfg(feature = "bzip2")]
+        CompressionFormat::Bzip2 => Box::new(BufReader::new(bzip2::bufread::MultiBzDecoder::new(reader))),
+        #[cfg(feature = "zstd")]
+        CompressionFormat::Zstd => Box::new(BufReader::new(zstd::stream::read::Decoder::with_buffer(reader)?)),
+        #[cfg(feature = "xz")]
+        CompressionFormat::Xz => Box::new(BufReader::new(xz2::bufread::XzDecoder::new_multi_decoder(reader))),
+        #[allow(unreachable_patterns)]
+        _ => bail!("{:?} compressed input requires the `{}` feature", format, format.feature_name()),
+    };
+
+    Ok(decompressed)
+}
+
+/// Opens a file for reading, transparently decompressing it if needed.
+///
+/// # Arguments
+///
+/// * `file_path` - Path to the file, possibly compressed
+/// * `buffer_size` - Buffer size to use when loading bytes from disk.
+///
+pub fn open_file(file_path: &Path, buffer_size: usize) -> Result<Box<dyn BufRead + Send>> {
+    let file: File = File::open(file_path)?;
+    decompress(BufReader::with_capacity(buffer_size, file))
+}
+
+/// Output stream compressed with one of the supported formats (or passed through when uncompressed).
+/// Call `finish()` to finalize the compressed stream and get its errors; dropping the writer
+/// finalizes the stream too but silently discards any error.
+pub struct CompressedWriter<W: Write>(Option<Encoder<W>>);
+
+/// Encoder of each compression format
+enum Encoder<W: Write> {
+    Uncompressed(W),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<W>),
+    #[cfg(feature = "bzip2")]
+    Bzip2(bzip2::write::BzEncoder<W>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, W>),
+    #[cfg(feature = "xz")]
+    Xz(xz2::write::XzEncoder<W>),
+}
+
+impl<W: Write> Encoder<W> {
+    /// Returns the encoder as writer
+    ///
+    fn as_writer(&mut self) -> &mut dyn Write {
+        match self {
+            Self::Uncompressed(writer) => writer,
+            #[cfg(feature = "gzip")]
+            Self::Gzip(encoder) => encoder,
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2(encoder) => encoder,
+            #[cfg(feature = "zstd")]
+            Self::Zstd(encoder) => encoder,
+            #[cfg(feature = "xz")]
+            Self::Xz(encoder) => encoder,
+        }
+    }
+
+    /// Writes the end of the compressed stream and returns the underlying writer
+    ///
+    fn finish(self) -> std::io::Result<W> {
+        match self {
+            Self::Uncompressed(mut writer) => {
+                writer.flush()?;
+                Ok(writer)
+            },
+            #[cfg(feature = "gzip")]
+            Self::Gzip(encoder) => encoder.finish(),
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2(encoder) => encoder.finish(),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(encoder) => encoder.finish(),
+            #[cfg(feature = "xz")]
+            Self::Xz(encoder) => encoder.finish(),
+        }
+    }
+}
+
+impl<W: Write> CompressedWriter<W> {
+    /// Finalizes the compressed stream and returns the underlying writer.
+    ///
+    pub fn finish(mut self) -> Result<W> {
+        // the encoder is only taken by finish() and drop()
+        Ok(self.0.take().unwrap().finish()?)
+    }
+}
+
+impl<W: Write> Write for CompressedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.as_mut().unwrap().as_writer().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.as_mut().unwrap().as_writer().flush()
+    }
+}
+
+impl<W: Write> Drop for CompressedWriter<W> {
+    fn drop(&mut self) {
+        if let Some(encoder) = self.0.take() {
+            let _ = encoder.finish();
+        }
+    }
+}
+
+/// Buffered compressed file, as created by `create_file()`
+pub type CompressedFile = BufWriter<CompressedWriter<File>>;
+
+/// Wraps the given writer into a compressor using the given format and default compression level.
+/// Call `finish()` on the returned writer to finalize the compressed stream.
+///
+/// # Arguments
+///
+/// * `writer` - Destination of the compressed content
+/// * `format` - Compression format
+///
+pub fn compress<W: Write>(writer: W, format: CompressionFormat) -> Result<CompressedWriter<W>> {
+    let encoder = match format {
+        CompressionFormat::Uncompressed => Encoder::Uncompressed(writer),
+        #[cfg(feature = "gzip")]
+        CompressionFormat::Gzip => Encoder::Gzip(flate2::write::GzEncoder::new(writer, flate2::Compression::default())),
+        #[cfg(feature = "bzip2")]
+        CompressionFormat::Bzip2 => Encoder::Bzip2(bzip2::write::BzEncoder::new(writer, bzip2::Compression::default())),
+        #[cfg(feature = "zstd")]
+        CompressionFormat::Zstd => Encoder::Zstd(zstd::stream::write::Encoder::new(writer, 0)?),
+        #[cfg(feature = "xz")]
+        CompressionFormat::Xz => Encoder::Xz(xz2::write::XzEncoder::new(writer, 6)),
+        #[allow(unreachable_patterns)]
+        _ => bail!("{:?} compressed output requires the `{}` feature", format, format.feature_name()),
+    };
+
+    Ok(CompressedWriter(Some(encoder)))
+}
+
+/// Creates a file for writing, compressing its content with the given format.
+/// Call `finish_file()` once everything is written to finalize the compressed stream.
+///
+/// # Arguments
+///
+/// * `file_path` - Path to the file
+/// * `format` - Compression format
+///
+pub fn create_file(file_path: &Path, format: CompressionFormat) -> Result<CompressedFile> {
+    let file: File = File::create(file_path)?;
+    Ok(BufWriter::new(compress(file, format)?))
+}
+
+/// Flushes the buffer, finalizes the compressed stream and returns the file.
+///
+/// # Arguments
+///
+/// * `compressed_file` - File created by `create_file()`
+///
+pub fn finish_file(compressed_file: CompressedFile) -> Result<File> {
+    compressed_file.into_inner().map_err(|err| err.into_error())?.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::io::Read;
+
+    const TEST_CONTENT: &str = ">sp|P27748|ACOX_CUPNH Acetoin catabolism protein X\nMGHAAGASAQIAPVVGIIANPISARDIRRVIANANSLQ\n";
+
+    /// Compresses the test content with the given format and reads it back
+    /// through the magic bytes detection.
+    fn assert_round_trip(format: CompressionFormat) {
+        let mut compressed_content = Vec::new();
+        let mut writer = compress(&mut compressed_content, format).unwrap();
+        writer.write_all(TEST_CONTENT.as_bytes()).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(CompressionFormat::from_magic_bytes(&compressed_content), format);
+
+        let mut decompressed_content = String::new();
+        decompress(compressed_content.as_slice()).unwrap().read_to_string(&mut decompressed_content).unwrap();
+        assert_eq!(decompressed_content, TEST_CONTENT);
+    }
+
+    #[test]
+    /// Tests the detection of the compression format by magic bytes and extension.
+    fn test_format_detection() {
+        assert_eq!(CompressionFormat::from_magic_bytes(TEST_CONTENT.as_bytes()), CompressionFormat::Uncompressed);
+        assert_eq!(CompressionFormat::from_magic_bytes(&[0x1f, 0x8b, 0x08]), CompressionFormat::Gzip);
+        assert_eq!(CompressionFormat::from_magic_bytes(b"BZh91AY"), CompressionFormat::Bzip2);
+        assert_eq!(CompressionFormat::from_magic_bytes(&[0x28, 0xb5, 0x2f, 0xfd, 0x00]), CompressionFormat::Zstd);
+        assert_eq!(CompressionFormat::from_magic_bytes(&[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00]), CompressionFormat::Xz);
+        assert_eq!(CompressionFormat::from_extension(Path::new("uniprot.fasta.gz")), CompressionFormat::Gzip);
+        assert_eq!(CompressionFormat::from_extension(Path::new("spectra.mgf.zst")), CompressionFormat::Zstd);
+        assert_eq!(CompressionFormat::from_extension(Path::new("spectra.mgf")), CompressionFormat::Uncompressed);
+    }
+
+    #[test]
+    /// Tests that uncompressed content is passed through.
+    fn test_uncompressed_round_trip() {
+        assert_round_trip(CompressionFormat::Uncompressed);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_round_trip() {
+        assert_round_trip(CompressionFormat::Gzip);
+    }
+
+    #[cfg(feature = "bzip2")]
+    #[test]
+    fn test_bzip2_round_trip() {
+        assert_round_trip(CompressionFormat::Bzip2);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_round_trip() {
+        assert_round_trip(CompressionFormat::Zstd);
+    }
+
+    #[cfg(feature = "xz")]
+    #[test]
+    fn test_xz_round_trip() {
+        assert_round_trip(CompressionFormat::Xz);
+    }
+}
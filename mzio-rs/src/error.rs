@@ -0,0 +1,249 @@
+//! Error type of the parsers, locating the problem within the input.
+
+// std imports
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+// 3rd party imports
+use thiserror::Error as ThisError;
+
+/// Location of an error within the parsed input
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// Path of the parsed file, if known
+    pub file_path: Option<PathBuf>,
+    /// Byte offset of the start of the faulty line
+    pub byte_offset: u64,
+    /// Line number (1-based) of the faulty line, 0 if unknown
+    pub line_number: u64,
+    /// Index (0-based) of the faulty record (spectrum or entry) within the input
+    pub record_index: Option<usize>,
+    /// Identifier of the faulty record, i.e. spectrum title or FASTA accession
+    pub record_id: Option<String>,
+}
+
+impl ErrorContext {
+    /// Creates a new context
+    ///
+    /// # Arguments
+    ///
+    /// * `byte_offset` - Byte offset of the start of the faulty line
+    /// * `line_number` - Line number (1-based) of the faulty line
+    ///
+    pub fn new(byte_offset: u64, line_number: u64) -> Self {
+        Self {
+            byte_offset,
+            line_number,
+            ..Default::default()
+        }
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file_path) = &self.file_path {
+            write!(f, "{}:", file_path.display())?;
+        }
+        write!(f, "line {} (byte offset {})", self.line_number, self.byte_offset)?;
+        if let Some(record_index) = self.record_index {
+            write!(f, ", record #{}", record_index + 1)?;
+        }
+        if let Some(record_id) = &self.record_id {
+            write!(f, " '{}'", record_id)?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors raised while parsing MGF and FASTA files
+#[derive(Debug, ThisError)]
+pub enum Error {
+    /// Reading from the underlying source failed
+    #[error("I/O error at {context}: {source}")]
+    Io {
+        source: std::io::Error,
+        context: ErrorContext,
+    },
+    /// Header or parameter line which cannot be interpreted
+    #[error("malformed header at {context}: {message}")]
+    MalformedHeader {
+        message: String,
+        context: ErrorContext,
+    },
+    /// Value which cannot be parsed as a number
+    #[error("invalid number '{value}' at {context}")]
+    InvalidNumber {
+        value: String,
+        context: ErrorContext,
+    },
+    /// Mandatory value which is missing, e.g. the intensity of a peak
+    #[error("{name} is missing at {context}")]
+    MissingValue {
+        name: String,
+        context: ErrorContext,
+    },
+    /// Character which is not allowed in a sequence
+    #[error("invalid residue '{residue}' at {context}")]
+    InvalidResidue {
+        residue: char,
+        context: ErrorContext,
+    },
+    /// Index which does not match the indexed content, or cannot be built from it
+    #[error("invalid index at {context}: {message}")]
+    InvalidIndex {
+        message: String,
+        context: ErrorContext,
+    },
+    /// Input ending in the middle of a record, e.g. EOF before END IONS
+    #[error("truncated record at {context}: {message}")]
+    TruncatedRecord {
+        message: String,
+        context: ErrorContext,
+    },
+    /// Setting or option value which cannot be interpreted, e.g. an unknown decoy method
+    #[error("invalid value at {context}: {message}")]
+    InvalidValue {
+        message: String,
+        context: ErrorContext,
+    },
+}
+
+// The constructors are only used by the format parsers
+#[cfg(any(feature = "fasta", feature = "mgf"))]
+impl Error {
+    /// Creates an I/O error with an empty context
+    ///
+    pub(crate) fn io(source: std::io::Error) -> Self {
+        Self::Io { source, context: ErrorContext::default() }
+    }
+
+    /// Creates a malformed header error with an empty context
+    ///
+    pub(crate) fn malformed_header(message: impl Into<String>) -> Self {
+        Self::MalformedHeader { message: message.into(), context: ErrorContext::default() }
+    }
+
+    /// Creates an invalid number error with an empty context
+    ///
+    pub(crate) fn invalid_number(value: impl Into<String>) -> Self {
+        Self::InvalidNumber { value: value.into(), context: ErrorContext::default() }
+    }
+
+    /// Creates a missing value error with an empty context
+    ///
+    pub(crate) fn missing_value(name: impl Into<String>) -> Self {
+        Self::MissingValue { name: name.into(), context: ErrorContext::default() }
+    }
+
+    /// Creates an invalid residue error with an empty context
+    ///
+    #[cfg(feature = "fasta")]
+    pub(crate) fn invalid_residue(residue: char) -> Self {
+        Self::InvalidResidue { residue, context: ErrorContext::default() }
+    }
+
+    /// Creates an invalid index error with an empty context
+    ///
+    pub(crate) fn invalid_index(message: impl Into<String>) -> Self {
+        Self::InvalidIndex { message: message.into(), context: ErrorContext::default() }
+    }
+
+    /// Creates a truncated record error with an empty context
+    ///
+    #[cfg(feature = "mgf")]
+    pub(crate) fn truncated_record(message: impl Into<String>) -> Self {
+        Self::TruncatedRecord { message: message.into(), context: ErrorContext::default() }
+    }
+
+    /// Creates an invalid value error with an empty context
+    ///
+    #[cfg(feature = "fasta")]
+    pub(crate) fn invalid_value(message: impl Into<String>) -> Self {
+        Self::InvalidValue { message: message.into(), context: ErrorContext::default() }
+    }
+}
+
+impl Error {
+    /// Returns the location of the error
+    ///
+    pub fn context(&self) -> &ErrorContext {
+        match self {
+            Self::Io { context, .. }
+            | Self::MalformedHeader { context, .. }
+            | Self::InvalidNumber { context, .. }
+            | Self::MissingValue { context, .. }
+            | Self::InvalidResidue { context, .. }
+            | Self::InvalidIndex { context, .. }
+            | Self::TruncatedRecord { context, .. }
+            | Self::InvalidValue { context, .. } => context,
+        }
+    }
+
+    /// Returns the location of the error (mutable)
+    ///
+    fn context_mut(&mut self) -> &mut ErrorContext {
+        match self {
+            Self::Io { context, .. }
+            | Self::MalformedHeader { context, .. }
+            | Self::InvalidNumber { context, .. }
+            | Self::MissingValue { context, .. }
+            | Self::InvalidResidue { context, .. }
+            | Self::InvalidIndex { context, .. }
+            | Self::TruncatedRecord { context, .. }
+            | Self::InvalidValue { context, .. } => context,
+        }
+    }
+
+    /// Replaces the location of the error
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Location of the error
+    ///
+    pub fn at(mut self, context: ErrorContext) -> Self {
+        *self.context_mut() = context;
+        self
+    }
+
+    /// Sets the path of the file in which the error occurred
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - Path of the parsed file
+    ///
+    pub fn in_file(mut self, file_path: &Path) -> Self {
+        self.context_mut().file_path = Some(file_path.to_path_buf());
+        self
+    }
+}
+
+/// Result type of the parsers
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    /// Checks that the error message locates the problem.
+    fn test_error_message() {
+        let context = ErrorContext {
+            file_path: Some(PathBuf::from("spectra.mgf")),
+            byte_offset: 1024,
+            line_number: 42,
+            record_index: Some(2),
+            record_id: Some("Velos005137.1000.1000.2".to_string()),
+        };
+        let error = Error::InvalidNumber { value: "12a.5".to_string(), context: ErrorContext::default() }.at(context.clone());
+
+        assert_eq!(error.context(), &context);
+        assert_eq!(
+            error.to_string(),
+            "invalid number '12a.5' at spectra.mgf:line 42 (byte offset 1024), record #3 'Velos005137.1000.1000.2'"
+        );
+
+        // still usable with anyhow
+        let anyhow_error: anyhow::Error = error.into();
+        assert!(anyhow_error.downcast_ref::<Error>().is_some());
+    }
+}
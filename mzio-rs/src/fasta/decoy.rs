@@ -0,0 +1,346 @@
+// std imports
+use std::fmt;
+use std::io::prelude::*;
+use std::str::FromStr;
+
+// 3rd party imports
+use fallible_iterator::FallibleIterator;
+
+// internal imports
+use crate::error::Error;
+use crate::fasta::entry::FastaEntry;
+use crate::fasta::writer::FastaWriter;
+
+/// Default accession prefix of the decoy entries
+pub const DEFAULT_DECOY_PREFIX: &str = "DECOY_";
+
+/// Method used to create the decoy sequences
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DecoyMethod {
+    /// Reversal of the whole sequence (default)
+    #[default]
+    Reverse,
+    /// Reversal of each peptide, the cleavage sites being kept in place
+    /// (e.g. `PEPTIDEKR` -> `EDITPEPKR` for trypsin)
+    PseudoReverse,
+    /// Random shuffling of the sequence, reproducible for a given seed
+    Shuffle,
+    /// Repeat-preserving decoy over the de Bruijn graph of the target k-mers: each decoy residue
+    /// only depends on the target k-mer ending at its position (the shorter prefix for the first residues),
+    /// being one of the preceding residues of this k-mer picked with a seed derived from its content.
+    /// Every segment of at least k residues shared between target proteins or repeated within a protein
+    /// therefore gives the same decoy segment (without its first k-1 residues), whatever its position.
+    DeBruijn,
+    /// Shuffling of k-mers: the sequence is cut into consecutive, non-overlapping k-mers from its first residue
+    /// (the last one may be shorter), each k-mer being shuffled with a seed derived from its content.
+    /// The same target k-mer always gives the same decoy k-mer, so repeats and segments shared between
+    /// target proteins are preserved in the decoy database when they start at the same k-mer boundary.
+    /// Unlike the de Bruijn method, the composition of the sequence is kept.
+    KmerShuffle,
+}
+
+impl FromStr for DecoyMethod {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().replace(['-', ' '], "_").as_str() {
+            "reverse" => Ok(Self::Reverse),
+            "pseudo_reverse" => Ok(Self::PseudoReverse),
+            "shuffle" => Ok(Self::Shuffle),
+            "de_bruijn" => Ok(Self::DeBruijn),
+            "kmer_shuffle" => Ok(Self::KmerShuffle),
+            _ => Err(Error::invalid_value(format!("unknown decoy method: {}", value)))
+        }
+    }
+}
+
+impl fmt::Display for DecoyMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reverse => write!(f, "reverse"),
+            Self::PseudoReverse => write!(f, "pseudo_reverse"),
+            Self::Shuffle => write!(f, "shuffle"),
+            Self::DeBruijn => write!(f, "de_bruijn"),
+            Self::KmerShuffle => write!(f, "kmer_shuffle"),
+        }
+    }
+}
+
+/// Creates decoy entries from target ones, e.g. read by `FastaReader`.
+/// Decoy entries are copies of the targets with a prefixed accession and entry name and a decoy sequence.
+/// A trailing stop codon (`*`) is kept at the end of the decoy sequence.
+#[derive(Clone, Debug)]
+pub struct DecoyGenerator {
+    method: DecoyMethod,
+    accession_prefix: String,
+    /// Residues after which the protease cleaves (pseudo-reversal)
+    cleavage_residues: String,
+    /// Seed of the random shuffling (shuffle, de Bruijn, k-mer shuffle)
+    seed: u64,
+    /// Length of the k-mers (de Bruijn, k-mer shuffle)
+    kmer_length: usize,
+}
+
+impl Default for DecoyGenerator {
+    fn default() -> Self {
+        Self::new(DecoyMethod::default())
+    }
+}
+
+impl DecoyGenerator {
+    /// Creates a new generator with the `DECOY_` accession prefix,
+    /// trypsin cleavage residues (KR), a seed of 0 and k-mers of 3 residues
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - Method used to create the decoy sequences
+    ///
+    pub fn new(method: DecoyMethod) -> Self {
+        Self {
+            method,
+            accession_prefix: DEFAULT_DECOY_PREFIX.to_owned(),
+            cleavage_residues: "KR".to_owned(),
+            seed: 0,
+            kmer_length: 3,
+        }
+    }
+
+    /// Sets the method used to create the decoy sequences
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - Decoy method
+    ///
+    pub fn set_method(&mut self, method: DecoyMethod) -> &mut Self {
+        self.method = method;
+        self
+    }
+
+    /// Returns the method used to create the decoy sequences
+    ///
+    pub fn get_method(&self) -> DecoyMethod {
+        self.method
+    }
+
+    /// Sets the prefix added to the accession of the decoy entries, e.g. `DECOY_` or `rev_`
+    ///
+    /// # Arguments
+    ///
+    /// * `accession_prefix` - Accession prefix
+    ///
+    pub fn set_accession_prefix(&mut self, accession_prefix: &str) -> &mut Self {
+        self.accession_prefix = accession_prefix.to_owned();
+        self
+    }
+
+    /// Returns the prefix added to the accession of the decoy entries
+    ///
+    pub fn get_accession_prefix(&self) -> &String {
+        &self.accession_prefix
+    }
+
+    /// Sets the residues after which the protease cleaves, kept in place by the pseudo-reversal
+    ///
+    /// # Arguments
+    ///
+    /// * `cleavage_residues` - Cleavage residues, e.g. `KR` for trypsin
+    ///
+    pub fn set_cleavage_residues(&mut self, cleavage_residues: &str) -> &mut Self {
+        self.cleavage_residues = cleavage_residues.to_owned();
+        self
+    }
+
+    /// Sets the seed of the random shuffling, the same seed always giving the same decoys
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - Random seed
+    ///
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the length of the k-mers used by the de Bruijn and k-mer shuffle methods
+    ///
+    /// # Arguments
+    ///
+    /// * `kmer_length` - K-mer length (at least 2)
+    ///
+    pub fn set_kmer_length(&mut self, kmer_length: usize) -> &mut Self {
+        self.kmer_length = kmer_length.max(2);
+        self
+    }
+
+    /// Returns true if the given accession is the one of a decoy entry
+    ///
+    /// # Arguments
+    ///
+    /// * `accession` - Entry accession
+    ///
+    pub fn is_decoy_accession(&self, accession: &str) -> bool {
+        accession.starts_with(&self.accession_prefix)
+    }
+
+    /// Creates the decoy entry of the given target one
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - Target entry
+    ///
+    pub fn create_decoy(&self, entry: &FastaEntry) -> FastaEntry {
+        FastaEntry::new(
+            entry.get_database().to_owned(),
+            format!("{}{}", self.accession_prefix, entry.get_accession()),
+            if entry.get_entry_name().is_empty() {
+                String::new()
+            } else {
+                format!("{}{}", self.accession_prefix, entry.get_entry_name())
+            },
+            entry.get_protein_name().to_owned(),
+            entry.get_keyword_attributes().clone(),
+            self.create_decoy_sequence(entry.get_sequence()),
+            // The plain header describes the target
+            None,
+        )
+    }
+
+    /// Creates the decoy sequence of the given target one.
+    /// Random decoys only depend on the seed and the target sequence,
+    /// so they are identical whatever the order of the entries.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - Target amino acid sequence
+    ///
+    pub fn create_decoy_sequence(&self, sequence: &str) -> String {
+        let (sequence, stop_codon) = match sequence.strip_suffix('*') {
+            Some(sequence) => (sequence, "*"),
+            None => (sequence, "")
+        };
+        let mut residues: Vec<char> = sequence.chars().collect();
+
+        match self.method {
+            DecoyMethod::Reverse => residues.reverse(),
+            DecoyMethod::PseudoReverse => {
+                for peptide in residues.split_inclusive_mut(|residue| self.cleavage_residues.contains(*residue)) {
+                    match peptide.split_last_mut() {
+                        Some((last_residue, others)) if self.cleavage_residues.contains(*last_residue) => others.reverse(),
+                        // C-terminal peptide
+                        _ => peptide.reverse()
+                    }
+                }
+            },
+            DecoyMethod::Shuffle => {
+                let mut rng = SplitMix64::new(self.seed ^ fnv1a_hash(sequence.as_bytes()));
+                rng.shuffle(&mut residues);
+            },
+            DecoyMethod::DeBruijn => {
+                let target_residues = residues.clone();
+                for (position, decoy_residue) in residues.iter_mut().enumerate() {
+                    let kmer = &target_residues[(position + 1).saturating_sub(self.kmer_length)..=position];
+                    if kmer.len() > 1 {
+                        let kmer_str: String = kmer.iter().collect();
+                        let mut rng = SplitMix64::new(self.seed ^ fnv1a_hash(kmer_str.as_bytes()));
+                        *decoy_residue = kmer[(rng.next_u64() % (kmer.len() as u64 - 1)) as usize];
+                    }
+                }
+            },
+            DecoyMethod::KmerShuffle => {
+                for kmer in residues.chunks_mut(self.kmer_length) {
+                    let kmer_str: String = kmer.iter().collect();
+                    let mut rng = SplitMix64::new(self.seed ^ fnv1a_hash(kmer_str.as_bytes()));
+                    rng.shuffle(kmer);
+                }
+            },
+        }
+
+        let mut decoy_sequence: String = residues.into_iter().collect();
+        decoy_sequence.push_str(stop_codon);
+        decoy_sequence
+    }
+
+    /// Returns an iterator over the decoys of the given target entries
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - Target entries, e.g. a `FastaReader`
+    ///
+    pub fn decoys<'a, I>(&'a self, entries: I) -> impl FallibleIterator<Item = FastaEntry, Error = I::Error> + 'a
+    where
+        I: FallibleIterator<Item = FastaEntry> + 'a,
+    {
+        entries.map(move |entry| Ok(self.create_decoy(&entry)))
+    }
+
+    /// Writes the concatenated target-decoy database: the target entries as read, then their decoys.
+    /// The targets are streamed twice, once for themselves and once for the decoys,
+    /// so the memory usage does not depend on the size of the database.
+    /// Returns the number of written bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `open_entries` - Opens a new stream of the target entries, e.g. `|| FastaReader::new(path, 1024, false)`
+    /// * `writer` - Destination of the target-decoy database
+    ///
+    pub fn write_target_decoy<F, I, W>(&self, mut open_entries: F, writer: &mut FastaWriter<W>) -> anyhow::Result<usize>
+    where
+        F: FnMut() -> anyhow::Result<I>,
+        I: FallibleIterator<Item = FastaEntry, Error = Error>,
+        W: Write,
+    {
+        let mut written_bytes: usize = 0;
+        let mut entries = open_entries()?;
+        while let Some(entry) = entries.next()? {
+            written_bytes += writer.write_entry(&entry)?;
+        }
+        let mut entries = open_entries()?;
+        while let Some(entry) = entries.next()? {
+            written_bytes += writer.write_entry(&self.create_decoy(&entry))?;
+        }
+
+        Ok(written_bytes)
+    }
+}
+
+/// SplitMix64 pseudo-random generator: tiny, fast and stable across platforms and versions
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Fisher-Yates shuffle
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - Items to shuffle in place
+    ///
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            let other_idx = (self.next_u64() % (idx as u64 + 1)) as usize;
+            items.swap(idx, other_idx);
+        }
+    }
+}
+
+/// FNV-1a hash, stable across platforms and versions (unlike `DefaultHasher`)
+///
+/// # Arguments
+///
+/// * `bytes` - Hashed content
+///
+fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
@@ -0,0 +1,500 @@
+// std imports
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+// 3rd party imports
+use regex::Regex;
+
+// internal imports
+use crate::error::Error;
+use crate::fasta::entry::FastaEntry;
+
+/// Monoisotopic mass of water
+const WATER_MONO_MASS: f64 = 18.0105646863;
+
+/// Protease, defined by the residues around its cleavage sites
+#[derive(Clone, Debug, Default)]
+pub enum Enzyme {
+    /// C-terminal to K and R, not before P (default)
+    #[default]
+    Trypsin,
+    /// C-terminal to K and R, even before P
+    TrypsinP,
+    /// C-terminal to K, not before P
+    LysC,
+    /// N-terminal to K
+    LysN,
+    /// C-terminal to R, not before P
+    ArgC,
+    /// N-terminal to D
+    AspN,
+    /// C-terminal to E, not before P
+    GluC,
+    /// C-terminal to F, W and Y, not before P (high specificity)
+    Chymotrypsin,
+    /// N- and C-terminal to F and L (pH 1.3)
+    Pepsin,
+    /// Between any two residues
+    NonSpecific,
+    /// Custom rule created by `Enzyme::custom`
+    Custom(CleavageRule),
+}
+
+/// Custom cleavage rule: a regular expression matched against each pair of residues surrounding a potential site,
+/// e.g. `[KR][^P]` for trypsin. The pattern is anchored at both ends and matched against uppercase residues.
+#[derive(Clone, Debug)]
+pub struct CleavageRule {
+    /// Pattern given by the user
+    pattern: String,
+    /// Pattern anchored at both ends
+    regex: Regex,
+}
+
+impl CleavageRule {
+    /// Returns the pattern of the rule, as given to `Enzyme::custom`
+    ///
+    pub fn get_pattern(&self) -> &String {
+        &self.pattern
+    }
+}
+
+impl Enzyme {
+    /// Creates a custom enzyme from a regular expression matched against each pair of residues surrounding
+    /// a potential cleavage site, e.g. `[KR][^P]` for trypsin or `.D` for Asp-N
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Regular expression on two residues
+    ///
+    pub fn custom(pattern: &str) -> Result<Self, Error> {
+        match Regex::new(&format!("^(?:{})$", pattern)) {
+            Ok(regex) => Ok(Self::Custom(CleavageRule { pattern: pattern.to_owned(), regex })),
+            Err(err) => Err(Error::invalid_value(format!("invalid cleavage rule {}: {}", pattern, err)))
+        }
+    }
+
+    /// Returns true if the enzyme cleaves between the given residues
+    ///
+    /// # Arguments
+    ///
+    /// * `residue_pair` - The residues before and after the potential site, in uppercase
+    ///
+    fn cleaves(&self, residue_pair: &[u8]) -> bool {
+        let (before, after) = (residue_pair[0], residue_pair[1]);
+        match self {
+            Self::Trypsin => matches!(before, b'K' | b'R') && after != b'P',
+            Self::TrypsinP => matches!(before, b'K' | b'R'),
+            Self::LysC => before == b'K' && after != b'P',
+            Self::LysN => after == b'K',
+            Self::ArgC => before == b'R' && after != b'P',
+            Self::AspN => after == b'D',
+            Self::GluC => before == b'E' && after != b'P',
+            Self::Chymotrypsin => matches!(before, b'F' | b'W' | b'Y') && after != b'P',
+            Self::Pepsin => matches!(before, b'F' | b'L') || matches!(after, b'F' | b'L'),
+            Self::NonSpecific => true,
+            Self::Custom(rule) => std::str::from_utf8(residue_pair).is_ok_and(|residue_pair| rule.regex.is_match(residue_pair)),
+        }
+    }
+}
+
+impl FromStr for Enzyme {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().replace(['-', '_', ' '], "").as_str() {
+            "trypsin" => Ok(Self::Trypsin),
+            "trypsin/p" => Ok(Self::TrypsinP),
+            "lysc" => Ok(Self::LysC),
+            "lysn" => Ok(Self::LysN),
+            "argc" => Ok(Self::ArgC),
+            "aspn" => Ok(Self::AspN),
+            "gluc" => Ok(Self::GluC),
+            "chymotrypsin" => Ok(Self::Chymotrypsin),
+            "pepsin" => Ok(Self::Pepsin),
+            "nonspecific" | "unspecific" => Ok(Self::NonSpecific),
+            _ => Err(Error::invalid_value(format!("unknown enzyme: {}", value)))
+        }
+    }
+}
+
+impl fmt::Display for Enzyme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Trypsin => write!(f, "Trypsin"),
+            Self::TrypsinP => write!(f, "Trypsin/P"),
+            Self::LysC => write!(f, "Lys-C"),
+            Self::LysN => write!(f, "Lys-N"),
+            Self::ArgC => write!(f, "Arg-C"),
+            Self::AspN => write!(f, "Asp-N"),
+            Self::GluC => write!(f, "Glu-C"),
+            Self::Chymotrypsin => write!(f, "Chymotrypsin"),
+            Self::Pepsin => write!(f, "Pepsin"),
+            Self::NonSpecific => write!(f, "non-specific"),
+            Self::Custom(rule) => write!(f, "{}", rule.pattern),
+        }
+    }
+}
+
+/// Number of peptide termini which have to match a cleavage site
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CleavageSpecificity {
+    /// Both termini (default)
+    #[default]
+    Specific,
+    /// At least one terminus
+    SemiSpecific,
+}
+
+/// Peptide produced by the digestion of a protein
+#[derive(Clone, Copy)]
+pub struct Peptide<'a> {
+    sequence: &'a str,
+    start: usize,
+    end: usize,
+    missed_cleavages: usize,
+    mass: Option<f64>,
+    protein: &'a FastaEntry,
+}
+
+impl<'a> Peptide<'a> {
+    /// Returns the amino acid sequence
+    ///
+    pub fn get_sequence(&self) -> &'a str {
+        self.sequence
+    }
+
+    /// Returns the position of the first residue in the protein (0-based)
+    ///
+    pub fn get_start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the position following the last residue in the protein (0-based, exclusive)
+    ///
+    pub fn get_end(&self) -> usize {
+        self.end
+    }
+
+    /// Returns the number of cleavage sites within the peptide
+    ///
+    pub fn get_missed_cleavages(&self) -> usize {
+        self.missed_cleavages
+    }
+
+    /// Returns the monoisotopic mass, None if the sequence contains ambiguous residues (e.g. X, B, Z)
+    ///
+    pub fn get_mass(&self) -> Option<f64> {
+        self.mass
+    }
+
+    /// Returns the protein the peptide comes from
+    ///
+    pub fn get_protein(&self) -> &'a FastaEntry {
+        self.protein
+    }
+}
+
+/// In-silico protease digestion of protein sequences
+#[derive(Clone, Debug)]
+pub struct Digester {
+    enzyme: Enzyme,
+    specificity: CleavageSpecificity,
+    max_missed_cleavages: usize,
+    min_length: usize,
+    max_length: usize,
+    min_mass: Option<f64>,
+    max_mass: Option<f64>,
+    /// Whether to also produce the peptides of the proteins without their N-terminal methionine
+    n_term_met_excision: bool,
+}
+
+impl Default for Digester {
+    fn default() -> Self {
+        Self::new(Enzyme::default())
+    }
+}
+
+impl Digester {
+    /// Creates a new digester: specific cleavage, up to 2 missed cleavages,
+    /// peptides of 7 to 50 residues, no mass bounds, with N-terminal methionine excision
+    ///
+    /// # Arguments
+    ///
+    /// * `enzyme` - Protease
+    ///
+    pub fn new(enzyme: Enzyme) -> Self {
+        Self {
+            enzyme,
+            specificity: CleavageSpecificity::Specific,
+            max_missed_cleavages: 2,
+            min_length: 7,
+            max_length: 50,
+            min_mass: None,
+            max_mass: None,
+            n_term_met_excision: true,
+        }
+    }
+
+    /// Sets the protease
+    ///
+    /// # Arguments
+    ///
+    /// * `enzyme` - Protease
+    ///
+    pub fn set_enzyme(&mut self, enzyme: Enzyme) -> &mut Self {
+        self.enzyme = enzyme;
+        self
+    }
+
+    /// Returns the protease
+    ///
+    pub fn get_enzyme(&self) -> &Enzyme {
+        &self.enzyme
+    }
+
+    /// Sets the number of peptide termini which have to match a cleavage site
+    ///
+    /// # Arguments
+    ///
+    /// * `specificity` - Cleavage specificity
+    ///
+    pub fn set_specificity(&mut self, specificity: CleavageSpecificity) -> &mut Self {
+        self.specificity = specificity;
+        self
+    }
+
+    /// Sets the maximal number of missed cleavages, ignored by the non-specific enzyme
+    ///
+    /// # Arguments
+    ///
+    /// * `max_missed_cleavages` - Maximal number of cleavage sites within a peptide
+    ///
+    pub fn set_max_missed_cleavages(&mut self, max_missed_cleavages: usize) -> &mut Self {
+        self.max_missed_cleavages = max_missed_cleavages;
+        self
+    }
+
+    /// Sets the length bounds of the peptides
+    ///
+    /// # Arguments
+    ///
+    /// * `min_length` - Minimal number of residues
+    /// * `max_length` - Maximal number of residues
+    ///
+    pub fn set_length_range(&mut self, min_length: usize, max_length: usize) -> &mut Self {
+        self.min_length = min_length.max(1);
+        self.max_length = max_length;
+        self
+    }
+
+    /// Sets the monoisotopic mass bounds of the peptides.
+    /// Peptides with ambiguous residues are dropped when a bound is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_mass` - Minimal mass, None to disable
+    /// * `max_mass` - Maximal mass, None to disable
+    ///
+    pub fn set_mass_range(&mut self, min_mass: Option<f64>, max_mass: Option<f64>) -> &mut Self {
+        self.min_mass = min_mass;
+        self.max_mass = max_mass;
+        self
+    }
+
+    /// Sets whether to also produce the peptides of the proteins without their N-terminal methionine
+    ///
+    /// # Arguments
+    ///
+    /// * `n_term_met_excision` - Whether to consider the N-terminal methionine excision or not
+    ///
+    pub fn set_n_term_met_excision(&mut self, n_term_met_excision: bool) -> &mut Self {
+        self.n_term_met_excision = n_term_met_excision;
+        self
+    }
+
+    /// Returns the positions of the cleavage sites of the given sequence,
+    /// including the protein termini, e.g. `[0, 5, 12]` for `PEPTKSEQENCE` digested by trypsin
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - Protein sequence
+    ///
+    pub fn find_cleavage_sites(&self, sequence: &str) -> Vec<usize> {
+        // The cleavage rules are defined on uppercase residues
+        let residues = sequence.to_ascii_uppercase().into_bytes();
+
+        let mut sites = Vec::with_capacity(residues.len() / 8 + 2);
+        sites.push(0);
+        for (pair_idx, residue_pair) in residues.windows(2).enumerate() {
+            if self.enzyme.cleaves(residue_pair) {
+                sites.push(pair_idx + 1);
+            }
+        }
+        if !residues.is_empty() {
+            sites.push(residues.len());
+        }
+
+        sites
+    }
+
+    /// Digests the sequence of the given protein.
+    /// Peptides are sorted by start then end position, each location being given once.
+    ///
+    /// # Arguments
+    ///
+    /// * `protein` - Protein entry
+    ///
+    pub fn digest<'a>(&self, protein: &'a FastaEntry) -> Vec<Peptide<'a>> {
+        let sequence = protein.get_sequence().as_str();
+        self.digest_sequence(sequence).into_iter()
+            .filter_map(|(start, end, missed_cleavages)| {
+                let peptide_sequence = &sequence[start..end];
+                let mass = calc_mono_mass(peptide_sequence);
+                if self.min_mass.is_some_and(|min_mass| !mass.is_some_and(|mass| mass >= min_mass)) ||
+                    self.max_mass.is_some_and(|max_mass| !mass.is_some_and(|mass| mass <= max_mass)) {
+                    return None;
+                }
+
+                Some(Peptide { sequence: peptide_sequence, start, end, missed_cleavages, mass, protein })
+            })
+            .collect()
+    }
+
+    /// Digests the sequences of the given proteins
+    ///
+    /// # Arguments
+    ///
+    /// * `proteins` - Protein entries
+    ///
+    pub fn digest_all<'a, I>(&'a self, proteins: I) -> impl Iterator<Item = Peptide<'a>> + 'a
+    where
+        I: IntoIterator<Item = &'a FastaEntry>,
+        I::IntoIter: 'a,
+    {
+        proteins.into_iter().flat_map(move |protein| self.digest(protein))
+    }
+
+    /// Returns the location (start, end) and number of missed cleavages of the peptides of the given sequence,
+    /// sorted by start then end position, before applying the mass bounds
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - Protein sequence
+    ///
+    pub fn digest_sequence(&self, sequence: &str) -> Vec<(usize, usize, usize)> {
+        if !sequence.is_ascii() || sequence.is_empty() {
+            return Vec::new();
+        }
+
+        let sites = self.find_cleavage_sites(sequence);
+        let has_excised_met = self.n_term_met_excision && sequence.starts_with(['M', 'm']) && sequence.len() > 1;
+
+        // Number of cleavage sites strictly between start and end
+        let count_missed_cleavages = |start: usize, end: usize| -> usize {
+            sites.partition_point(|site| *site < end) - sites.partition_point(|site| *site <= start)
+        };
+        let max_missed_cleavages = match self.enzyme {
+            Enzyme::NonSpecific => usize::MAX,
+            _ => self.max_missed_cleavages,
+        };
+        let is_valid_length = |start: usize, end: usize| -> bool {
+            end - start >= self.min_length && end - start <= self.max_length
+        };
+
+        // The protein start without methionine is handled as a cleavage site
+        let mut start_sites = sites[..sites.len() - 1].to_vec();
+        if has_excised_met && sites[1] != 1 {
+            start_sites.insert(1, 1);
+        }
+
+        let mut peptides: Vec<(usize, usize, usize)> = Vec::new();
+        match self.specificity {
+            CleavageSpecificity::Specific => {
+                for start in start_sites.iter().copied() {
+                    let first_end_idx = sites.partition_point(|site| *site <= start);
+                    for end in sites[first_end_idx..].iter().copied() {
+                        let missed_cleavages = count_missed_cleavages(start, end);
+                        if missed_cleavages > max_missed_cleavages || end - start > self.max_length {
+                            break;
+                        }
+                        if is_valid_length(start, end) {
+                            peptides.push((start, end, missed_cleavages));
+                        }
+                    }
+                }
+            },
+            CleavageSpecificity::SemiSpecific => {
+                let mut locations: HashSet<(usize, usize)> = HashSet::new();
+                let mut add_peptide = |start: usize, end: usize| {
+                    let missed_cleavages = count_missed_cleavages(start, end);
+                    if missed_cleavages <= max_missed_cleavages && is_valid_length(start, end) && locations.insert((start, end)) {
+                        peptides.push((start, end, missed_cleavages));
+                    }
+                };
+
+                // Specific N-terminus
+                for start in start_sites.iter().copied() {
+                    for end in start + self.min_length..=(start + self.max_length).min(sequence.len()) {
+                        add_peptide(start, end);
+                    }
+                }
+                // Specific C-terminus
+                for end in sites[1..].iter().copied() {
+                    for start in end.saturating_sub(self.max_length)..=end.saturating_sub(self.min_length) {
+                        add_peptide(start, end);
+                    }
+                }
+
+                peptides.sort_unstable();
+            },
+        }
+
+        peptides
+    }
+}
+
+/// Returns the monoisotopic residue mass of the given amino acid, None if ambiguous (e.g. X, B, Z)
+///
+/// # Arguments
+///
+/// * `residue` - Amino acid one-letter code
+///
+pub fn get_residue_mono_mass(residue: u8) -> Option<f64> {
+    let mass = match residue.to_ascii_uppercase() {
+        b'A' => 71.0371138,
+        b'R' => 156.1011110,
+        b'N' => 114.0429275,
+        b'D' => 115.0269431,
+        b'C' => 103.0091845,
+        b'E' => 129.0425931,
+        b'Q' => 128.0585775,
+        b'G' => 57.0214637,
+        b'H' => 137.0589119,
+        b'I' => 113.0840640,
+        b'L' => 113.0840640,
+        b'K' => 128.0949630,
+        b'M' => 131.0404846,
+        b'F' => 147.0684139,
+        b'P' => 97.0527638,
+        b'S' => 87.0320284,
+        b'T' => 101.0476785,
+        b'U' => 150.9536356,
+        b'W' => 186.0793130,
+        b'Y' => 163.0633285,
+        b'V' => 99.0684139,
+        b'O' => 237.1476924,
+        _ => return None
+    };
+    Some(mass)
+}
+
+/// Returns the monoisotopic mass of the given unmodified peptide, None if it contains ambiguous residues
+///
+/// # Arguments
+///
+/// * `sequence` - Peptide sequence
+///
+pub fn calc_mono_mass(sequence: &str) -> Option<f64> {
+    sequence.bytes().try_fold(WATER_MONO_MASS, |mass, residue| Some(mass + get_residue_mono_mass(residue)?))
+}
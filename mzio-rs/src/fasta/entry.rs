@@ -1,6 +1,15 @@
+// std imports
 use std::collections::HashMap;
 
+// 3rd party imports
+use serde::{Serialize, Deserialize};
+
+// internal imports
+use crate::error::{Error, Result};
+use crate::fasta::header::{is_uniref_database, AutoHeaderParser, FastaHeaderParser, GenericHeaderParser};
+
 /// Keeps all information of FASTA entry
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FastaEntry {
     database: String,
     accession: String,
@@ -8,6 +17,7 @@ pub struct FastaEntry {
     protein_name: String,
     keyword_attributes: HashMap<String, String>,
     sequence: String,
+    #[serde(default)]
     plain_header: Option<String>
 }
 
@@ -21,6 +31,7 @@ impl FastaEntry {
     /// * `protein_name` - Protein name
     /// * `keyword_attributes` - Additional keyword attributes, e.g. OX=381666
     /// * `sequence` - Amino acid sequence
+    /// * `plain_header` - Plain header the entry was parsed from, if any
     /// 
     pub fn new(database: String, accession: String, entry_name: String, protein_name: String,
         keyword_attributes: HashMap<String, String>, sequence: String, plain_header: Option<String>) -> Self {
@@ -78,4 +89,296 @@ impl FastaEntry {
         pub fn get_plain_header(&self) -> &Option<String> {
             &self.plain_header
         }
-}
\ No newline at end of file
+
+        /// Sets the database type
+        ///
+        /// # Arguments
+        ///
+        /// * `database` - The FASTA database, e.g. `sp`
+        ///
+        pub fn set_database(&mut self, database: String) -> &mut Self {
+            self.database = database;
+            self
+        }
+
+        /// Sets the accession
+        ///
+        /// # Arguments
+        ///
+        /// * `accession` - Entry accession
+        ///
+        pub fn set_accession(&mut self, accession: String) -> &mut Self {
+            self.accession = accession;
+            self
+        }
+
+        /// Sets the entry name
+        ///
+        /// # Arguments
+        ///
+        /// * `entry_name` - Entry name
+        ///
+        pub fn set_entry_name(&mut self, entry_name: String) -> &mut Self {
+            self.entry_name = entry_name;
+            self
+        }
+
+        /// Sets the protein name
+        ///
+        /// # Arguments
+        ///
+        /// * `protein_name` - Protein name
+        ///
+        pub fn set_protein_name(&mut self, protein_name: String) -> &mut Self {
+            self.protein_name = protein_name;
+            self
+        }
+
+        /// Replaces all the keyword attributes
+        ///
+        /// # Arguments
+        ///
+        /// * `keyword_attributes` - Keyword attributes, e.g. OX=381666
+        ///
+        pub fn set_keyword_attributes(&mut self, keyword_attributes: HashMap<String, String>) -> &mut Self {
+            self.keyword_attributes = keyword_attributes;
+            self
+        }
+
+        /// Sets a keyword attribute, replacing the value of an existing key
+        ///
+        /// # Arguments
+        ///
+        /// * `key` - Attribute key, e.g. `GN`
+        /// * `value` - Attribute value
+        ///
+        pub fn set_keyword_attribute(&mut self, key: String, value: String) -> &mut Self {
+            self.keyword_attributes.insert(key, value);
+            self
+        }
+
+        /// Removes a keyword attribute and returns its value, if any
+        ///
+        /// # Arguments
+        ///
+        /// * `key` - Attribute key, e.g. `GN`
+        ///
+        pub fn remove_keyword_attribute(&mut self, key: &str) -> Option<String> {
+            self.keyword_attributes.remove(key)
+        }
+
+        /// Sets the amino acid sequence
+        ///
+        /// # Arguments
+        ///
+        /// * `sequence` - Amino acid sequence
+        ///
+        pub fn set_sequence(&mut self, sequence: String) -> &mut Self {
+            self.sequence = sequence;
+            self
+        }
+
+        /// Sets the plain header. It is not updated by the other setters.
+        ///
+        /// # Arguments
+        ///
+        /// * `plain_header` - Plain header, None to drop it
+        ///
+        pub fn set_plain_header(&mut self, plain_header: Option<String>) -> &mut Self {
+            self.plain_header = plain_header;
+            self
+        }
+
+        /// Creates a new FASTA entry from the given header and sequence,
+        /// detecting the header layout (UniProtKB, UniRef, NCBI, Ensembl or generic).
+        /// The header is kept as plain header.
+        ///
+        /// # Arguments
+        ///
+        /// * `header` - A FASTA header, with or without '>'
+        /// * `sequence` - Amino acid sequence
+        ///
+        pub fn from_header(header: &str, sequence: &str) -> Result<Self> {
+            let parsed_header = AutoHeaderParser.parse(header)
+                .or_else(|| GenericHeaderParser.parse(header))
+                .ok_or_else(|| Error::malformed_header("empty header"))?;
+
+            Ok(Self::new(
+                parsed_header.database,
+                parsed_header.accession,
+                parsed_header.entry_name,
+                parsed_header.protein_name,
+                parsed_header.keyword_attributes,
+                sequence.replace('\n', ""),
+                Some(header.trim().to_owned())
+            ))
+        }
+
+        /// Creates the FASTA header of the entry, as written by `FastaWriter`, in the layout matching its database,
+        /// so that entries read with the default header parser are written back as they were read:
+        /// * UniProtKB, e.g. `>sp|P27748|ACOX_CUPNH Acetoin catabolism protein X OX=381666 GN=acoX`
+        /// * UniRef, e.g. `>UniRef100_Q6GZX4 Putative transcription factor 001R n=1 Tax=Frog virus 3 TaxID=654924 RepID=001R_FRG3G`
+        /// * NCBI, e.g. `>XP_012345.1 description [Homo sapiens]` or `>gi|4504347|ref|NP_000549.1| description [Homo sapiens]`
+        /// * Ensembl, e.g. `>ENSP00000354587.3 pep gene:ENSG00000198804.2 gene_symbol:MT-CO1 description:...`
+        /// * `>accession description` for entries without database (e.g. read with the generic header parser)
+        ///
+        /// The UniRef and Ensembl keyword attributes are written in the order used by these databases,
+        /// unknown keyword attributes come after them.
+        ///
+        /// # Arguments
+        ///
+        /// * `sort_keyword_attributes` - If true the keyword attributes will be sorted (for testing and readability reasons)
+        ///
+        pub fn to_header(&self, sort_keyword_attributes: bool) -> String {
+            let mut header = ">".to_string();
+            if self.keyword_attributes.contains_key("gi") || (matches!(self.database.as_str(), "ref" | "gb") && self.entry_name.is_empty()) {
+                self.push_ncbi_header(&mut header, sort_keyword_attributes);
+            } else if self.database == "ensembl" {
+                self.push_ensembl_header(&mut header, sort_keyword_attributes);
+            } else if is_uniref_database(&self.database) {
+                header.push_str(&self.accession);
+                push_description(&mut header, &self.protein_name);
+                let (known_attributes, other_attributes) = format_keyword_attributes(&self.keyword_attributes, &UNIREF_KEY_ORDER, &[], "=",
+                    sort_keyword_attributes);
+                push_words(&mut header, &known_attributes);
+                push_words(&mut header, &other_attributes);
+            } else {
+                if self.database.is_empty() {
+                    header.push_str(&self.accession);
+                } else {
+                    header.push_str(&self.database);
+                    header.push('|');
+                    header.push_str(&self.accession);
+                    header.push('|');
+                    header.push_str(&self.entry_name);
+                }
+                push_description(&mut header, &self.protein_name);
+                let (_, attributes) = format_keyword_attributes(&self.keyword_attributes, &[], &[], "=", sort_keyword_attributes);
+                push_words(&mut header, &attributes);
+            }
+            header
+        }
+
+        /// Appends the NCBI layout of the header, i.e. `accession description [organism]`
+        /// or `gi|<gi number>|<database>|<accession>| description [organism]`.
+        /// The organism is taken from the `OS` keyword attribute.
+        ///
+        /// # Arguments
+        ///
+        /// * `header` - Header being created
+        /// * `sort_keyword_attributes` - If true the keyword attributes will be sorted
+        ///
+        fn push_ncbi_header(&self, header: &mut String, sort_keyword_attributes: bool) {
+            match self.keyword_attributes.get("gi") {
+                Some(gi_number) if self.database.is_empty() && &self.accession == gi_number => {
+                    header.push_str("gi|");
+                    header.push_str(gi_number);
+                },
+                Some(gi_number) => {
+                    header.push_str("gi|");
+                    header.push_str(gi_number);
+                    header.push('|');
+                    header.push_str(&self.database);
+                    header.push('|');
+                    header.push_str(&self.accession);
+                    header.push('|');
+                },
+                None => header.push_str(&self.accession)
+            }
+            push_description(header, &self.protein_name);
+
+            // gi number and organism are part of the layout
+            let (_, other_attributes) = format_keyword_attributes(&self.keyword_attributes, &[], &["gi", "OS"], "=", sort_keyword_attributes);
+            push_words(header, &other_attributes);
+            if let Some(organism) = self.keyword_attributes.get("OS") {
+                header.push_str(" [");
+                header.push_str(organism);
+                header.push(']');
+            }
+        }
+
+        /// Appends the Ensembl layout of the header, i.e. `accession type key:value... description:protein name`.
+        ///
+        /// # Arguments
+        ///
+        /// * `header` - Header being created
+        /// * `sort_keyword_attributes` - If true the keyword attributes will be sorted
+        ///
+        fn push_ensembl_header(&self, header: &mut String, sort_keyword_attributes: bool) {
+            header.push_str(&self.accession);
+            if let Some(sequence_type) = self.keyword_attributes.get("type") {
+                header.push(' ');
+                header.push_str(sequence_type);
+            }
+
+            // the location (e.g. `chromosome:GRCh38:MT:5904:7445:1`), whose key varies, comes first
+            let (known_attributes, location_attributes) = format_keyword_attributes(&self.keyword_attributes, &ENSEMBL_KEY_ORDER, &["type"], ":",
+                sort_keyword_attributes);
+            push_words(header, &location_attributes);
+            push_words(header, &known_attributes);
+            if !self.protein_name.is_empty() {
+                header.push_str(" description:");
+                header.push_str(&self.protein_name);
+            }
+        }
+}
+
+/// Keyword attributes of UniRef headers, in writing order
+const UNIREF_KEY_ORDER: [&str; 4] = ["n", "Tax", "TaxID", "RepID"];
+
+/// Keyword attributes of Ensembl headers written after the location (e.g. `chromosome:GRCh38:MT:5904:7445:1`), in writing order
+const ENSEMBL_KEY_ORDER: [&str; 5] = ["gene", "transcript", "gene_biotype", "transcript_biotype", "gene_symbol"];
+
+/// Appends the given description to the header, separated by a space
+///
+/// # Arguments
+///
+/// * `header` - Header being created
+/// * `description` - Description, e.g. the protein name, nothing is appended if empty
+///
+fn push_description(header: &mut String, description: &str) {
+    if !description.is_empty() {
+        header.push(' ');
+        header.push_str(description);
+    }
+}
+
+/// Formats the keyword attributes as `key<separator>value`, returning the ones with the given known keys
+/// (in the order of the known keys) and the other ones (in hash order unless sorted).
+///
+/// # Arguments
+///
+/// * `keyword_attributes` - Keyword attributes
+/// * `known_keys` - Keys of the header layout, in writing order
+/// * `excluded_keys` - Keys written separately by the header layout, e.g. `OS` for NCBI headers
+/// * `separator` - Separator between key and value, e.g. `=`
+/// * `sort_keyword_attributes` - If true the attributes with unknown keys will be sorted
+///
+fn format_keyword_attributes(keyword_attributes: &HashMap<String, String>, known_keys: &[&str], excluded_keys: &[&str],
+    separator: &str, sort_keyword_attributes: bool) -> (Vec<String>, Vec<String>) {
+    let known_attributes: Vec<String> = known_keys.iter()
+        .filter_map(|key| keyword_attributes.get(*key).map(|value| format!("{}{}{}", key, separator, value)))
+        .collect();
+    let mut other_attributes: Vec<String> = keyword_attributes.iter()
+        .filter(|(key, _)| !known_keys.contains(&key.as_str()) && !excluded_keys.contains(&key.as_str()))
+        .map(|(key, value)| format!("{}{}{}", key, separator, value))
+        .collect();
+    if sort_keyword_attributes {
+        other_attributes.sort();
+    }
+    (known_attributes, other_attributes)
+}
+
+/// Appends the given words to the header, each one preceded by a space
+///
+/// # Arguments
+///
+/// * `header` - Header being created
+/// * `words` - Words to append, e.g. formatted keyword attributes
+///
+fn push_words(header: &mut String, words: &[String]) {
+    for word in words {
+        header.push(' ');
+        header.push_str(word);
+    }
+}
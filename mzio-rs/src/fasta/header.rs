@@ -0,0 +1,483 @@
+// std imports
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+// internal imports
+use crate::error::Error;
+
+/// Information extracted from a FASTA header line
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FastaHeader {
+    /// Database, e.g. `sp`, `tr`, `ref` or `UniRef100`, empty if unknown
+    pub database: String,
+    /// Accession, e.g. `P27748`
+    pub accession: String,
+    /// Entry name, e.g. `ACOX_CUPNH`, empty if unknown
+    pub entry_name: String,
+    /// Protein name or free text description
+    pub protein_name: String,
+    /// Additional keyword attributes, e.g. OX=381666
+    pub keyword_attributes: HashMap<String, String>,
+}
+
+/// Extracts the entry information from a FASTA header line
+pub trait FastaHeaderParser: Send + Sync {
+    /// Parses the given header, returns None if it does not match the expected layout.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - A FASTA header, with or without the leading '>'
+    ///
+    fn parse(&self, header: &str) -> Option<FastaHeader>;
+}
+
+/// Parser for UniProtKB headers, e.g. `>sp|P27748|ACOX_CUPNH Acetoin catabolism protein X OS=Cupriavidus necator OX=381666`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UniProtKbHeaderParser;
+
+impl FastaHeaderParser for UniProtKbHeaderParser {
+    fn parse(&self, header: &str) -> Option<FastaHeader> {
+        let (identifier, description) = split_identifier(header)?;
+
+        // Split by '|' and extract database, accession and entry name
+        let mut identifier_split = identifier.splitn(3, '|');
+        let database = identifier_split.next()?;
+        let accession = identifier_split.next()?;
+        let entry_name = identifier_split.next()?;
+        if accession.is_empty() {
+            return None;
+        }
+
+        let (protein_name, keyword_attributes) = split_keyword_attributes(description);
+
+        Some(FastaHeader {
+            database: database.to_string(),
+            accession: accession.to_string(),
+            entry_name: entry_name.to_string(),
+            protein_name,
+            keyword_attributes,
+        })
+    }
+}
+
+/// Parser for UniRef headers, e.g. `>UniRef100_Q6GZX4 Putative transcription factor 001R n=1 Tax=Frog virus 3 TaxID=654924 RepID=001R_FRG3G`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UniRefHeaderParser;
+
+impl FastaHeaderParser for UniRefHeaderParser {
+    fn parse(&self, header: &str) -> Option<FastaHeader> {
+        let (identifier, description) = split_identifier(header)?;
+
+        // The cluster identifier is prefixed by the database, e.g. UniRef90_Q6GZX4
+        let (database, _) = identifier.split_once('_')?;
+        if !is_uniref_database(database) {
+            return None;
+        }
+
+        let (protein_name, keyword_attributes) = split_keyword_attributes(description);
+
+        Some(FastaHeader {
+            database: database.to_string(),
+            accession: identifier.to_string(),
+            entry_name: keyword_attributes.get("RepID").cloned().unwrap_or_default(),
+            protein_name,
+            keyword_attributes,
+        })
+    }
+}
+
+/// Parser for NCBI RefSeq/GenBank headers, e.g. `>XP_012345.1 description [Homo sapiens]`
+/// or the legacy `>gi|4504347|ref|NP_000549.1| description [Homo sapiens]`.
+/// The organism given between brackets is stored in the `OS` keyword attribute.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NcbiHeaderParser;
+
+impl FastaHeaderParser for NcbiHeaderParser {
+    fn parse(&self, header: &str) -> Option<FastaHeader> {
+        let (identifier, description) = split_identifier(header)?;
+
+        let mut keyword_attributes = HashMap::new();
+        let (database, accession) = if let Some(gi_identifier) = identifier.strip_prefix("gi|") {
+            // gi|<gi number>|<database>|<accession>|<locus>
+            let mut identifier_split = gi_identifier.split('|');
+            let gi_number = identifier_split.next()?;
+            let database = identifier_split.next().unwrap_or_default();
+            let accession = identifier_split.next().filter(|acc| !acc.is_empty()).unwrap_or(gi_number);
+            keyword_attributes.insert("gi".to_string(), gi_number.to_string());
+            (database.to_string(), accession.to_string())
+        } else if is_ncbi_accession(identifier) {
+            // RefSeq accessions have a two letters prefix followed by '_', e.g. NP_, XP_, WP_
+            let database = if identifier.as_bytes().get(2) == Some(&b'_') { "ref" } else { "gb" };
+            (database.to_string(), identifier.to_string())
+        } else {
+            return None;
+        };
+
+        // Extract the organism, given between brackets at the end of the description
+        let mut protein_name = description;
+        if let Some(description_without_organism) = description.strip_suffix(']') {
+            if let Some(idx) = description_without_organism.rfind(" [") {
+                keyword_attributes.insert("OS".to_string(), description_without_organism[idx + 2..].to_string());
+                protein_name = description_without_organism[..idx].trim_end();
+            }
+        }
+
+        Some(FastaHeader {
+            database,
+            accession,
+            entry_name: String::new(),
+            protein_name: protein_name.to_string(),
+            keyword_attributes,
+        })
+    }
+}
+
+/// Parser for Ensembl headers, e.g.
+/// `>ENSP00000354587.3 pep chromosome:GRCh38:MT:5904:7445:1 gene:ENSG00000198804.2 gene_symbol:MT-CO1 description:mitochondrially encoded cytochrome c oxidase I`.
+/// Every `key:value` token is stored as keyword attribute, the sequence type (e.g. `pep`) under the `type` key.
+/// The gene symbol is used as entry name and the description as protein name.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnsemblHeaderParser;
+
+impl FastaHeaderParser for EnsemblHeaderParser {
+    fn parse(&self, header: &str) -> Option<FastaHeader> {
+        let (identifier, description) = split_identifier(header)?;
+        if !is_ensembl_accession(identifier) {
+            return None;
+        }
+
+        let mut keyword_attributes: HashMap<String, String> = HashMap::new();
+        let mut protein_name = String::new();
+        let mut remaining_description = description;
+        while !remaining_description.is_empty() {
+            // The description runs until the end of the line
+            if let Some(value) = remaining_description.strip_prefix("description:") {
+                protein_name = value.to_string();
+                break;
+            }
+            let (token, tail) = remaining_description.split_once(' ').unwrap_or((remaining_description, ""));
+            match token.split_once(':') {
+                Some((key, value)) => keyword_attributes.insert(key.to_string(), value.to_string()),
+                None => keyword_attributes.insert("type".to_string(), token.to_string()),
+            };
+            remaining_description = tail.trim_start();
+        }
+
+        Some(FastaHeader {
+            database: "ensembl".to_string(),
+            accession: identifier.to_string(),
+            entry_name: keyword_attributes.get("gene_symbol").cloned().unwrap_or_default(),
+            protein_name,
+            keyword_attributes,
+        })
+    }
+}
+
+/// Parser for headers made of an accession followed by a free text description,
+/// e.g. `>contaminant_KERATIN01 Keratin, type II cytoskeletal 1`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GenericHeaderParser;
+
+impl FastaHeaderParser for GenericHeaderParser {
+    fn parse(&self, header: &str) -> Option<FastaHeader> {
+        let (identifier, description) = split_identifier(header)?;
+
+        Some(FastaHeader {
+            accession: identifier.to_string(),
+            protein_name: description.to_string(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Parser keeping the whole header (without '>') as accession
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RawHeaderParser;
+
+impl FastaHeaderParser for RawHeaderParser {
+    fn parse(&self, header: &str) -> Option<FastaHeader> {
+        let header = header.strip_prefix('>').unwrap_or(header).trim();
+        if header.is_empty() {
+            return None;
+        }
+
+        Some(FastaHeader {
+            accession: header.to_string(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Parser detecting the layout of each header,
+/// falling back to the generic "accession + description" layout for unknown ones.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AutoHeaderParser;
+
+impl FastaHeaderParser for AutoHeaderParser {
+    fn parse(&self, header: &str) -> Option<FastaHeader> {
+        FastaHeaderFormat::detect(header).parse(header)
+            .or_else(|| GenericHeaderParser.parse(header))
+    }
+}
+
+/// Built-in FASTA header layouts
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FastaHeaderFormat {
+    UniProtKb,
+    UniRef,
+    Ncbi,
+    Ensembl,
+    Generic,
+    Raw,
+    #[default]
+    Auto,
+}
+
+impl FastaHeaderFormat {
+    /// Detects the layout of the given header, `Generic` if unknown.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - A FASTA header, with or without the leading '>'
+    ///
+    pub fn detect(header: &str) -> Self {
+        let identifier = match split_identifier(header) {
+            Some((identifier, _)) => identifier,
+            None => return Self::Generic
+        };
+
+        if identifier.starts_with("gi|") {
+            Self::Ncbi
+        } else if identifier.split_once('_').is_some_and(|(prefix, _)| is_uniref_database(prefix)) {
+            Self::UniRef
+        } else if identifier.matches('|').count() >= 2 {
+            Self::UniProtKb
+        } else if is_ensembl_accession(identifier) {
+            Self::Ensembl
+        } else if is_ncbi_accession(identifier) {
+            Self::Ncbi
+        } else {
+            Self::Generic
+        }
+    }
+}
+
+impl FastaHeaderParser for FastaHeaderFormat {
+    fn parse(&self, header: &str) -> Option<FastaHeader> {
+        match self {
+            Self::UniProtKb => UniProtKbHeaderParser.parse(header),
+            Self::UniRef => UniRefHeaderParser.parse(header),
+            Self::Ncbi => NcbiHeaderParser.parse(header),
+            Self::Ensembl => EnsemblHeaderParser.parse(header),
+            Self::Generic => GenericHeaderParser.parse(header),
+            Self::Raw => RawHeaderParser.parse(header),
+            Self::Auto => AutoHeaderParser.parse(header),
+        }
+    }
+}
+
+impl FromStr for FastaHeaderFormat {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "uniprotkb" | "uniprot" => Ok(Self::UniProtKb),
+            "uniref" => Ok(Self::UniRef),
+            "ncbi" | "refseq" | "genbank" => Ok(Self::Ncbi),
+            "ensembl" => Ok(Self::Ensembl),
+            "generic" => Ok(Self::Generic),
+            "raw" => Ok(Self::Raw),
+            "auto" => Ok(Self::Auto),
+            _ => Err(Error::malformed_header(format!("unknown FASTA header format: {}", value)))
+        }
+    }
+}
+
+impl fmt::Display for FastaHeaderFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UniProtKb => write!(f, "uniprotkb"),
+            Self::UniRef => write!(f, "uniref"),
+            Self::Ncbi => write!(f, "ncbi"),
+            Self::Ensembl => write!(f, "ensembl"),
+            Self::Generic => write!(f, "generic"),
+            Self::Raw => write!(f, "raw"),
+            Self::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+/// Splits a header into its identifier (first word) and description.
+/// Returns None for empty headers.
+///
+/// # Arguments
+///
+/// * `header` - A FASTA header, with or without the leading '>'
+///
+fn split_identifier(header: &str) -> Option<(&str, &str)> {
+    let header = header.strip_prefix('>').unwrap_or(header).trim();
+    if header.is_empty() {
+        return None;
+    }
+
+    match header.split_once(|c: char| c.is_ascii_whitespace()) {
+        Some((identifier, description)) => Some((identifier, description.trim_start())),
+        None => Some((header, ""))
+    }
+}
+
+/// Splits a description into the protein name and the trailing keyword attributes.
+/// The protein name runs until the first word containing '=' (begin of keyword attributes),
+/// every following word without '=' is appended to the value of the current attribute.
+///
+/// # Arguments
+///
+/// * `description` - Description, e.g. `Acetoin catabolism protein X OS=Cupriavidus necator OX=381666`
+///
+fn split_keyword_attributes(description: &str) -> (String, HashMap<String, String>) {
+    let mut protein_name_chunks: Vec<&str> = Vec::new();
+    let mut keyword_attributes: HashMap<String, String> = HashMap::new();
+    let mut current_attr: Option<(&str, String)> = None;
+
+    for chunk in description.split(' ') {
+        if let Some((key, value)) = chunk.split_once('=') {
+            if let Some((key, value)) = current_attr.take() {
+                keyword_attributes.insert(key.to_string(), value);
+            }
+            current_attr = Some((key, value.to_string()));
+        } else if let Some((_, value)) = current_attr.as_mut() {
+            value.push(' ');
+            value.push_str(chunk);
+        } else {
+            protein_name_chunks.push(chunk);
+        }
+    }
+    // Process the remaining attribute
+    if let Some((key, value)) = current_attr {
+        keyword_attributes.insert(key.to_string(), value);
+    }
+
+    (protein_name_chunks.join(" "), keyword_attributes)
+}
+
+/// Returns true if the given prefix is a UniRef database, i.e. UniRef100, UniRef90 or UniRef50
+///
+/// # Arguments
+///
+/// * `prefix` - Identifier prefix
+///
+pub(crate) fn is_uniref_database(prefix: &str) -> bool {
+    prefix.strip_prefix("UniRef").is_some_and(|identity| !identity.is_empty() && identity.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Returns true if the identifier looks like an Ensembl stable ID, e.g. ENSP00000354587.3 or ENSMUSP00000000001
+///
+/// # Arguments
+///
+/// * `identifier` - First word of the header
+///
+fn is_ensembl_accession(identifier: &str) -> bool {
+    let stable_id = identifier.split('.').next().unwrap_or_default();
+    match stable_id.strip_prefix("ENS") {
+        Some(tail) => {
+            let num_letters = tail.bytes().take_while(|b| b.is_ascii_uppercase()).count();
+            num_letters > 0 && tail.len() - num_letters >= 11 && tail.bytes().skip(num_letters).all(|b| b.is_ascii_digit())
+        },
+        None => false
+    }
+}
+
+/// Returns true if the identifier looks like a versioned NCBI accession, e.g. XP_012345.1 or AAB12345.1
+///
+/// # Arguments
+///
+/// * `identifier` - First word of the header
+///
+fn is_ncbi_accession(identifier: &str) -> bool {
+    let (accession, version) = match identifier.rsplit_once('.') {
+        Some(split) => split,
+        None => return false
+    };
+    if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+
+    let accession = accession.replacen('_', "", 1);
+    let num_letters = accession.bytes().take_while(|b| b.is_ascii_uppercase()).count();
+    (1..=4).contains(&num_letters) && accession.len() > num_letters && accession.bytes().skip(num_letters).all(|b| b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    /// Tests the layout detection of the supported databases.
+    fn test_format_detection() {
+        assert_eq!(FastaHeaderFormat::detect(">sp|P27748|ACOX_CUPNH Acetoin catabolism protein X OX=381666"), FastaHeaderFormat::UniProtKb);
+        assert_eq!(FastaHeaderFormat::detect(">UniRef100_Q6GZX4 Putative transcription factor 001R n=1"), FastaHeaderFormat::UniRef);
+        assert_eq!(FastaHeaderFormat::detect(">XP_012345.1 description [Homo sapiens]"), FastaHeaderFormat::Ncbi);
+        assert_eq!(FastaHeaderFormat::detect(">AAB12345.1 description"), FastaHeaderFormat::Ncbi);
+        assert_eq!(FastaHeaderFormat::detect(">gi|4504347|ref|NP_000549.1| hemoglobin [Homo sapiens]"), FastaHeaderFormat::Ncbi);
+        assert_eq!(FastaHeaderFormat::detect(">ENSP00000354587.3"), FastaHeaderFormat::Ensembl);
+        assert_eq!(FastaHeaderFormat::detect(">contaminant_KERATIN01"), FastaHeaderFormat::Generic);
+        assert_eq!(FastaHeaderFormat::detect(">DECOY_P27748 reversed"), FastaHeaderFormat::Generic);
+        assert_eq!(FastaHeaderFormat::detect(">"), FastaHeaderFormat::Generic);
+    }
+
+    #[test]
+    /// Tests the parsing of UniRef headers.
+    fn test_uniref_parsing() {
+        let header = UniRefHeaderParser.parse(">UniRef100_Q6GZX4 Putative transcription factor 001R n=1 Tax=Frog virus 3 TaxID=654924 RepID=001R_FRG3G").unwrap();
+        assert_eq!(header.database, "UniRef100");
+        assert_eq!(header.accession, "UniRef100_Q6GZX4");
+        assert_eq!(header.entry_name, "001R_FRG3G");
+        assert_eq!(header.protein_name, "Putative transcription factor 001R");
+        assert_eq!(header.keyword_attributes.get("Tax").unwrap(), "Frog virus 3");
+    }
+
+    #[test]
+    /// Tests the parsing of NCBI headers, modern and legacy.
+    fn test_ncbi_parsing() {
+        let header = NcbiHeaderParser.parse(">XP_012345.1 uncharacterized protein LOC101 [Homo sapiens]").unwrap();
+        assert_eq!(header.database, "ref");
+        assert_eq!(header.accession, "XP_012345.1");
+        assert_eq!(header.protein_name, "uncharacterized protein LOC101");
+        assert_eq!(header.keyword_attributes.get("OS").unwrap(), "Homo sapiens");
+
+        let header = NcbiHeaderParser.parse(">gi|4504347|ref|NP_000549.1| hemoglobin subunit alpha [Homo sapiens]").unwrap();
+        assert_eq!(header.database, "ref");
+        assert_eq!(header.accession, "NP_000549.1");
+        assert_eq!(header.keyword_attributes.get("gi").unwrap(), "4504347");
+    }
+
+    #[test]
+    /// Tests the parsing of Ensembl headers, with and without description.
+    fn test_ensembl_parsing() {
+        let header = EnsemblHeaderParser.parse(">ENSP00000354587.3 pep chromosome:GRCh38:MT:5904:7445:1 gene:ENSG00000198804.2 gene_symbol:MT-CO1 description:mitochondrially encoded cytochrome c oxidase I").unwrap();
+        assert_eq!(header.accession, "ENSP00000354587.3");
+        assert_eq!(header.entry_name, "MT-CO1");
+        assert_eq!(header.protein_name, "mitochondrially encoded cytochrome c oxidase I");
+        assert_eq!(header.keyword_attributes.get("type").unwrap(), "pep");
+        assert_eq!(header.keyword_attributes.get("chromosome").unwrap(), "GRCh38:MT:5904:7445:1");
+
+        let header = EnsemblHeaderParser.parse(">ENSP00000354587.3").unwrap();
+        assert_eq!(header.accession, "ENSP00000354587.3");
+        assert!(header.keyword_attributes.is_empty());
+    }
+
+    #[test]
+    /// Tests that headers not matching a layout are rejected, or handled by the generic parsers.
+    fn test_fallbacks() {
+        assert!(UniProtKbHeaderParser.parse(">contaminant_KERATIN01").is_none());
+        assert!(GenericHeaderParser.parse(">").is_none());
+
+        let header = AutoHeaderParser.parse(">contaminant_KERATIN01 Keratin, type II").unwrap();
+        assert_eq!(header.accession, "contaminant_KERATIN01");
+        assert_eq!(header.protein_name, "Keratin, type II");
+
+        let header = RawHeaderParser.parse(">sp|P27748|ACOX_CUPNH Acetoin").unwrap();
+        assert_eq!(header.accession, "sp|P27748|ACOX_CUPNH Acetoin");
+    }
+}
@@ -0,0 +1,473 @@
+// std imports
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, SeekFrom};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+// internal imports
+use crate::error::{Error, ErrorContext, Result};
+use crate::fasta::entry::FastaEntry;
+use crate::fasta::header::{AutoHeaderParser, FastaHeaderParser};
+use crate::fasta::reader::create_entry_or_generic;
+
+/// Location of an entry within a FASTA file, as stored in a samtools `.fai` index
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FastaIndexRecord {
+    /// Name of the entry, i.e. the first word of its header without '>'
+    pub name: String,
+    /// Number of residues of the sequence
+    pub length: u64,
+    /// Byte offset of the first residue
+    pub offset: u64,
+    /// Number of residues per sequence line
+    pub line_bases: u64,
+    /// Number of bytes per sequence line, including the line ending
+    pub line_width: u64,
+}
+
+impl FastaIndexRecord {
+    /// Returns the byte offset of the given residue position (0-based)
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Position of the residue within the sequence
+    ///
+    fn residue_offset(&self, position: u64) -> u64 {
+        if self.line_bases == 0 {
+            return self.offset;
+        }
+        self.offset + (position / self.line_bases) * self.line_width + position % self.line_bases
+    }
+}
+
+/// Index of the entries of a FASTA file, compatible with samtools faidx (`.fai`)
+#[derive(Clone, Debug, Default)]
+pub struct FastaIndex {
+    records: Vec<FastaIndexRecord>,
+    /// Record indices by name
+    name_to_record: HashMap<String, usize>,
+    /// Record indices by accession, for names which differ from the accession (e.g. `sp|P27748|ACOX_CUPNH`)
+    accession_to_record: HashMap<String, usize>,
+}
+
+impl FastaIndex {
+    /// Creates an index from the given records
+    ///
+    /// # Arguments
+    ///
+    /// * `records` - Index records, in file order
+    ///
+    pub fn new(records: Vec<FastaIndexRecord>) -> Self {
+        let mut name_to_record = HashMap::with_capacity(records.len());
+        let mut accession_to_record = HashMap::new();
+        for (record_idx, record) in records.iter().enumerate() {
+            name_to_record.entry(record.name.clone()).or_insert(record_idx);
+            if let Some(header) = AutoHeaderParser.parse(&record.name) {
+                if header.accession != record.name {
+                    accession_to_record.entry(header.accession).or_insert(record_idx);
+                }
+            }
+        }
+
+        Self {
+            records,
+            name_to_record,
+            accession_to_record,
+        }
+    }
+
+    /// Builds the index by scanning the given FASTA content.
+    /// Sequence lines of an entry must all have the same length, except the last one.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Buffered source of uncompressed FASTA content
+    ///
+    pub fn build<R: BufRead>(mut reader: R) -> Result<Self> {
+        let mut records: Vec<FastaIndexRecord> = Vec::new();
+        let mut byte_offset: u64 = 0;
+        let mut line_number: u64 = 0;
+        // True once a line shorter than the others was read, which must be the last one of the entry
+        let mut is_last_line_read = false;
+
+        let mut line: Vec<u8> = Vec::new();
+        loop {
+            line.clear();
+            let num_bytes = reader.read_until(b'\n', &mut line).map_err(Error::io)? as u64;
+            if num_bytes == 0 {
+                break;
+            }
+            line_number += 1;
+
+            let context = ErrorContext {
+                record_index: records.len().checked_sub(1),
+                record_id: records.last().map(|record| record.name.clone()),
+                ..ErrorContext::new(byte_offset, line_number)
+            };
+            byte_offset += num_bytes;
+
+            let line_content = line.strip_suffix(b"\n").unwrap_or(&line);
+            let line_content = line_content.strip_suffix(b"\r").unwrap_or(line_content);
+            let num_residues = line_content.len() as u64;
+
+            if line_content.starts_with(b">") {
+                let name = String::from_utf8_lossy(&line_content[1..]).split_ascii_whitespace().next()
+                    .unwrap_or_default().to_string();
+                records.push(FastaIndexRecord { name, length: 0, offset: byte_offset, line_bases: 0, line_width: 0 });
+                is_last_line_read = false;
+                continue;
+            }
+
+            let record = match records.last_mut() {
+                Some(record) => record,
+                None if num_residues == 0 => continue,
+                None => return Err(Error::missing_value("FASTA header (sequence data found before any header)").at(context))
+            };
+
+            if num_residues == 0 {
+                is_last_line_read = true;
+            } else if is_last_line_read || (record.line_bases > 0 && num_residues > record.line_bases) {
+                return Err(Error::invalid_index("sequence lines of different length").at(context));
+            } else {
+                if record.line_bases == 0 {
+                    record.line_bases = num_residues;
+                    record.line_width = num_bytes;
+                } else if num_residues < record.line_bases {
+                    is_last_line_read = true;
+                }
+                record.length += num_residues;
+            }
+        }
+
+        Ok(Self::new(records))
+    }
+
+    /// Builds the index of the given FASTA file
+    ///
+    /// # Arguments
+    ///
+    /// * `fasta_file_path` - Path to the uncompressed FASTA file
+    ///
+    pub fn build_from_file(fasta_file_path: &Path) -> Result<Self> {
+        let fasta_file = File::open(fasta_file_path).map_err(|err| Error::io(err).in_file(fasta_file_path))?;
+        Self::build(BufReader::new(fasta_file)).map_err(|err| err.in_file(fasta_file_path))
+    }
+
+    /// Reads an index in samtools `.fai` format
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Buffered source of `.fai` content
+    ///
+    pub fn read_fai<R: BufRead>(reader: R) -> Result<Self> {
+        let mut records: Vec<FastaIndexRecord> = Vec::new();
+
+        for (line_idx, line) in reader.lines().enumerate() {
+            let context = ErrorContext::new(0, line_idx as u64 + 1);
+            let line = line.map_err(|err| Error::io(err).at(context.clone()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let columns: Vec<&str> = line.split('\t').collect();
+            if columns.len() < 5 {
+                return Err(Error::invalid_index("expected 5 tab separated columns").at(context));
+            }
+            let parse_column = |value: &str| -> Result<u64> {
+                value.trim().parse().map_err(|_| Error::invalid_number(value).at(context.clone()))
+            };
+
+            records.push(FastaIndexRecord {
+                name: columns[0].to_string(),
+                length: parse_column(columns[1])?,
+                offset: parse_column(columns[2])?,
+                line_bases: parse_column(columns[3])?,
+                line_width: parse_column(columns[4])?,
+            });
+        }
+
+        Ok(Self::new(records))
+    }
+
+    /// Reads the given `.fai` file
+    ///
+    /// # Arguments
+    ///
+    /// * `fai_file_path` - Path to the `.fai` file
+    ///
+    pub fn read_fai_file(fai_file_path: &Path) -> Result<Self> {
+        let fai_file = File::open(fai_file_path).map_err(|err| Error::io(err).in_file(fai_file_path))?;
+        Self::read_fai(BufReader::new(fai_file)).map_err(|err| err.in_file(fai_file_path))
+    }
+
+    /// Writes the index in samtools `.fai` format
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Destination of the `.fai` content
+    ///
+    pub fn write_fai<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        for record in self.records.iter() {
+            writeln!(
+                writer,
+                "{}\t{}\t{}\t{}\t{}",
+                record.name, record.length, record.offset, record.line_bases, record.line_width
+            )?;
+        }
+        writer.flush()
+    }
+
+    /// Writes the index into the given `.fai` file
+    ///
+    /// # Arguments
+    ///
+    /// * `fai_file_path` - Path to the `.fai` file
+    ///
+    pub fn write_fai_file(&self, fai_file_path: &Path) -> std::io::Result<()> {
+        self.write_fai(BufWriter::new(File::create(fai_file_path)?))
+    }
+
+    /// Returns the index records, in file order
+    ///
+    pub fn get_records(&self) -> &Vec<FastaIndexRecord> {
+        &self.records
+    }
+
+    /// Returns the number of indexed entries
+    ///
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns true if no entry is indexed
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns the record of the given entry
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Name (first word of the header) or accession of the entry
+    ///
+    pub fn get(&self, id: &str) -> Option<&FastaIndexRecord> {
+        self.name_to_record.get(id)
+            .or_else(|| self.accession_to_record.get(id))
+            .map(|record_idx| &self.records[*record_idx])
+    }
+}
+
+/// Returns the path of the `.fai` index of the given FASTA file, e.g. `uniprot.fasta.fai`
+///
+/// # Arguments
+///
+/// * `fasta_file_path` - Path to the FASTA file
+///
+pub fn fai_file_path(fasta_file_path: &Path) -> PathBuf {
+    let mut fai_file_path = fasta_file_path.as_os_str().to_owned();
+    fai_file_path.push(".fai");
+    PathBuf::from(fai_file_path)
+}
+
+/// Reader fetching single entries or subsequences of an indexed FASTA file, without scanning it
+pub struct IndexedFastaReader<R: Read + Seek> {
+    internal_reader: R,
+    index: FastaIndex,
+    keep_plain_header: bool,
+    header_parser: Box<dyn FastaHeaderParser>,
+}
+
+impl IndexedFastaReader<BufReader<File>> {
+    /// Creates a new Reader.
+    /// The index is read from the `.fai` file next to the FASTA file, or built if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `fasta_file_path` - Path to the uncompressed FASTA file
+    /// * `keep_plain_header` - Whether to keep or not the plain read header in the generated FastaEntry.
+    ///
+    pub fn new(fasta_file_path: &Path, keep_plain_header: bool) -> Result<Self> {
+        let fai_file_path = fai_file_path(fasta_file_path);
+        let index = if fai_file_path.exists() {
+            FastaIndex::read_fai_file(&fai_file_path)?
+        } else {
+            FastaIndex::build_from_file(fasta_file_path)?
+        };
+
+        let fasta_file = File::open(fasta_file_path).map_err(|err| Error::io(err).in_file(fasta_file_path))?;
+        Ok(Self::from_reader(BufReader::new(fasta_file), index, keep_plain_header))
+    }
+}
+
+impl<R: Read + Seek> IndexedFastaReader<R> {
+    /// Creates a new Reader from any seekable source
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Seekable source of uncompressed FASTA content
+    /// * `index` - Index of the content
+    /// * `keep_plain_header` - Whether to keep or not the plain read header in the generated FastaEntry.
+    ///
+    pub fn from_reader(reader: R, index: FastaIndex, keep_plain_header: bool) -> Self {
+        Self {
+            internal_reader: reader,
+            index,
+            keep_plain_header,
+            header_parser: Box::new(AutoHeaderParser),
+        }
+    }
+
+    /// Sets the parser used to extract the entry information from the headers (default: `AutoHeaderParser`)
+    ///
+    /// # Arguments
+    ///
+    /// * `header_parser` - FASTA header parser
+    ///
+    pub fn set_header_parser<P: FastaHeaderParser + 'static>(&mut self, header_parser: P) -> &mut Self {
+        self.header_parser = Box::new(header_parser);
+        self
+    }
+
+    /// Returns the index
+    ///
+    pub fn get_index(&self) -> &FastaIndex {
+        &self.index
+    }
+
+    /// Consumes the indexed reader and returns the underlying reader
+    ///
+    pub fn into_inner(self) -> R {
+        self.internal_reader
+    }
+
+    /// Fetches the given entry, parsed like a streaming read with `FastaReader`.
+    /// Returns None if the entry is not indexed.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Name (first word of the header) or accession of the entry
+    ///
+    pub fn fetch_entry(&mut self, id: &str) -> Result<Option<FastaEntry>> {
+        let record = match self.index.get(id) {
+            Some(record) => record.clone(),
+            None => return Ok(None)
+        };
+
+        let header = self.read_header(&record)?;
+        let sequence = self.read_residues(&record, 0, record.length)?;
+
+        match create_entry_or_generic(&header, &sequence, self.keep_plain_header, self.header_parser.as_ref()) {
+            Some(entry) => Ok(Some(entry)),
+            None => Err(Error::malformed_header("empty header").at(self.record_context(&record)))
+        }
+    }
+
+    /// Fetches the residues `start..end` (0-based, end excluded) of the sequence of the given entry.
+    /// Returns None if the entry is not indexed.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Name (first word of the header) or accession of the entry
+    /// * `start` - Position of the first residue
+    /// * `end` - Position after the last residue, clamped to the sequence length
+    ///
+    pub fn fetch_subsequence(&mut self, id: &str, start: u64, end: u64) -> Result<Option<String>> {
+        let record = match self.index.get(id) {
+            Some(record) => record.clone(),
+            None => return Ok(None)
+        };
+
+        let end = end.min(record.length);
+        if start > end {
+            let message = format!("invalid range {}..{} for a sequence of length {}", start, end, record.length);
+            return Err(Error::invalid_index(message).at(self.record_context(&record)));
+        }
+
+        Ok(Some(self.read_residues(&record, start, end)?))
+    }
+
+    /// Returns the location of the given entry, for error reporting
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - Index record of the entry
+    ///
+    fn record_context(&self, record: &FastaIndexRecord) -> ErrorContext {
+        ErrorContext {
+            record_index: self.index.name_to_record.get(&record.name).copied(),
+            record_id: Some(record.name.clone()),
+            ..ErrorContext::new(record.offset, 0)
+        }
+    }
+
+    /// Reads the residues `start..end` of the given entry, without line endings
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - Index record of the entry
+    /// * `start` - Position of the first residue
+    /// * `end` - Position after the last residue
+    ///
+    fn read_residues(&mut self, record: &FastaIndexRecord, start: u64, end: u64) -> Result<String> {
+        let start_offset = record.residue_offset(start);
+        // Offset after the last residue, as the offset of position `end` lies after the line ending
+        // when `end` is at the start of a line, which is beyond the end of content without trailing newline
+        let end_offset = if end > start { record.residue_offset(end - 1) + 1 } else { start_offset };
+
+        let mut bytes = vec![0; (end_offset - start_offset) as usize];
+        self.internal_reader.seek(SeekFrom::Start(start_offset))
+            .and_then(|_| self.internal_reader.read_exact(&mut bytes))
+            .map_err(|err| Error::io(err).at(self.record_context(record)))?;
+        bytes.retain(|byte| *byte != b'\n' && *byte != b'\r');
+
+        if bytes.len() as u64 != end - start {
+            return Err(Error::invalid_index("sequence lines do not match the index").at(self.record_context(record)));
+        }
+
+        String::from_utf8(bytes)
+            .map_err(|_| Error::invalid_index("sequence is not valid UTF-8").at(self.record_context(record)))
+    }
+
+    /// Reads the header line preceding the sequence of the given entry, by scanning backward from the sequence start.
+    /// Fails if the first word of the header is not the name of the entry, e.g. with a stale `.fai` file.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - Index record of the entry
+    ///
+    fn read_header(&mut self, record: &FastaIndexRecord) -> Result<String> {
+        let mut chunk_size: u64 = 1024;
+        loop {
+            let chunk_start = record.offset.saturating_sub(chunk_size);
+            let mut chunk = vec![0; (record.offset - chunk_start) as usize];
+            self.internal_reader.seek(SeekFrom::Start(chunk_start))
+                .and_then(|_| self.internal_reader.read_exact(&mut chunk))
+                .map_err(|err| Error::io(err).at(self.record_context(record)))?;
+
+            // Ignore the line ending of the header
+            let header_end = chunk.len() - chunk.iter().rev().take_while(|byte| **byte == b'\n' || **byte == b'\r').count();
+            let header_start = match chunk[..header_end].iter().rposition(|byte| *byte == b'\n') {
+                Some(idx) => Some(idx + 1),
+                None if chunk_start == 0 => Some(0),
+                None => None
+            };
+
+            if let Some(header_start) = header_start {
+                let header = String::from_utf8_lossy(&chunk[header_start..header_end]).trim().to_string();
+                let name = match header.strip_prefix('>') {
+                    Some(header_content) => header_content.split_ascii_whitespace().next().unwrap_or_default(),
+                    None => return Err(Error::invalid_index("no header found before the sequence offset").at(self.record_context(record)))
+                };
+                if name != record.name {
+                    let message = format!("header of {} found at the offset of {}, the index may be stale", name, record.name);
+                    return Err(Error::invalid_index(message).at(self.record_context(record)));
+                }
+                return Ok(header);
+            }
+
+            chunk_size *= 2;
+        }
+    }
+}
@@ -1,6 +1,12 @@
+pub mod decoy;
+#[cfg(feature = "digestion")]
+pub mod digestion;
 pub mod entry;
+pub mod header;
+pub mod index;
 pub mod reader;
 pub mod writer;
+pub mod protein_database;
 pub mod prelude;
 
 pub use prelude::*;
@@ -33,7 +39,8 @@ mod test {
 
         let mut entries = Vec::with_capacity(1000);
 
-        for entry in reader {
+        for entry in reader.iterator() {
+            let entry = entry.unwrap();
 
             let entry_as_string = FastaWriter::stringify_entry(&entry, true, None);
 
@@ -63,4 +70,396 @@ mod test {
             tmp_fasta_content.as_str()
         );
     }
+
+    #[test]
+    /// Reads a FASTA file, write the proteins back into memory and compares the result with the original file.
+    fn test_writing_to_buffer() {
+        let fasta_file_path = Path::new(FASTA_FILE_PATH_STR);
+        let reader = FastaReader::new(fasta_file_path, 1024, true).unwrap();
+        let entries: Vec<FastaEntry> = reader.collect().unwrap();
+        assert_eq!(entries.len(), EXPECTED_NUM_PROTEINS);
+
+        let mut writer = FastaWriter::from_writer_with_default_seq_formatting(
+            Vec::new(),
+            true
+        );
+
+        writer.write_all(entries.iter()).unwrap();
+        writer.flush().unwrap();
+
+        let written_fasta_content = String::from_utf8(writer.into_inner()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(fasta_file_path).unwrap().trim().replace("\r\n", "\n"),
+            written_fasta_content.trim()
+        );
+    }
+
+    #[test]
+    /// Reads FASTA entries from an in-memory buffer.
+    fn test_reading_from_buffer() {
+        let fasta_content = fs::read(FASTA_FILE_PATH_STR).unwrap();
+
+        let reader = FastaReader::from_reader(fasta_content.as_slice(), false);
+
+        let entries: Vec<FastaEntry> = reader.collect().unwrap();
+        assert_eq!(entries.len(), EXPECTED_NUM_PROTEINS);
+        assert_eq!(entries[0].get_accession(), "A0A024B7W1");
+        assert!(entries[0].get_plain_header().is_none());
+    }
+
+    #[test]
+    /// Reads a FASTA file mixing databases and writes the generic entries back.
+    fn test_reading_mixed_databases() {
+        let fasta_content = ">sp|P27748|ACOX_CUPNH Acetoin catabolism protein X OX=381666
+MGHAAGASAQ
+>ENSP00000354587.3
+MFADRWLFST
+>XP_012345.1 uncharacterized protein [Homo sapiens]
+MKLV
+>contaminant_KERATIN01 Keratin, type II
+MSCQ
+>DECOY_P27748
+QASAGAAHGM
+>gi|4504347|ref|NP_000549.1| hemoglobin subunit alpha [Homo sapiens]
+MVLSPADKTN
+>UniRef100_Q6GZX4 Putative transcription factor 001R n=1 Tax=Frog virus 3 TaxID=654924 RepID=001R_FRG3G
+MAFSAEDVLK
+>ENSP00000354687.2 pep chromosome:GRCh38:MT:3307:4262:1 gene:ENSG00000198888.2 transcript:ENST00000361390.2 gene_biotype:protein_coding transcript_biotype:protein_coding gene_symbol:MT-ND1 description:mitochondrially encoded NADH dehydrogenase 1
+MPMANLLLLI
+";
+        let entries: Vec<FastaEntry> = FastaReader::from_reader(fasta_content.as_bytes(), false).collect().unwrap();
+        assert_eq!(entries.len(), 8);
+        assert_eq!(entries[0].get_accession(), "P27748");
+        assert_eq!(entries[1].get_database(), "ensembl");
+        assert_eq!(entries[2].get_database(), "ref");
+        assert_eq!(entries[3].get_protein_name(), "Keratin, type II");
+        assert_eq!(entries[4].get_accession(), "DECOY_P27748");
+
+        assert_eq!(entries[5].get_keyword_attributes()["gi"], "4504347");
+        assert_eq!(entries[6].get_database(), "UniRef100");
+        assert_eq!(entries[7].get_entry_name(), "MT-ND1");
+
+        // every layout is written back as read
+        let mut writer = FastaWriter::from_writer(Vec::new(), true, None);
+        writer.write_all(entries.iter()).unwrap();
+        let written_fasta_content = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(written_fasta_content, fasta_content);
+
+        let mut reader = FastaReader::from_reader(fasta_content.as_bytes(), false);
+        reader.set_header_parser(RawHeaderParser);
+        let entries: Vec<FastaEntry> = reader.collect().unwrap();
+        assert_eq!(entries[0].get_accession(), "sp|P27748|ACOX_CUPNH Acetoin catabolism protein X OX=381666");
+
+        let mut writer = FastaWriter::from_writer(Vec::new(), true, None);
+        writer.write_all(entries.iter()).unwrap();
+        let written_fasta_content = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(written_fasta_content, fasta_content);
+    }
+
+    #[test]
+    /// Checks that malformed FASTA content is reported with its location instead of panicking or hanging.
+    fn test_malformed_content() {
+        use crate::error::Error;
+
+        let mut reader = FastaReader::from_reader("MKLV\n>P1\nMKLV\n".as_bytes(), false);
+        let error = reader.next().err().unwrap();
+        assert!(matches!(error, Error::MissingValue { .. }));
+        assert_eq!(error.context().line_number, 1);
+
+        let mut reader = FastaReader::from_reader(">P1 first\nMKLV\n>\nMKLV\n>P3\nMK1V\n>P4\nMKLV".as_bytes(), false);
+        assert_eq!(reader.next().unwrap().unwrap().get_accession(), "P1");
+
+        let error = reader.next().err().unwrap();
+        assert!(matches!(error, Error::MalformedHeader { .. }));
+        assert_eq!(error.context().line_number, 3);
+        assert_eq!(error.context().byte_offset, 15);
+        assert_eq!(error.context().record_index, Some(1));
+
+        let error = reader.next().err().unwrap();
+        assert!(matches!(error, Error::InvalidResidue { residue: '1', .. }));
+        assert_eq!(error.context().line_number, 6);
+        assert_eq!(error.context().record_index, Some(2));
+        assert_eq!(error.context().record_id.as_deref(), Some("P3"));
+
+        // a header without keyword attributes used to hang the reader
+        let entries: Vec<FastaEntry> = FastaReader::from_reader(">sp|P27748|ACOX_CUPNH Acetoin\nMKLV\n".as_bytes(), false)
+            .collect().unwrap();
+        assert_eq!(entries[0].get_protein_name(), "Acetoin");
+    }
+
+    #[test]
+    /// Creates decoy entries with each method and writes a target-decoy database.
+    fn test_decoy_generation() {
+        let mut decoy_generator = DecoyGenerator::new(DecoyMethod::Reverse);
+        assert_eq!(decoy_generator.create_decoy_sequence("MPEPTIDEKAAR*"), "RAAKEDITPEPM*");
+
+        decoy_generator.set_method(DecoyMethod::PseudoReverse);
+        assert_eq!(decoy_generator.create_decoy_sequence("MPEPTIDEKAARGGG"), "EDITPEPMKAARGGG");
+        assert_eq!(decoy_generator.create_decoy_sequence("PEAKRLLVW"), "AEPKRWVLL");
+
+        // random decoys are reproducible and keep the composition
+        for method in [DecoyMethod::Shuffle, DecoyMethod::KmerShuffle] {
+            decoy_generator.set_method(method).set_seed(42);
+            let sequence = "MGHAAGASAQIAPVVGIIANPISARDIRRVIANANSLQLADRVNIVLRLLAALASCGVER";
+            let decoy_sequence = decoy_generator.create_decoy_sequence(sequence);
+            assert_eq!(decoy_sequence, decoy_generator.create_decoy_sequence(sequence));
+            assert_ne!(decoy_sequence, sequence);
+            let mut residues: Vec<char> = sequence.chars().collect();
+            let mut decoy_residues: Vec<char> = decoy_sequence.chars().collect();
+            residues.sort_unstable();
+            decoy_residues.sort_unstable();
+            assert_eq!(residues, decoy_residues);
+        }
+
+        // k-mer shuffle decoys preserve the repeats starting at a k-mer boundary
+        decoy_generator.set_method(DecoyMethod::KmerShuffle).set_kmer_length(3);
+        let decoy_sequence = decoy_generator.create_decoy_sequence("ACDEFGACDEFG");
+        assert_eq!(decoy_sequence[..6], decoy_sequence[6..]);
+        // each k-mer is shuffled in place, the last one being shorter, whatever the repeats
+        let sequence = "MACDEFACDEFVW";
+        let decoy_sequence = decoy_generator.create_decoy_sequence(sequence);
+        for (kmer, decoy_kmer) in sequence.as_bytes().chunks(3).zip(decoy_sequence.as_bytes().chunks(3)) {
+            let mut residues = kmer.to_vec();
+            let mut decoy_residues = decoy_kmer.to_vec();
+            residues.sort_unstable();
+            decoy_residues.sort_unstable();
+            assert_eq!(residues, decoy_residues);
+        }
+        assert_eq!(&decoy_sequence[3..6], &decoy_generator.create_decoy_sequence("DEF"));
+        assert_eq!("kmer-shuffle".parse::<DecoyMethod>().unwrap(), DecoyMethod::KmerShuffle);
+
+        // de Bruijn decoys preserve the repeats whatever their position, picking residues from the target k-mers
+        decoy_generator.set_method(DecoyMethod::DeBruijn).set_kmer_length(4);
+        let sequence = "MWACDEFGHIKLNPQYWVACDEFGHIKLNPQST";
+        let decoy_sequence = decoy_generator.create_decoy_sequence(sequence);
+        assert_eq!(decoy_sequence, decoy_generator.create_decoy_sequence(sequence));
+        assert_ne!(decoy_sequence, sequence);
+        assert_eq!(decoy_sequence.len(), sequence.len());
+        // the repeat starts at 2 and 18, the decoy repeats lacking their first k-1 residues
+        assert_eq!(&sequence[2..15], &sequence[18..31]);
+        assert_eq!(&decoy_sequence[5..15], &decoy_sequence[21..31]);
+        for (position, decoy_residue) in decoy_sequence.chars().enumerate().skip(1) {
+            assert!(sequence[position.saturating_sub(3)..position].contains(decoy_residue));
+        }
+        assert_eq!("de_bruijn".parse::<DecoyMethod>().unwrap(), DecoyMethod::DeBruijn);
+        assert_eq!(DecoyMethod::DeBruijn.to_string().parse::<DecoyMethod>().unwrap(), DecoyMethod::DeBruijn);
+        assert!(matches!("inverse".parse::<DecoyMethod>(), Err(crate::error::Error::InvalidValue { .. })));
+
+        let fasta_file_path = Path::new(FASTA_FILE_PATH_STR);
+        let mut decoy_generator = DecoyGenerator::new(DecoyMethod::Reverse);
+        decoy_generator.set_accession_prefix("rev_");
+        let decoys: Vec<FastaEntry> = decoy_generator.decoys(FastaReader::new(fasta_file_path, 1024, false).unwrap())
+            .collect().unwrap();
+        assert_eq!(decoys.len(), EXPECTED_NUM_PROTEINS);
+        assert!(decoys.iter().all(|decoy| decoy_generator.is_decoy_accession(decoy.get_accession())));
+
+        let mut fasta_writer = FastaWriter::from_writer(Vec::new(), true, None);
+        decoy_generator.write_target_decoy(|| FastaReader::new(fasta_file_path, 1024, false), &mut fasta_writer).unwrap();
+        let target_decoy_content = fasta_writer.into_inner();
+        let entries: Vec<FastaEntry> = FastaReader::from_reader(target_decoy_content.as_slice(), false).collect().unwrap();
+        assert_eq!(entries.len(), 2 * EXPECTED_NUM_PROTEINS);
+        assert_eq!(entries[EXPECTED_NUM_PROTEINS].get_accession(), &format!("rev_{}", entries[0].get_accession()));
+        assert_eq!(entries[EXPECTED_NUM_PROTEINS].get_entry_name(), &format!("rev_{}", entries[0].get_entry_name()));
+        assert_eq!(entries[EXPECTED_NUM_PROTEINS].get_sequence(), &entries[0].get_sequence().chars().rev().collect::<String>());
+    }
+
+    #[cfg(feature = "digestion")]
+    #[test]
+    /// Digests protein sequences with several enzymes and settings.
+    fn test_digestion() {
+        let protein = FastaEntry::new(String::new(), "P1".to_owned(), String::new(), String::new(),
+            Default::default(), "MAGICKPEPTIDERSEQKENCER".to_owned(), None);
+
+        let mut digester = Digester::new(Enzyme::Trypsin);
+        digester.set_length_range(1, 50).set_max_missed_cleavages(0).set_n_term_met_excision(false);
+        assert_eq!(digester.find_cleavage_sites(protein.get_sequence()), vec![0, 14, 18, 23]);
+        let peptides = digester.digest(&protein);
+        let sequences: Vec<&str> = peptides.iter().map(|peptide| peptide.get_sequence()).collect();
+        assert_eq!(sequences, vec!["MAGICKPEPTIDER", "SEQK", "ENCER"]);
+        assert_eq!(peptides[1].get_start(), 14);
+        assert_eq!(peptides[1].get_end(), 18);
+        assert_eq!(peptides[1].get_protein().get_accession(), "P1");
+        assert!((peptides[1].get_mass().unwrap() - 490.2388).abs() < 1e-3);
+
+        digester.set_max_missed_cleavages(1).set_n_term_met_excision(true);
+        let sequences: Vec<&str> = digester.digest(&protein).iter().map(|peptide| peptide.get_sequence()).collect();
+        assert_eq!(sequences, vec!["MAGICKPEPTIDER", "MAGICKPEPTIDERSEQK", "AGICKPEPTIDER", "AGICKPEPTIDERSEQK", "SEQK", "SEQKENCER", "ENCER"]);
+
+        digester.set_enzyme(Enzyme::TrypsinP).set_max_missed_cleavages(0).set_n_term_met_excision(false);
+        assert_eq!(digester.find_cleavage_sites(protein.get_sequence()), vec![0, 6, 14, 18, 23]);
+        digester.set_enzyme(Enzyme::AspN);
+        assert_eq!(digester.find_cleavage_sites(protein.get_sequence()), vec![0, 11, 23]);
+        digester.set_enzyme(Enzyme::custom("[KR][^P]").unwrap());
+        assert_eq!(digester.find_cleavage_sites(protein.get_sequence()), vec![0, 14, 18, 23]);
+        assert_eq!(digester.get_enzyme().to_string(), "[KR][^P]");
+        // the custom rules are anchored: a single residue never matches a pair
+        digester.set_enzyme(Enzyme::custom("K").unwrap());
+        assert_eq!(digester.get_enzyme().to_string(), "K");
+        assert_eq!(digester.find_cleavage_sites(protein.get_sequence()), vec![0, 23]);
+        assert!(matches!("Lys-C".parse::<Enzyme>().unwrap(), Enzyme::LysC));
+        assert!(matches!("Lys-X".parse::<Enzyme>(), Err(crate::error::Error::InvalidValue { .. })));
+        assert!(matches!(Enzyme::custom("[KR"), Err(crate::error::Error::InvalidValue { .. })));
+
+        // lowercase residues are cleaved like uppercase ones
+        digester.set_enzyme(Enzyme::Trypsin);
+        assert_eq!(digester.find_cleavage_sites(&protein.get_sequence().to_ascii_lowercase()), vec![0, 14, 18, 23]);
+
+        // length and mass bounds
+        digester.set_enzyme(Enzyme::Trypsin).set_length_range(5, 50).set_mass_range(Some(600.0), Some(1000.0));
+        let sequences: Vec<&str> = digester.digest(&protein).iter().map(|peptide| peptide.get_sequence()).collect();
+        assert_eq!(sequences, vec!["ENCER"]);
+
+        // semi-specific and non-specific peptides
+        let protein = FastaEntry::new(String::new(), "P2".to_owned(), String::new(), String::new(),
+            Default::default(), "AAKCCRDD".to_owned(), None);
+        let mut digester = Digester::new(Enzyme::Trypsin);
+        digester.set_length_range(2, 3).set_max_missed_cleavages(0).set_n_term_met_excision(false)
+            .set_specificity(CleavageSpecificity::SemiSpecific);
+        let sequences: Vec<&str> = digester.digest(&protein).iter().map(|peptide| peptide.get_sequence()).collect();
+        assert_eq!(sequences, vec!["AA", "AAK", "AK", "CC", "CCR", "CR", "DD"]);
+        digester.set_enzyme(Enzyme::NonSpecific).set_specificity(CleavageSpecificity::Specific).set_length_range(7, 8);
+        let sequences: Vec<&str> = digester.digest_all([&protein]).map(|peptide| peptide.get_sequence()).collect();
+        assert_eq!(sequences, vec!["AAKCCRD", "AAKCCRDD", "AKCCRDD"]);
+    }
+
+    #[test]
+    /// Maps peptides to the proteins containing them.
+    fn test_protein_database() {
+        let fasta_content = ">P1 first
+MPEPTIDEKLLSEQ
+>P2 second
+AAPEPTIDEKVV
+>P3 third
+GGPEPTLDEK
+";
+        let protein_database = ProteinDatabase::from_fasta(FastaReader::from_reader(fasta_content.as_bytes(), false), false).unwrap();
+        assert_eq!(protein_database.len(), 3);
+
+        let matches = protein_database.find_matches("PEPTIDEK");
+        assert_eq!(matches, vec![
+            ProteinMatch { protein_index: 0, start: 1, end: 9, residue_before: Some('M'), residue_after: Some('L') },
+            ProteinMatch { protein_index: 1, start: 2, end: 10, residue_before: Some('A'), residue_after: Some('V') },
+        ]);
+        assert!(protein_database.is_shared_peptide("PEPTIDEK"));
+        let proteins = protein_database.find_proteins("LLSEQ");
+        assert_eq!(proteins.len(), 1);
+        assert_eq!(proteins[0].get_accession(), "P1");
+        assert_eq!(protein_database.find_matches("MPEP")[0].residue_before, None);
+        assert_eq!(protein_database.find_matches("KVV")[0].residue_after, None);
+        assert!(protein_database.find_matches("EQAA").is_empty());
+        assert!(protein_database.find_matches("").is_empty());
+
+        // isoleucine and leucine are equated
+        let protein_database = ProteinDatabase::from_fasta(FastaReader::from_reader(fasta_content.as_bytes(), false), true).unwrap();
+        let accessions: Vec<&String> = protein_database.find_proteins("peptide").iter().map(|protein| protein.get_accession()).collect();
+        assert_eq!(accessions, vec!["P1", "P2", "P3"]);
+        assert_eq!(protein_database.find_matches("PEPTIDE")[2].residue_after, Some('K'));
+
+        // all the peptides of a digested database are found back
+        let fasta_file_path = Path::new(FASTA_FILE_PATH_STR);
+        let protein_database = ProteinDatabase::from_fasta(FastaReader::new(fasta_file_path, 1024, false).unwrap(), true).unwrap();
+        for (protein_index, protein) in protein_database.get_proteins().iter().enumerate() {
+            let sequence = protein.get_sequence();
+            for start in (0..sequence.len().saturating_sub(10)).step_by(37) {
+                let matches = protein_database.find_matches(&sequence[start..start + 10]);
+                assert!(matches.iter().any(|protein_match| protein_match.protein_index == protein_index && protein_match.start == start));
+            }
+        }
+
+        // redundant databases (identical isoforms, repeats) are indexed quickly
+        let repeat = "MAGICKPEPTIDERSEQKENCERWVLHY".repeat(40);
+        let proteins: Vec<FastaEntry> = (0..200)
+            .map(|protein_index| FastaEntry::new(String::new(), format!("P{}", protein_index), String::new(), String::new(),
+                Default::default(), format!("{}{}", repeat, protein_index % 2), None))
+            .collect();
+        let protein_database = ProteinDatabase::new(proteins, false).unwrap();
+        assert_eq!(protein_database.find_matches("PEPTIDERSEQ").len(), 200 * 40);
+        assert_eq!(protein_database.find_proteins("HY1").len(), 100);
+        assert_eq!(protein_database.find_matches("HYMAG")[0], ProteinMatch {
+            protein_index: 0, start: 26, end: 31, residue_before: Some('L'), residue_after: Some('I')
+        });
+    }
+
+    #[test]
+    /// Indexes a FASTA file and fetches its entries and subsequences by accession.
+    fn test_indexed_reading() {
+        let fasta_file_path = Path::new(FASTA_FILE_PATH_STR);
+        let fasta_content = fs::read_to_string(fasta_file_path).unwrap();
+
+        let index = FastaIndex::build_from_file(fasta_file_path).unwrap();
+        assert_eq!(index.len(), EXPECTED_NUM_PROTEINS);
+        let first_record = &index.get_records()[0];
+        assert_eq!(first_record.name, "sp|A0A024B7W1|POLG_ZIKVF");
+        assert_eq!(first_record.offset, fasta_content.find("\nMKNPKKK").unwrap() as u64 + 1);
+        assert_eq!(first_record.line_bases, 60);
+
+        // .fai round trip
+        let mut fai_content = Vec::new();
+        index.write_fai(&mut fai_content).unwrap();
+        assert!(String::from_utf8(fai_content.clone()).unwrap().starts_with("sp|A0A024B7W1|POLG_ZIKVF\t"));
+        let read_index = FastaIndex::read_fai(fai_content.as_slice()).unwrap();
+        assert_eq!(read_index.get_records(), index.get_records());
+
+        let entries: Vec<FastaEntry> = FastaReader::new(fasta_file_path, 1024, true).unwrap().collect().unwrap();
+        let mut indexed_reader = IndexedFastaReader::from_reader(
+            std::io::Cursor::new(fasta_content.as_bytes()),
+            read_index,
+            true
+        );
+        for entry in entries.iter() {
+            let indexed_entry = indexed_reader.fetch_entry(entry.get_accession()).unwrap().unwrap();
+            assert_eq!(
+                FastaWriter::stringify_entry(&indexed_entry, true, None),
+                FastaWriter::stringify_entry(entry, true, None)
+            );
+            assert_eq!(indexed_entry.get_plain_header(), entry.get_plain_header());
+        }
+
+        let sequence = entries[1].get_sequence();
+        let subsequence = indexed_reader.fetch_subsequence(entries[1].get_accession(), 55, 130).unwrap().unwrap();
+        assert_eq!(subsequence, sequence[55..130]);
+        assert!(indexed_reader.fetch_entry("UNKNOWN").unwrap().is_none());
+
+        // the last sequence ends exactly at a line end, without trailing newline
+        let fasta_content = ">P1\nMKLV\n>P2\nMKLV\nMKLV";
+        let index = FastaIndex::build(fasta_content.as_bytes()).unwrap();
+        let mut indexed_reader = IndexedFastaReader::from_reader(std::io::Cursor::new(fasta_content.as_bytes()), index, false);
+        assert_eq!(indexed_reader.fetch_entry("P2").unwrap().unwrap().get_sequence(), "MKLVMKLV");
+        assert_eq!(indexed_reader.fetch_subsequence("P2", 0, 4).unwrap().unwrap(), "MKLV");
+        assert_eq!(indexed_reader.fetch_subsequence("P2", 4, 4).unwrap().unwrap(), "");
+
+        // a stale index pointing at another entry is reported instead of returning the wrong protein
+        let index = FastaIndex::build(fasta_content.as_bytes()).unwrap();
+        let fasta_content = ">P0\nMKLV\n>P1\nMKLV\nMKLV";
+        let mut indexed_reader = IndexedFastaReader::from_reader(std::io::Cursor::new(fasta_content.as_bytes()), index, false);
+        let error = indexed_reader.fetch_entry("P2").unwrap_err();
+        assert!(matches!(error, crate::error::Error::InvalidIndex { .. }));
+        assert!(error.to_string().contains("header of P1"));
+        assert_eq!(error.context().record_id.as_deref(), Some("P2"));
+    }
+
+    #[test]
+    /// Edits entries and converts them from and to plain headers
+    fn test_entry_editing() {
+        let header = ">sp|P27748|ACOX_CUPNH Acetoin catabolism protein X OS=Cupriavidus necator OX=381666 GN=acoX PE=4 SV=2";
+        let mut entry = FastaEntry::from_header(header, "MGHAAG\nASAQIA").unwrap();
+        assert_eq!(entry.get_accession(), "P27748");
+        assert_eq!(entry.get_sequence(), "MGHAAGASAQIA");
+        assert_eq!(entry.get_plain_header().as_deref(), Some(header));
+        assert_eq!(entry.to_header(true), ">sp|P27748|ACOX_CUPNH Acetoin catabolism protein X GN=acoX OS=Cupriavidus necator OX=381666 PE=4 SV=2");
+
+        let target_entry = entry.clone();
+        assert_eq!(entry, target_entry);
+        entry.set_accession("DECOY_P27748".to_owned())
+            .set_keyword_attribute("GN".to_owned(), "acoY".to_owned())
+            .set_plain_header(None);
+        assert_eq!(entry.remove_keyword_attribute("OS").as_deref(), Some("Cupriavidus necator"));
+        assert_ne!(entry, target_entry);
+        assert_eq!(entry.to_header(true), ">sp|DECOY_P27748|ACOX_CUPNH Acetoin catabolism protein X GN=acoY OX=381666 PE=4 SV=2");
+
+        let generic_entry = FastaEntry::from_header("contig_1 assembled protein", "MKV").unwrap();
+        assert_eq!(generic_entry.to_header(true), ">contig_1 assembled protein");
+        assert!(FastaEntry::from_header(">", "MKV").is_err());
+    }
 }
@@ -1,3 +1,14 @@
+pub use fallible_iterator::IntoFallibleIterator;
+pub use fallible_iterator::FallibleIterator;
+pub use crate::fasta::decoy::{DecoyGenerator, DecoyMethod, DEFAULT_DECOY_PREFIX};
+#[cfg(feature = "digestion")]
+pub use crate::fasta::digestion::{CleavageRule, CleavageSpecificity, Digester, Enzyme, Peptide};
 pub use crate::fasta::entry::FastaEntry;
-pub use crate::fasta::reader::FastaReader;
+pub use crate::fasta::header::{
+    AutoHeaderParser, EnsemblHeaderParser, FastaHeader, FastaHeaderFormat, FastaHeaderParser,
+    GenericHeaderParser, NcbiHeaderParser, RawHeaderParser, UniProtKbHeaderParser, UniRefHeaderParser
+};
+pub use crate::fasta::index::{FastaIndex, FastaIndexRecord, IndexedFastaReader};
+pub use crate::fasta::protein_database::{ProteinDatabase, ProteinMatch};
+pub use crate::fasta::reader::{create_entry, create_entry_with_parser, FastaReader};
 pub use crate::fasta::writer::FastaWriter;
\ No newline at end of file
@@ -0,0 +1,328 @@
+// 3rd party imports
+use fallible_iterator::FallibleIterator;
+
+// internal imports
+use crate::error::{Error, Result};
+use crate::fasta::entry::FastaEntry;
+
+/// Separator of the protein sequences in the indexed text, never part of a peptide
+const SEQUENCE_SEPARATOR: u8 = b'$';
+
+/// Occurrence of a peptide in a protein
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ProteinMatch {
+    /// Index of the protein in the database
+    pub protein_index: usize,
+    /// Position of the first residue of the peptide in the protein (0-based)
+    pub start: usize,
+    /// Position following the last residue of the peptide in the protein (0-based, exclusive)
+    pub end: usize,
+    /// Residue preceding the peptide, None at the protein N-terminus
+    pub residue_before: Option<char>,
+    /// Residue following the peptide, None at the protein C-terminus
+    pub residue_after: Option<char>,
+}
+
+/// Protein sequences indexed by a suffix array, to find all the proteins containing a peptide
+/// (e.g. for protein inference or to spot the shared peptides)
+pub struct ProteinDatabase {
+    proteins: Vec<FastaEntry>,
+    /// Upper-case protein sequences, separated by `SEQUENCE_SEPARATOR`
+    text: Vec<u8>,
+    /// Position of each protein sequence in the text
+    protein_offsets: Vec<usize>,
+    /// Positions of the text suffixes, in lexicographic order
+    suffix_array: Vec<u32>,
+    /// Whether isoleucine and leucine are considered identical (same mass)
+    equate_il: bool,
+}
+
+impl ProteinDatabase {
+    /// Indexes the given proteins.
+    /// Fails if the proteins have more than `u32::MAX` residues in total.
+    ///
+    /// # Arguments
+    ///
+    /// * `proteins` - Protein entries
+    /// * `equate_il` - If true, isoleucine (I) and leucine (L) are considered identical
+    ///
+    pub fn new(proteins: Vec<FastaEntry>, equate_il: bool) -> Result<Self> {
+        let text_len = proteins.iter().map(|protein| protein.get_sequence().len() + 1).sum();
+        let mut text: Vec<u8> = Vec::with_capacity(text_len);
+        let mut protein_offsets: Vec<usize> = Vec::with_capacity(proteins.len());
+        for protein in &proteins {
+            protein_offsets.push(text.len());
+            text.extend(protein.get_sequence().bytes().map(|residue| normalize_residue(residue, equate_il)));
+            text.push(SEQUENCE_SEPARATOR);
+        }
+        if text.len() > u32::MAX as usize {
+            return Err(Error::invalid_index(format!(
+                "protein database too large to be indexed: {} residues and separators, at most {}",
+                text.len(), u32::MAX
+            )));
+        }
+
+        let mut suffix_array = build_suffix_array(&text);
+        suffix_array.retain(|position| text[*position as usize] != SEQUENCE_SEPARATOR);
+
+        Ok(Self { proteins, text, protein_offsets, suffix_array, equate_il })
+    }
+
+    /// Reads and indexes the proteins of a FASTA stream.
+    /// Fails if the proteins have more than `u32::MAX` residues in total.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - Protein entries, e.g. a `FastaReader`
+    /// * `equate_il` - If true, isoleucine (I) and leucine (L) are considered identical
+    ///
+    pub fn from_fasta<I>(entries: I, equate_il: bool) -> Result<Self>
+    where
+        I: FallibleIterator<Item = FastaEntry, Error = Error>,
+    {
+        Self::new(entries.collect()?, equate_il)
+    }
+
+    /// Returns the indexed proteins
+    ///
+    pub fn get_proteins(&self) -> &Vec<FastaEntry> {
+        &self.proteins
+    }
+
+    /// Returns the protein at the given index
+    ///
+    /// # Arguments
+    ///
+    /// * `protein_index` - Index of the protein in the database
+    ///
+    pub fn get_protein(&self, protein_index: usize) -> Option<&FastaEntry> {
+        self.proteins.get(protein_index)
+    }
+
+    /// Returns true if isoleucine and leucine are considered identical
+    ///
+    pub fn is_equating_il(&self) -> bool {
+        self.equate_il
+    }
+
+    /// Returns the number of proteins
+    ///
+    pub fn len(&self) -> usize {
+        self.proteins.len()
+    }
+
+    /// Returns true if there is no protein
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.proteins.is_empty()
+    }
+
+    /// Returns all the occurrences of the given peptide, sorted by protein then position
+    ///
+    /// # Arguments
+    ///
+    /// * `peptide` - Peptide sequence
+    ///
+    pub fn find_matches(&self, peptide: &str) -> Vec<ProteinMatch> {
+        let query: Vec<u8> = peptide.bytes().map(|residue| normalize_residue(residue, self.equate_il)).collect();
+        if query.is_empty() || query.contains(&SEQUENCE_SEPARATOR) {
+            return Vec::new();
+        }
+
+        // Suffixes starting with the peptide are contiguous in the suffix array
+        let suffix = |position: &u32| &self.text[*position as usize..];
+        let first_idx = self.suffix_array.partition_point(|position| suffix(position) < query.as_slice());
+        let num_matches = self.suffix_array[first_idx..].partition_point(|position| suffix(position).starts_with(&query));
+
+        let mut matches: Vec<ProteinMatch> = self.suffix_array[first_idx..first_idx + num_matches].iter()
+            .map(|position| {
+                let position = *position as usize;
+                let protein_index = self.protein_offsets.partition_point(|offset| *offset <= position) - 1;
+                let sequence = self.proteins[protein_index].get_sequence().as_bytes();
+                let start = position - self.protein_offsets[protein_index];
+                let end = start + query.len();
+                ProteinMatch {
+                    protein_index,
+                    start,
+                    end,
+                    residue_before: start.checked_sub(1).map(|idx| sequence[idx] as char),
+                    residue_after: sequence.get(end).map(|residue| *residue as char),
+                }
+            })
+            .collect();
+        matches.sort_unstable_by_key(|protein_match| (protein_match.protein_index, protein_match.start));
+
+        matches
+    }
+
+    /// Returns the proteins containing the given peptide, in database order
+    ///
+    /// # Arguments
+    ///
+    /// * `peptide` - Peptide sequence
+    ///
+    pub fn find_proteins(&self, peptide: &str) -> Vec<&FastaEntry> {
+        let mut protein_indices: Vec<usize> = self.find_matches(peptide).into_iter()
+            .map(|protein_match| protein_match.protein_index)
+            .collect();
+        protein_indices.dedup();
+
+        protein_indices.into_iter().map(|protein_index| &self.proteins[protein_index]).collect()
+    }
+
+    /// Returns true if the given peptide belongs to several proteins
+    ///
+    /// # Arguments
+    ///
+    /// * `peptide` - Peptide sequence
+    ///
+    pub fn is_shared_peptide(&self, peptide: &str) -> bool {
+        self.find_proteins(peptide).len() > 1
+    }
+}
+
+/// Converts a residue to its indexed form: upper case, I replaced by L if they are equated
+///
+/// # Arguments
+///
+/// * `residue` - Amino acid one-letter code
+/// * `equate_il` - Whether isoleucine and leucine are considered identical
+///
+fn normalize_residue(residue: u8, equate_il: bool) -> u8 {
+    match residue.to_ascii_uppercase() {
+        b'I' if equate_il => b'L',
+        residue => residue
+    }
+}
+
+/// Builds the suffix array of the given text with the SA-IS algorithm (Nong, Zhang & Chan, 2009),
+/// in linear time whatever the redundancy of the text (isoforms, repeats, target-decoy duplicates)
+///
+/// # Arguments
+///
+/// * `text` - Indexed text, at most `u32::MAX` bytes long
+///
+fn build_suffix_array(text: &[u8]) -> Vec<u32> {
+    let symbols: Vec<u32> = text.iter().map(|byte| *byte as u32).collect();
+    sa_is(&symbols, u8::MAX as usize)
+}
+
+/// Recursive step of SA-IS: sorts the LMS substrings by induced sorting, names them,
+/// sorts the reduced string recursively and induces the order of all the suffixes from it
+///
+/// # Arguments
+///
+/// * `text` - Symbols of the text
+/// * `max_symbol` - Greatest symbol of the text
+///
+fn sa_is(text: &[u32], max_symbol: usize) -> Vec<u32> {
+    const EMPTY: u32 = u32::MAX;
+    let text_len = text.len();
+    match text_len {
+        0 => return Vec::new(),
+        1 => return vec![0],
+        2 => return if text[0] < text[1] { vec![0, 1] } else { vec![1, 0] },
+        _ => {}
+    }
+
+    // S-type suffixes are smaller than the following one, L-type ones greater
+    let mut is_s_type = vec![false; text_len];
+    for position in (0..text_len - 1).rev() {
+        is_s_type[position] = if text[position] == text[position + 1] {
+            is_s_type[position + 1]
+        } else {
+            text[position] < text[position + 1]
+        };
+    }
+    let is_lms = |position: usize| position > 0 && is_s_type[position] && !is_s_type[position - 1];
+
+    // Start of the L-type and S-type parts of each symbol bucket
+    let mut l_bucket_starts = vec![0usize; max_symbol + 2];
+    let mut s_bucket_starts = vec![0usize; max_symbol + 2];
+    for position in 0..text_len {
+        let symbol = text[position] as usize;
+        if is_s_type[position] {
+            l_bucket_starts[symbol + 1] += 1;
+        } else {
+            s_bucket_starts[symbol] += 1;
+        }
+    }
+    for symbol in 0..=max_symbol {
+        s_bucket_starts[symbol] += l_bucket_starts[symbol];
+        l_bucket_starts[symbol + 1] += s_bucket_starts[symbol];
+    }
+
+    let induce = |suffix_array: &mut Vec<u32>, lms_positions: &[usize]| {
+        suffix_array.clear();
+        suffix_array.resize(text_len, EMPTY);
+        let mut bucket_ends = s_bucket_starts.clone();
+        for position in lms_positions {
+            let symbol = text[*position] as usize;
+            suffix_array[bucket_ends[symbol]] = *position as u32;
+            bucket_ends[symbol] += 1;
+        }
+        let mut bucket_starts = l_bucket_starts.clone();
+        let last_symbol = text[text_len - 1] as usize;
+        suffix_array[bucket_starts[last_symbol]] = (text_len - 1) as u32;
+        bucket_starts[last_symbol] += 1;
+        for idx in 0..text_len {
+            let position = suffix_array[idx];
+            if position != EMPTY && position > 0 && !is_s_type[position as usize - 1] {
+                let symbol = text[position as usize - 1] as usize;
+                suffix_array[bucket_starts[symbol]] = position - 1;
+                bucket_starts[symbol] += 1;
+            }
+        }
+        let mut bucket_ends = l_bucket_starts.clone();
+        for idx in (0..text_len).rev() {
+            let position = suffix_array[idx];
+            if position != EMPTY && position > 0 && is_s_type[position as usize - 1] {
+                let symbol = text[position as usize - 1] as usize;
+                bucket_ends[symbol + 1] -= 1;
+                suffix_array[bucket_ends[symbol + 1]] = position - 1;
+            }
+        }
+    };
+
+    let lms_positions: Vec<usize> = (1..text_len).filter(|position| is_lms(*position)).collect();
+    let mut lms_indices = vec![EMPTY; text_len];
+    for (lms_index, position) in lms_positions.iter().enumerate() {
+        lms_indices[*position] = lms_index as u32;
+    }
+
+    let mut suffix_array = Vec::with_capacity(text_len);
+    induce(&mut suffix_array, &lms_positions);
+    if lms_positions.is_empty() {
+        return suffix_array;
+    }
+
+    // Names of the LMS substrings, equal substrings sharing their name
+    let mut sorted_lms_positions: Vec<usize> = suffix_array.iter()
+        .map(|position| *position as usize)
+        .filter(|position| lms_indices[*position] != EMPTY)
+        .collect();
+    let lms_end = |position: usize| lms_positions.get(lms_indices[position] as usize + 1).copied().unwrap_or(text_len);
+    let mut reduced_text = vec![0u32; lms_positions.len()];
+    let mut max_name: u32 = 0;
+    for idx in 1..sorted_lms_positions.len() {
+        let previous = sorted_lms_positions[idx - 1];
+        let current = sorted_lms_positions[idx];
+        let (previous_end, current_end) = (lms_end(previous), lms_end(current));
+        let is_same_substring = previous_end - previous == current_end - current
+            && previous_end < text_len
+            && current_end < text_len
+            && text[previous..=previous_end] == text[current..=current_end];
+        if !is_same_substring {
+            max_name += 1;
+        }
+        reduced_text[lms_indices[current] as usize] = max_name;
+    }
+
+    let reduced_suffix_array = sa_is(&reduced_text, max_name as usize);
+    for (idx, lms_index) in reduced_suffix_array.into_iter().enumerate() {
+        sorted_lms_positions[idx] = lms_positions[lms_index as usize];
+    }
+    induce(&mut suffix_array, &sorted_lms_positions);
+
+    suffix_array
+}
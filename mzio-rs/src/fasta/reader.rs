@@ -1,171 +1,311 @@
 // std imports
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufReader;
 use std::io::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // 3rd party imports
-use anyhow::Result;
+use fallible_iterator::FallibleIterator;
 
 // internal imports
+use crate::compression;
+use crate::error::{Error, ErrorContext, Result};
 use crate::fasta::entry::FastaEntry;
+use crate::fasta::header::{AutoHeaderParser, FastaHeaderParser, GenericHeaderParser};
 
-/// Reader for common FASTA files as distributed by UniProt (https://uniprot.org)
-pub struct FastaReader {
-    internal_reader: BufReader<File>,
+/// Reader for common FASTA files as distributed by UniProt (https://uniprot.org), NCBI, Ensembl...
+/// Works on any buffered source (file, stdin, in-memory buffer...).
+pub struct FastaReader<R: BufRead> {
+    internal_reader: R,
     keep_plain_header: bool,
+    header_parser: Box<dyn FastaHeaderParser>,
+    /// Path of the read file, used to locate errors
+    file_path: Option<PathBuf>,
+    /// Number of bytes read so far
+    byte_offset: u64,
+    /// Number of lines read so far
+    line_number: u64,
+    /// Number of entries read so far
+    num_entries_read: usize,
     is_eof: bool,
-    header: String,
+    /// Header of the next entry (already read) and the location of its line
+    header: Option<(String, ErrorContext)>,
+    /// Location of the header line of the last created entry
+    entry_context: ErrorContext,
     sequence: String
 }
 
-impl FastaReader {
-    /// Creates a new Reader
+impl<R: BufRead> FastaReader<R> {
+    /// Creates a new Reader from any buffered source
     /// # Arguments
     ///
-    /// * `fasta_file_path` - Path to FASTA file
-    /// * `buffer_size` - Buffer size to use when loading bytes from disk.
+    /// * `reader` - Buffered source of FASTA content, e.g. `std::io::stdin().lock()` or `&[u8]`
     /// * `keep_plain_header` - Whether to keep or not the plain read header in the generated FastaEntry.
-    /// 
-    pub fn new(fasta_file_path: &Path, buffer_size: usize, keep_plain_header: bool) -> Result<Self> {
-        let fasta_file: File = File::open(fasta_file_path)?;
-        Ok(Self {
-            internal_reader: BufReader::with_capacity(buffer_size, fasta_file),
+    ///
+    pub fn from_reader(reader: R, keep_plain_header: bool) -> Self {
+        Self {
+            internal_reader: reader,
             keep_plain_header,
+            header_parser: Box::new(AutoHeaderParser),
+            file_path: None,
+            byte_offset: 0,
+            line_number: 0,
+            num_entries_read: 0,
             is_eof: false,
-            header: String::new(),
+            header: None,
+            entry_context: ErrorContext::default(),
             sequence: String::new()
-        })
+        }
     }
 
-    /// Processes and adds a keyword attribute to the HashMap
+    /// Sets the parser used to extract the entry information from the headers
+    /// (default: `AutoHeaderParser`, detecting the layout of each header).
+    /// Headers rejected by the parser are read with the `GenericHeaderParser`.
+    ///
     /// # Arguments
     ///
-    /// * `raw_attr` - Raw attributes, e.g. `key=value with spaces`
-    /// * `keyword_attributes` - Additional keyword attributes
-    /// 
-    fn prep_and_add_attribute_to_keyword_attributes(raw_attr: &str, keyword_attributes: &mut HashMap<String, String>) {
-        let attr_split = raw_attr.split("=").collect::<Vec<&str>>();
-        if let Some(key) = attr_split.get(0) {
-            if let Some(value) = attr_split.get(1) {
-                keyword_attributes.insert(
-                    key.to_string(),
-                    value.to_string()
-                );
-            }
+    /// * `header_parser` - FASTA header parser, e.g. `UniProtKbHeaderParser` or `FastaHeaderFormat::Ncbi`
+    ///
+    pub fn set_header_parser<P: FastaHeaderParser + 'static>(&mut self, header_parser: P) -> &mut Self {
+        self.header_parser = Box::new(header_parser);
+        self
+    }
+
+    /// Returns the number of entries read so far
+    ///
+    pub fn get_num_entries_read(&self) -> usize {
+        self.num_entries_read
+    }
+
+    /// Returns a reference to the underlying reader
+    ///
+    pub fn get_ref(&self) -> &R {
+        &self.internal_reader
+    }
+
+    /// Consumes the FASTA reader and returns the underlying reader
+    ///
+    pub fn into_inner(self) -> R {
+        self.internal_reader
+    }
+
+    /// Sets the location of the start of the underlying reader within the file, used to locate errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - Path of the read file, if any
+    /// * `byte_offset` - Byte offset of the current position of the underlying reader
+    /// * `line_number` - Number of lines located before the current position
+    ///
+    #[cfg(feature = "peff")]
+    pub(crate) fn set_location(&mut self, file_path: Option<PathBuf>, byte_offset: u64, line_number: u64) {
+        self.file_path = file_path;
+        self.byte_offset = byte_offset;
+        self.line_number = line_number;
+    }
+
+    /// Returns the location of the header line of the last read entry
+    ///
+    #[cfg(feature = "peff")]
+    pub(crate) fn get_entry_context(&self) -> &ErrorContext {
+        &self.entry_context
+    }
+
+    /// Reads the next line into the given buffer (cleared beforehand) and keeps track of the position.
+    /// Returns the location of the read line, None at EOF.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Line buffer
+    ///
+    fn read_line(&mut self, line: &mut String) -> Result<Option<ErrorContext>> {
+        line.clear();
+        let mut context = ErrorContext {
+            file_path: self.file_path.clone(),
+            record_index: Some(self.num_entries_read),
+            record_id: self.header.as_ref().and_then(|(header, _)| header_identifier(header)),
+            ..ErrorContext::new(self.byte_offset, self.line_number + 1)
+        };
+
+        let num_bytes = match self.internal_reader.read_line(line) {
+            Ok(num_bytes) => num_bytes,
+            Err(err) => return Err(Error::io(err).at(context))
+        };
+        if num_bytes == 0 {
+            return Ok(None);
+        }
+
+        self.byte_offset += num_bytes as u64;
+        self.line_number += 1;
+        if line.starts_with('>') {
+            // The header line starts a new entry
+            context.record_index = Some(self.num_entries_read + usize::from(self.header.is_some()));
+            context.record_id = header_identifier(line.trim());
         }
+
+        Ok(Some(context))
     }
 
-    /// Creates a new FastaEntry from the given header and sequence.
-    /// 
+    /// Creates a new FastaEntry from the pending header and sequence using the configured header parser.
+    ///
+    fn create_pending_entry(&mut self) -> Result<Option<FastaEntry>> {
+        let (header, context) = match self.header.take() {
+            Some(header) => header,
+            None => return Ok(None)
+        };
+
+        let entry = create_entry_or_generic(&header, &self.sequence, self.keep_plain_header, self.header_parser.as_ref());
+        self.sequence.clear();
+        self.entry_context = context.clone();
+        // The record is consumed even if invalid, so that reading can go on after the error
+        self.num_entries_read += 1;
+
+        match entry {
+            Some(entry) => Ok(Some(entry)),
+            None => Err(Error::malformed_header("empty header").at(context))
+        }
+    }
+}
+
+impl FastaReader<Box<dyn BufRead + Send>> {
+    /// Creates a new Reader.
+    /// Compressed files (gzip, bzip2, zstd, xz) are detected by their magic bytes
+    /// and decompressed on the fly, if the matching feature is enabled.
     /// # Arguments
     ///
-    /// * `header` - A FASTA header
-    /// * `sequence` - Amino acid sequence
+    /// * `fasta_file_path` - Path to FASTA file
+    /// * `buffer_size` - Buffer size to use when loading bytes from disk.
     /// * `keep_plain_header` - Whether to keep or not the plain read header in the generated FastaEntry.
     /// 
-    pub fn create_entry(header: &str, sequence: &str, keep_plain_header: bool) -> Option<FastaEntry> {
-        // Split by '|' and extract database and accession 
-        let mut header_split = header.split("|").collect::<Vec<&str>>();
-        let mut database: String = header_split.remove(0).to_string();
-        database = database.as_str()[1..].to_string(); // remove '>'
-        let accession: String = header_split.remove(0).to_string();
-
-        // Split by ' '
-        header_split = header_split.remove(0).split(" ").collect::<Vec<&str>>();
-
-        // Extract entry name 
-        let entry_name: String = header_split.remove(0).to_string();
-        // Add chunks to protein name until first string with '=' occurs (begin of keyword attributes) 
-        let mut protein_name: String = header_split.remove(0).to_string();
-        loop {
-            if let Some(chunk) = header_split.get(0) {
-                if !chunk.contains("=") {
-                    protein_name.push_str(" ");
-                    protein_name.push_str(header_split.remove(0));
-                } else {
-                    break
-                }
-            }
-        }
-        // Extract keyword attributes
-        let mut keyword_attributes: HashMap<String, String> = HashMap::new();
-        if header_split.len() > 0 {
-            let mut current_attr: String = String::new();
-            while header_split.len() > 0 {
-                if let Some(chunk) = header_split.get(0) {
-                    // Every time a chunk does not start a new attribute (chunk does not contains '=')
-                    // add the chunk to the current attribute, otherwise process the current attribute
-                    // and begin a new one
-                    if !chunk.contains("=") {
-                        current_attr.push_str(" ");
-                        current_attr.push_str(header_split.remove(0));
-                    } else {
-                        FastaReader::prep_and_add_attribute_to_keyword_attributes(
-                            &current_attr, 
-                            &mut keyword_attributes
-                        );
-                        if header_split.len() > 0 {
-                            current_attr = header_split.remove(0).to_string();
-                        }
-                    }
-                }
-            }
-            // Process the remaining attribute
-            FastaReader::prep_and_add_attribute_to_keyword_attributes(
-                &current_attr, 
-                &mut keyword_attributes
-            );
-        }
-        let plain_header_opt = if keep_plain_header {Some(header.to_string())} else {None};
-
-        return Some(FastaEntry::new(
-            database,   // database
-            accession,   // accession
-            entry_name,
-            protein_name,
-            keyword_attributes,
-            sequence.replace("\n", ""),
-            plain_header_opt
-        ));
+    pub fn new(fasta_file_path: &Path, buffer_size: usize, keep_plain_header: bool) -> anyhow::Result<Self> {
+        let mut reader = Self::from_reader(
+            compression::open_file(fasta_file_path, buffer_size)?,
+            keep_plain_header
+        );
+        reader.file_path = Some(fasta_file_path.to_path_buf());
+        Ok(reader)
     }
 }
 
 
-impl Iterator for FastaReader {
+impl<R: BufRead> FallibleIterator for FastaReader<R> {
     type Item = FastaEntry;
+    type Error = Error;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.is_eof {
-            return None;
-        }
-        self.sequence = String::new(); // Reset sequence, new header is still present from last iteration.
-        loop {
-            let mut line = String::new();
-            if let Ok(num_bytes) = self.internal_reader.read_line(&mut line) {
-                if num_bytes == 0 {
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        let mut line = String::new();
+        while !self.is_eof {
+            let context = match self.read_line(&mut line)? {
+                Some(context) => context,
+                None => {
                     self.is_eof = true;
-                    return FastaReader::create_entry(&self.header, &self.sequence, self.keep_plain_header);
+                    break;
+                }
+            };
+
+            let trimmed_line = line.trim();
+            if trimmed_line.is_empty() {
+                continue
+            }
+
+            if trimmed_line.starts_with('>') {
+                let new_header = (trimmed_line.to_string(), context);
+                // Return the previous entry and save newly read header
+                if self.header.is_some() {
+                    let entry = self.create_pending_entry();
+                    self.header = Some(new_header);
+                    return entry;
                 }
-                line = line.as_mut_str().trim().to_string();
-                if !line.starts_with(">") && num_bytes > 0 {
-                    self.sequence.push_str(&line)
-                } else {
-                    if self.header.len() > 0 {
-                        let entry = FastaReader::create_entry(&self.header, &self.sequence, self.keep_plain_header);
-                        self.header = line; // save newly read header
-                        return entry;
-                    } else  {
-                        self.header = line; 
-                    }
+                self.header = Some(new_header);
+            } else if self.header.is_none() {
+                return Err(Error::missing_value("FASTA header (sequence data found before any header)").at(context));
+            } else {
+                if let Some(idx) = trimmed_line.find(|c: char| !is_valid_residue(c)) {
+                    let residue = trimmed_line[idx..].chars().next().unwrap_or_default();
+                    return Err(Error::invalid_residue(residue).at(context));
                 }
+                self.sequence.push_str(trimmed_line);
             }
         }
-        
+
+        self.create_pending_entry()
     }
 }
 
+/// Creates a new FastaEntry from the given header and sequence,
+/// detecting the header layout (UniProtKB, UniRef, NCBI, Ensembl or generic).
+/// 
+/// # Arguments
+///
+/// * `header` - A FASTA header
+/// * `sequence` - Amino acid sequence
+/// * `keep_plain_header` - Whether to keep or not the plain read header in the generated FastaEntry.
+/// 
+pub fn create_entry(header: &str, sequence: &str, keep_plain_header: bool) -> Option<FastaEntry> {
+    create_entry_with_parser(header, sequence, keep_plain_header, &AutoHeaderParser)
+}
+
+/// Creates a new FastaEntry from the given header and sequence using the given header parser.
+/// Returns None if the header is rejected by the parser.
+/// 
+/// # Arguments
+///
+/// * `header` - A FASTA header
+/// * `sequence` - Amino acid sequence
+/// * `keep_plain_header` - Whether to keep or not the plain read header in the generated FastaEntry.
+/// * `header_parser` - FASTA header parser
+/// 
+pub fn create_entry_with_parser(header: &str, sequence: &str, keep_plain_header: bool,
+    header_parser: &dyn FastaHeaderParser) -> Option<FastaEntry> {
+    let parsed_header = header_parser.parse(header)?;
+    let plain_header_opt = if keep_plain_header {Some(header.to_string())} else {None};
+
+    Some(FastaEntry::new(
+        parsed_header.database,
+        parsed_header.accession,
+        parsed_header.entry_name,
+        parsed_header.protein_name,
+        parsed_header.keyword_attributes,
+        sequence.replace("\n", ""),
+        plain_header_opt
+    ))
+}
+
+/// Creates a new FastaEntry from the given header and sequence using the given header parser.
+/// Headers rejected by the parser are read with the `GenericHeaderParser`, so None is only returned for empty headers.
+/// 
+/// # Arguments
+///
+/// * `header` - A FASTA header
+/// * `sequence` - Amino acid sequence
+/// * `keep_plain_header` - Whether to keep or not the plain read header in the generated FastaEntry.
+/// * `header_parser` - FASTA header parser
+/// 
+pub(crate) fn create_entry_or_generic(header: &str, sequence: &str, keep_plain_header: bool,
+    header_parser: &dyn FastaHeaderParser) -> Option<FastaEntry> {
+    create_entry_with_parser(header, sequence, keep_plain_header, header_parser)
+        .or_else(|| create_entry_with_parser(header, sequence, keep_plain_header, &GenericHeaderParser))
+}
+
+/// Returns true if the given character may be part of a sequence:
+/// any letter (amino acid or nucleotide IUPAC codes), `*` (stop) or `-` (gap)
+///
+/// # Arguments
+///
+/// * `residue` - Sequence character
+///
+fn is_valid_residue(residue: char) -> bool {
+    residue.is_ascii_alphabetic() || residue == '*' || residue == '-'
+}
+
+/// Returns the identifier of a header line, i.e. its first word without '>', None if the header is empty
+///
+/// # Arguments
+///
+/// * `header` - A FASTA header
+///
+fn header_identifier(header: &str) -> Option<String> {
+    header.trim_start_matches('>').split_ascii_whitespace().next().map(str::to_string)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -199,7 +339,7 @@ QRSGIVALDGERELAFGPDDEVTVTLHDHAFRSIDVAACMRHAGRHHLMRSLPQPAAVG";
     /// Tests the creation of a FASTA entry from a header and a sequence.
     ///
     fn test_entry_creation() {
-        let entry = FastaReader::create_entry(TEST_HEADER, TEST_SEQUENCE, false).unwrap();
+        let entry = create_entry(TEST_HEADER, TEST_SEQUENCE, false).unwrap();
         assert_eq!(entry.get_database(), EXPECTED_DATABASE);
         assert_eq!(entry.get_accession(), EXPECTED_ACCESSION);
         assert_eq!(entry.get_entry_name(), EXPECTED_ENTRY_NAME);
@@ -8,21 +8,23 @@ use std::path::Path;
 use anyhow::Result;
 
 // internal imports
+use crate::compression::{self, CompressedFile, CompressionFormat};
 use crate::fasta::entry::FastaEntry;
 
 /// DEFAULT max amino acids per sequence line.
 const DEFAULT_MAX_AMINO_ACIDS_PER_LINE: usize = 60;
 
 /// Writer for common FASTA files as distributed by UniProt (https://uniprot.org)
+/// Works on any destination (file, stdout, `Vec<u8>`, socket, compressed stream...).
 /// Use flush() to mak ensure the buffer is written completely.
-pub struct FastaWriter {
+pub struct FastaWriter<W: Write> {
     /// Max amino acids per sequence line.
     max_amino_acids_per_line: Option<usize>,
     sort_keyword_attributes: bool,
-    internal_writer: BufWriter<File>
+    internal_writer: W
 }
 
-impl FastaWriter {
+impl FastaWriter<BufWriter<File>> {
     /// Creates a new Writer
     /// 
     /// # Arguments
@@ -34,11 +36,7 @@ impl FastaWriter {
     pub fn new(fasta_file_path: &Path, sort_keyword_attributes: bool, max_amino_acids_per_line: Option<usize>) -> Result<Self> {
         let fasta_file: File = File::create(fasta_file_path)?;
 
-        Ok(Self {
-            max_amino_acids_per_line,
-            sort_keyword_attributes,
-            internal_writer: BufWriter::new(fasta_file),
-        })
+        Ok(Self::from_writer(BufWriter::new(fasta_file), sort_keyword_attributes, max_amino_acids_per_line))
     }
 
     /// Creates a new Writer
@@ -49,13 +47,7 @@ impl FastaWriter {
     /// * `sort_keyword_attributes` - If true the keyword attributes will be sorted (for testing and readability reasons)
     ///
     pub fn new_with_default_seq_formatting(fasta_file_path: &Path, sort_keyword_attributes: bool) -> Result<Self> {
-        let fasta_file: File = File::create(fasta_file_path)?;
-
-        Ok(Self {
-            max_amino_acids_per_line: Some(DEFAULT_MAX_AMINO_ACIDS_PER_LINE),
-            sort_keyword_attributes,
-            internal_writer: BufWriter::new(fasta_file)
-        })
+        Self::new(fasta_file_path, sort_keyword_attributes, Some(DEFAULT_MAX_AMINO_ACIDS_PER_LINE))
     }
 
     /// Creates a new Writer
@@ -66,13 +58,7 @@ impl FastaWriter {
     /// * `sort_keyword_attributes` - If true the keyword attributes will be sorted (for testing and readability reasons)
     ///
     pub fn new_without_seq_formatting(fasta_file_path: &Path, sort_keyword_attributes: bool) -> Result<Self> {
-        let fasta_file: File = File::create(fasta_file_path)?;
-
-        Ok(Self {
-            max_amino_acids_per_line: None,
-            sort_keyword_attributes,
-            internal_writer: BufWriter::new(fasta_file)
-        })
+        Self::new(fasta_file_path, sort_keyword_attributes, None)
     }
 
     /// Creates a fasta header from the given entry.
@@ -83,26 +69,8 @@ impl FastaWriter {
     /// * `sort_keyword_attributes` - If true the keyword attributes will be sorted (for testing and readability reasons)
     /// 
     fn create_header(entry: &FastaEntry, sort_keyword_attributes: bool) -> String {
-        let mut header = ">".to_string();
-        header.push_str(entry.get_database());
-        header.push_str("|");
-        header.push_str(entry.get_accession());
-        header.push_str("|");
-        header.push_str(entry.get_entry_name());
-        header.push_str(" ");
-        header.push_str(entry.get_protein_name());
-        if entry.get_keyword_attributes().len() > 0 {
-            header.push_str(" ");
-            let mut keyword_arguments: Vec<String> = entry.get_keyword_attributes().into_iter()
-                .map(|(key, value)| format!("{}={}", key, value)).collect();
-            if sort_keyword_attributes {
-                keyword_arguments.sort();
-
-            }
-            header.push_str(&keyword_arguments.join(" "));
-        }
-        return header;
-    } 
+        entry.to_header(sort_keyword_attributes)
+    }
 
     /// Splits sequence into chunk of MAX_AMINO_ACIDS_PER_LINE.
     /// 
@@ -111,7 +79,7 @@ impl FastaWriter {
     /// * `sequence` - Amino acid sequence
     /// * `max_amino_acids_per_line` - Maximum length of each line containing sequence amino acids.
     /// 
-    fn format_sequence(sequence: &str, max_amino_acids_per_line: usize) -> String {
+    pub(crate) fn format_sequence(sequence: &str, max_amino_acids_per_line: usize) -> String {
         return sequence.chars()
             .collect::<Vec<char>>()
             .chunks(max_amino_acids_per_line)
@@ -140,6 +108,77 @@ impl FastaWriter {
 
         [header_as_string, seq_as_string].join("\n")
     }
+}
+
+impl FastaWriter<CompressedFile> {
+    /// Creates a new Writer compressing the output file.
+    /// Call `finish()` once everything is written to finalize the compressed stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `fasta_file_path` - Path to FASTA file
+    /// * `compression_format` - Compression format, e.g. `CompressionFormat::from_extension(fasta_file_path)`
+    /// * `sort_keyword_attributes` - If true the keyword attributes will be sorted (for testing and readability reasons)
+    /// * `max_amino_acids_per_line` - If Some(), will format the sequence line to not exceed the given length.
+    ///
+    pub fn new_compressed(fasta_file_path: &Path, compression_format: CompressionFormat, sort_keyword_attributes: bool,
+        max_amino_acids_per_line: Option<usize>) -> Result<Self> {
+        Ok(Self::from_writer(
+            compression::create_file(fasta_file_path, compression_format)?,
+            sort_keyword_attributes,
+            max_amino_acids_per_line
+        ))
+    }
+
+    /// Flushes the remaining content, finalizes the compressed stream and returns the file.
+    /// Unlike dropping the writer, this reports the errors occurring while finalizing the stream.
+    ///
+    pub fn finish(self) -> Result<File> {
+        compression::finish_file(self.into_inner())
+    }
+}
+
+impl<W: Write> FastaWriter<W> {
+    /// Creates a new Writer on top of any destination.
+    /// The given writer is used as is, wrap it into a `BufWriter` if it is unbuffered.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Destination of the FASTA content
+    /// * `sort_keyword_attributes` - If true the keyword attributes will be sorted (for testing and readability reasons)
+    /// * `max_amino_acids_per_line` - If Some(), will format the sequence line to not exceed the given length.
+    ///
+    pub fn from_writer(writer: W, sort_keyword_attributes: bool, max_amino_acids_per_line: Option<usize>) -> Self {
+        Self {
+            max_amino_acids_per_line,
+            sort_keyword_attributes,
+            internal_writer: writer,
+        }
+    }
+
+    /// Creates a new Writer on top of any destination, using the default sequence formatting.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Destination of the FASTA content
+    /// * `sort_keyword_attributes` - If true the keyword attributes will be sorted (for testing and readability reasons)
+    ///
+    pub fn from_writer_with_default_seq_formatting(writer: W, sort_keyword_attributes: bool) -> Self {
+        Self::from_writer(writer, sort_keyword_attributes, Some(DEFAULT_MAX_AMINO_ACIDS_PER_LINE))
+    }
+
+    /// Returns a reference to the underlying writer
+    ///
+    pub fn get_ref(&self) -> &W {
+        &self.internal_writer
+    }
+
+    /// Consumes the FASTA writer and returns the underlying writer.
+    /// Call flush() beforehand when the underlying writer is buffered.
+    ///
+    pub fn into_inner(self) -> W {
+        self.internal_writer
+    }
 
     /// Writes entry into the FASTA file.
     /// 
@@ -148,10 +187,11 @@ impl FastaWriter {
     /// * `entry` - FASTA entry
     ///
     pub fn write_entry(&mut self, entry: &FastaEntry) -> Result<usize> {
-        let mut entry_as_string = Self::stringify_entry(entry, self.sort_keyword_attributes, self.max_amino_acids_per_line);
+        let mut entry_as_string = FastaWriter::stringify_entry(entry, self.sort_keyword_attributes, self.max_amino_acids_per_line);
         entry_as_string.push_str("\n");
 
-        let written_bytes = self.internal_writer.write(entry_as_string.as_bytes())?;
+        self.internal_writer.write_all(entry_as_string.as_bytes())?;
+        let written_bytes = entry_as_string.len();
 
         return Ok(written_bytes);
     }
@@ -1,4 +1,8 @@
+pub mod compression;
+pub mod error;
 #[cfg(feature = "fasta")]
 pub mod fasta;
 #[cfg(feature = "mgf")]
 pub mod mgf;
+#[cfg(feature = "peff")]
+pub mod peff;
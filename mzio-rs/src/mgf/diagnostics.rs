@@ -0,0 +1,212 @@
+// std imports
+use std::fmt;
+use std::str::FromStr;
+
+// internal imports
+use crate::error::{Error, ErrorContext};
+
+/// Behaviour of the MGF reader when encountering malformed content
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MgfParsePolicy {
+    /// Fail on the first malformed line (default)
+    #[default]
+    Strict,
+    /// Drop the whole spectrum containing a malformed line and continue with the next one
+    SkipBadSpectrum,
+    /// Drop only the malformed lines, spectra which cannot be completed (e.g. EOF before END IONS) are still dropped
+    SkipBadLine,
+}
+
+impl FromStr for MgfParsePolicy {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "strict" => Ok(Self::Strict),
+            "skip_bad_spectrum" => Ok(Self::SkipBadSpectrum),
+            "skip_bad_line" => Ok(Self::SkipBadLine),
+            _ => Err(Error::malformed_header(format!("unknown parse policy: {}", value)))
+        }
+    }
+}
+
+impl fmt::Display for MgfParsePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Strict => write!(f, "strict"),
+            Self::SkipBadSpectrum => write!(f, "skip_bad_spectrum"),
+            Self::SkipBadLine => write!(f, "skip_bad_line"),
+        }
+    }
+}
+
+/// Kind of content dropped by a lenient parse policy
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MgfSkippedItem {
+    Line,
+    Spectrum,
+}
+
+/// Content dropped while reading, with the reason why
+#[derive(Debug)]
+pub struct MgfParseDiagnostic {
+    /// Kind of dropped content
+    pub skipped_item: MgfSkippedItem,
+    /// Parsing error which caused the drop, located in the input
+    pub error: Error,
+}
+
+impl MgfParseDiagnostic {
+    /// Returns the kind of dropped content
+    ///
+    pub fn get_skipped_item(&self) -> MgfSkippedItem {
+        self.skipped_item
+    }
+
+    /// Returns the parsing error which caused the drop
+    ///
+    pub fn get_error(&self) -> &Error {
+        &self.error
+    }
+
+    /// Returns the location of the dropped content
+    ///
+    pub fn get_context(&self) -> &ErrorContext {
+        self.error.context()
+    }
+}
+
+impl fmt::Display for MgfParseDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.skipped_item {
+            MgfSkippedItem::Line => write!(f, "skipped line: {}", self.error),
+            MgfSkippedItem::Spectrum => write!(f, "skipped spectrum: {}", self.error),
+        }
+    }
+}
+
+/// Default maximum number of diagnostics kept by a parse report
+pub const DEFAULT_MAX_DIAGNOSTICS: usize = 1000;
+
+/// Report of the content dropped by a lenient parse policy.
+/// Every drop is counted, but only the first diagnostics are kept (see `set_max_diagnostics()`)
+/// so the report stays small when reading large files full of malformed lines.
+#[derive(Debug)]
+pub struct MgfParseReport {
+    /// Number of dropped spectra
+    pub num_skipped_spectra: usize,
+    /// Number of dropped lines, within spectra kept or not
+    pub num_skipped_lines: usize,
+    /// First dropped content, in reading order
+    pub diagnostics: Vec<MgfParseDiagnostic>,
+    /// Maximum number of kept diagnostics
+    max_diagnostics: usize,
+}
+
+impl Default for MgfParseReport {
+    fn default() -> Self {
+        Self::with_max_diagnostics(DEFAULT_MAX_DIAGNOSTICS)
+    }
+}
+
+impl MgfParseReport {
+    /// Creates a new, empty report keeping up to `DEFAULT_MAX_DIAGNOSTICS` diagnostics
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new, empty report keeping up to the given number of diagnostics
+    ///
+    /// # Arguments
+    ///
+    /// * `max_diagnostics` - Maximum number of kept diagnostics
+    ///
+    pub fn with_max_diagnostics(max_diagnostics: usize) -> Self {
+        Self {
+            num_skipped_spectra: 0,
+            num_skipped_lines: 0,
+            diagnostics: Vec::new(),
+            max_diagnostics,
+        }
+    }
+
+    /// Returns true if nothing was dropped
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.num_skipped_spectra == 0 && self.num_skipped_lines == 0
+    }
+
+    /// Returns the number of dropped spectra
+    ///
+    pub fn get_num_skipped_spectra(&self) -> usize {
+        self.num_skipped_spectra
+    }
+
+    /// Returns the number of dropped lines
+    ///
+    pub fn get_num_skipped_lines(&self) -> usize {
+        self.num_skipped_lines
+    }
+
+    /// Returns the first dropped content, in reading order
+    ///
+    pub fn get_diagnostics(&self) -> &Vec<MgfParseDiagnostic> {
+        &self.diagnostics
+    }
+
+    /// Returns the number of drops counted but not kept as diagnostic because the limit was reached
+    ///
+    pub fn get_num_omitted_diagnostics(&self) -> usize {
+        self.num_skipped_spectra + self.num_skipped_lines - self.diagnostics.len()
+    }
+
+    /// Returns the maximum number of kept diagnostics
+    ///
+    pub fn get_max_diagnostics(&self) -> usize {
+        self.max_diagnostics
+    }
+
+    /// Sets the maximum number of kept diagnostics, already kept ones beyond the limit are discarded
+    ///
+    /// # Arguments
+    ///
+    /// * `max_diagnostics` - Maximum number of kept diagnostics
+    ///
+    pub fn set_max_diagnostics(&mut self, max_diagnostics: usize) -> &mut Self {
+        self.max_diagnostics = max_diagnostics;
+        self.diagnostics.truncate(max_diagnostics);
+        self
+    }
+
+    /// Records dropped content
+    ///
+    /// # Arguments
+    ///
+    /// * `skipped_item` - Kind of dropped content
+    /// * `error` - Located parsing error which caused the drop
+    ///
+    pub fn add(&mut self, skipped_item: MgfSkippedItem, error: Error) {
+        match skipped_item {
+            MgfSkippedItem::Line => self.num_skipped_lines += 1,
+            MgfSkippedItem::Spectrum => self.num_skipped_spectra += 1,
+        }
+        if self.diagnostics.len() < self.max_diagnostics {
+            self.diagnostics.push(MgfParseDiagnostic { skipped_item, error });
+        }
+    }
+
+    /// Appends the content of another report, e.g. of a chunk read in parallel.
+    /// Diagnostics beyond the limit of this report are discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Report to append
+    ///
+    pub fn merge(&mut self, other: MgfParseReport) {
+        self.num_skipped_spectra += other.num_skipped_spectra;
+        self.num_skipped_lines += other.num_skipped_lines;
+        let num_free_slots = self.max_diagnostics.saturating_sub(self.diagnostics.len());
+        self.diagnostics.extend(other.diagnostics.into_iter().take(num_free_slots));
+    }
+}
@@ -0,0 +1,182 @@
+// std imports
+use std::fmt;
+use std::str::FromStr;
+
+// 3rd party imports
+use serde::{Serialize, Deserialize};
+
+// internal imports
+use crate::error::Error;
+use crate::mgf::spectrum::{MgfSpectrumHeader, MgfToleranceUnit};
+
+/// Type of mass used for the search (MASS)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum MgfMassType {
+    Monoisotopic,
+    Average,
+}
+
+impl FromStr for MgfMassType {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "monoisotopic" => Ok(Self::Monoisotopic),
+            "average" => Ok(Self::Average),
+            _ => Err(Error::malformed_header(format!("unknown mass type: {}", value)))
+        }
+    }
+}
+
+impl fmt::Display for MgfMassType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Monoisotopic => write!(f, "Monoisotopic"),
+            Self::Average => write!(f, "Average"),
+        }
+    }
+}
+
+/// File level parameters of a MGF file, given before the first `BEGIN IONS`.
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct MgfGlobalHeader {
+    /// Search title (COM)
+    pub comment: Option<String>,
+    /// Type of search, e.g. MIS, SQ or PMF (SEARCH)
+    pub search: Option<String>,
+    /// Type of mass (MASS)
+    pub mass_type: Option<MgfMassType>,
+    /// Default candidate precursor charges of the spectra (CHARGE)
+    pub precursor_charges: Vec<i8>,
+    /// Peptide mass tolerance (TOL)
+    pub tolerance: Option<f64>,
+    /// Unit of the peptide mass tolerance (TOLU)
+    pub tolerance_unit: Option<MgfToleranceUnit>,
+    /// Fragment ion mass tolerance (ITOL)
+    pub fragment_tolerance: Option<f64>,
+    /// Unit of the fragment ion mass tolerance (ITOLU)
+    pub fragment_tolerance_unit: Option<MgfToleranceUnit>,
+    /// Enzyme (CLE)
+    pub enzyme: Option<String>,
+    /// Database (DB)
+    pub database: Option<String>,
+    /// Fixed modifications (MODS)
+    pub fixed_modifications: Vec<String>,
+    /// Variable modifications (IT_MODS)
+    pub variable_modifications: Vec<String>,
+    /// Instrument type (INSTRUMENT)
+    pub instrument: Option<String>,
+    /// Parameters not handled explicitly (e.g. TAXONOMY, USERNAME), in reading order
+    pub additional_params: Vec<(String, String)>,
+}
+
+impl MgfGlobalHeader {
+    /// Creates a new, empty global header
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if no global parameter is set
+    ///
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns the search title
+    ///
+    pub fn get_comment(&self) -> Option<&String> {
+        self.comment.as_ref()
+    }
+
+    /// Returns the type of search
+    ///
+    pub fn get_search(&self) -> Option<&String> {
+        self.search.as_ref()
+    }
+
+    /// Returns the type of mass
+    ///
+    pub fn get_mass_type(&self) -> Option<MgfMassType> {
+        self.mass_type
+    }
+
+    /// Returns the default candidate precursor charges
+    ///
+    pub fn get_precursor_charges(&self) -> &Vec<i8> {
+        &self.precursor_charges
+    }
+
+    /// Returns the peptide mass tolerance
+    ///
+    pub fn get_tolerance(&self) -> Option<f64> {
+        self.tolerance
+    }
+
+    /// Returns the unit of the peptide mass tolerance
+    ///
+    pub fn get_tolerance_unit(&self) -> Option<MgfToleranceUnit> {
+        self.tolerance_unit
+    }
+
+    /// Returns the fragment ion mass tolerance
+    ///
+    pub fn get_fragment_tolerance(&self) -> Option<f64> {
+        self.fragment_tolerance
+    }
+
+    /// Returns the unit of the fragment ion mass tolerance
+    ///
+    pub fn get_fragment_tolerance_unit(&self) -> Option<MgfToleranceUnit> {
+        self.fragment_tolerance_unit
+    }
+
+    /// Returns the enzyme
+    ///
+    pub fn get_enzyme(&self) -> Option<&String> {
+        self.enzyme.as_ref()
+    }
+
+    /// Returns the database
+    ///
+    pub fn get_database(&self) -> Option<&String> {
+        self.database.as_ref()
+    }
+
+    /// Returns the fixed modifications
+    ///
+    pub fn get_fixed_modifications(&self) -> &Vec<String> {
+        &self.fixed_modifications
+    }
+
+    /// Returns the variable modifications
+    ///
+    pub fn get_variable_modifications(&self) -> &Vec<String> {
+        &self.variable_modifications
+    }
+
+    /// Returns the instrument type
+    ///
+    pub fn get_instrument(&self) -> Option<&String> {
+        self.instrument.as_ref()
+    }
+
+    /// Returns the parameters not handled explicitly, in reading order
+    ///
+    pub fn get_additional_params(&self) -> &Vec<(String, String)> {
+        &self.additional_params
+    }
+
+    /// Applies the global defaults to a spectrum header.
+    /// Only values missing from the spectrum are set, currently the precursor charges.
+    ///
+    /// # Arguments
+    ///
+    /// * `spectrum_header` - Header of a spectrum read from the same file
+    ///
+    pub fn apply_defaults(&self, spectrum_header: &mut MgfSpectrumHeader) {
+        if spectrum_header.precursor_charges.is_empty() {
+            spectrum_header.precursor_charges = self.precursor_charges.clone();
+        }
+    }
+}
@@ -0,0 +1,413 @@
+// std imports
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, SeekFrom};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+// 3rd party imports
+use fallible_iterator::FallibleIterator;
+
+// internal imports
+use crate::error::{Error, ErrorContext, Result};
+use crate::mgf::global_header::MgfGlobalHeader;
+use crate::mgf::reader::{MgfReader, parse_scan, parse_scan_range};
+use crate::mgf::spectrum::MgfSpectrum;
+
+/// First line of the index files, describing the columns
+const INDEX_FILE_HEADER: &str = "#offset\tfirst_scan\tlast_scan\ttitle";
+
+/// Location of a spectrum within a MGF file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MgfIndexRecord {
+    /// Byte offset of the `BEGIN IONS` line
+    pub offset: u64,
+    /// Title of the spectrum, empty if none
+    pub title: String,
+    /// Scan number, or first scan of the range (SCANS)
+    pub first_scan: Option<u32>,
+    /// Last scan of the range (SCANS)
+    pub last_scan: Option<u32>,
+}
+
+/// Index of the spectra of a MGF file, accessible by ordinal, title or scan number
+#[derive(Clone, Debug, Default)]
+pub struct MgfIndex {
+    records: Vec<MgfIndexRecord>,
+    /// Record indices by title
+    title_to_record: HashMap<String, usize>,
+    /// Record indices by (first) scan number
+    scan_to_record: HashMap<u32, usize>,
+}
+
+impl MgfIndex {
+    /// Creates an index from the given records
+    ///
+    /// # Arguments
+    ///
+    /// * `records` - Index records, in file order
+    ///
+    pub fn new(records: Vec<MgfIndexRecord>) -> Self {
+        let mut title_to_record = HashMap::with_capacity(records.len());
+        let mut scan_to_record = HashMap::with_capacity(records.len());
+        for (record_idx, record) in records.iter().enumerate() {
+            if !record.title.is_empty() {
+                title_to_record.entry(record.title.clone()).or_insert(record_idx);
+            }
+            if let Some(first_scan) = record.first_scan {
+                scan_to_record.entry(first_scan).or_insert(record_idx);
+            }
+        }
+
+        Self {
+            records,
+            title_to_record,
+            scan_to_record,
+        }
+    }
+
+    /// Builds the index by scanning the given MGF content.
+    /// Only the `BEGIN IONS`, `TITLE` and `SCANS` lines are interpreted.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Buffered source of uncompressed MGF content
+    ///
+    pub fn build<R: BufRead>(mut reader: R) -> Result<Self> {
+        let mut records: Vec<MgfIndexRecord> = Vec::new();
+        let mut byte_offset: u64 = 0;
+        let mut in_spectrum = false;
+
+        let mut line: Vec<u8> = Vec::new();
+        loop {
+            line.clear();
+            let num_bytes = reader.read_until(b'\n', &mut line).map_err(Error::io)? as u64;
+            if num_bytes == 0 {
+                break;
+            }
+            let line_offset = byte_offset;
+            byte_offset += num_bytes;
+
+            let trimmed_line = line.trim_ascii();
+            if trimmed_line.starts_with(b"BEGIN IONS") {
+                records.push(MgfIndexRecord { offset: line_offset, title: String::new(), first_scan: None, last_scan: None });
+                in_spectrum = true;
+            } else if trimmed_line.starts_with(b"END IONS") {
+                in_spectrum = false;
+            } else if !in_spectrum {
+                continue;
+            } else if let Some(title) = trimmed_line.strip_prefix(b"TITLE=") {
+                if let Some(record) = records.last_mut() {
+                    record.title = String::from_utf8_lossy(title).into_owned();
+                }
+            } else if let Some(scans) = trimmed_line.strip_prefix(b"SCANS=") {
+                // Non-numeric scan identifiers (e.g. `F1:1000`) are not indexed
+                let scan_range = parse_scan_range(std::str::from_utf8(scans).unwrap_or_default());
+                if let (Some(record), Some((first_scan, last_scan))) = (records.last_mut(), scan_range) {
+                    record.first_scan = Some(first_scan);
+                    record.last_scan = last_scan;
+                }
+            }
+        }
+
+        Ok(Self::new(records))
+    }
+
+    /// Builds the index of the given MGF file
+    ///
+    /// # Arguments
+    ///
+    /// * `mgf_file_path` - Path to the uncompressed MGF file
+    ///
+    pub fn build_from_file(mgf_file_path: &Path) -> Result<Self> {
+        let mgf_file = File::open(mgf_file_path).map_err(|err| Error::io(err).in_file(mgf_file_path))?;
+        Self::build(BufReader::new(mgf_file)).map_err(|err| err.in_file(mgf_file_path))
+    }
+
+    /// Reads an index written by `write()`
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Buffered source of the index content
+    ///
+    pub fn read<R: BufRead>(reader: R) -> Result<Self> {
+        let mut records: Vec<MgfIndexRecord> = Vec::new();
+
+        for (line_idx, line) in reader.lines().enumerate() {
+            let context = ErrorContext::new(0, line_idx as u64 + 1);
+            let line = line.map_err(|err| Error::io(err).at(context.clone()))?;
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // The title comes last, so that it may contain any character
+            let columns: Vec<&str> = line.splitn(4, '\t').collect();
+            if columns.len() < 4 {
+                return Err(Error::invalid_index("expected 4 tab separated columns").at(context));
+            }
+            let parse_optional_scan = |value: &str| -> Result<Option<u32>> {
+                if value.is_empty() {
+                    return Ok(None);
+                }
+                parse_scan(value).map(Some).map_err(|err| err.at(context.clone()))
+            };
+
+            records.push(MgfIndexRecord {
+                offset: columns[0].parse().map_err(|_| Error::invalid_number(columns[0]).at(context.clone()))?,
+                first_scan: parse_optional_scan(columns[1])?,
+                last_scan: parse_optional_scan(columns[2])?,
+                title: columns[3].to_string(),
+            });
+        }
+
+        Ok(Self::new(records))
+    }
+
+    /// Reads the given index file
+    ///
+    /// # Arguments
+    ///
+    /// * `index_file_path` - Path to the index file
+    ///
+    pub fn read_file(index_file_path: &Path) -> Result<Self> {
+        let index_file = File::open(index_file_path).map_err(|err| Error::io(err).in_file(index_file_path))?;
+        Self::read(BufReader::new(index_file)).map_err(|err| err.in_file(index_file_path))
+    }
+
+    /// Writes the index as tab separated values: offset, first scan, last scan and title
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Destination of the index content
+    ///
+    pub fn write<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(writer, "{}", INDEX_FILE_HEADER)?;
+        for record in self.records.iter() {
+            let format_scan = |scan: Option<u32>| scan.map(|scan| scan.to_string()).unwrap_or_default();
+            writeln!(
+                writer,
+                "{}\t{}\t{}\t{}",
+                record.offset, format_scan(record.first_scan), format_scan(record.last_scan), record.title
+            )?;
+        }
+        writer.flush()
+    }
+
+    /// Writes the index into the given file, usually located by `index_file_path()`
+    ///
+    /// # Arguments
+    ///
+    /// * `index_file_path` - Path to the index file
+    ///
+    pub fn write_file(&self, index_file_path: &Path) -> std::io::Result<()> {
+        self.write(BufWriter::new(File::create(index_file_path)?))
+    }
+
+    /// Returns the index records, in file order
+    ///
+    pub fn get_records(&self) -> &Vec<MgfIndexRecord> {
+        &self.records
+    }
+
+    /// Returns the number of indexed spectra
+    ///
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns true if no spectrum is indexed
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns the ordinal of the spectrum with the given title
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - Spectrum title
+    ///
+    pub fn find_by_title(&self, title: &str) -> Option<usize> {
+        self.title_to_record.get(title).copied()
+    }
+
+    /// Returns the ordinal of the spectrum with the given scan number, or whose scan range contains it
+    ///
+    /// # Arguments
+    ///
+    /// * `scan` - Scan number
+    ///
+    pub fn find_by_scan(&self, scan: u32) -> Option<usize> {
+        self.scan_to_record.get(&scan).copied().or_else(|| {
+            self.records.iter().position(|record| match (record.first_scan, record.last_scan) {
+                (Some(first_scan), Some(last_scan)) => first_scan <= scan && scan <= last_scan,
+                _ => false
+            })
+        })
+    }
+}
+
+/// Returns the path of the index file of the given MGF file, e.g. `spectra.mgf.mgfi`
+///
+/// # Arguments
+///
+/// * `mgf_file_path` - Path to the MGF file
+///
+pub fn index_file_path(mgf_file_path: &Path) -> PathBuf {
+    let mut index_file_path = mgf_file_path.as_os_str().to_owned();
+    index_file_path.push(".mgfi");
+    PathBuf::from(index_file_path)
+}
+
+/// Reader fetching single spectra of an indexed MGF file, without scanning it
+pub struct IndexedMgfReader<R: BufRead + Seek> {
+    internal_reader: R,
+    /// Path of the read file, used to locate errors
+    file_path: Option<PathBuf>,
+    index: MgfIndex,
+    global_header: MgfGlobalHeader,
+    apply_global_defaults: bool,
+}
+
+impl IndexedMgfReader<BufReader<File>> {
+    /// Creates a new Reader.
+    /// The index is read from the index file next to the MGF file (see `index_file_path()`), or built if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `mgf_file_path` - Path to the uncompressed MGF file
+    ///
+    pub fn new(mgf_file_path: &Path) -> Result<Self> {
+        let index_file_path = index_file_path(mgf_file_path);
+        let index = if index_file_path.exists() {
+            MgfIndex::read_file(&index_file_path)?
+        } else {
+            MgfIndex::build_from_file(mgf_file_path)?
+        };
+
+        let mgf_file = File::open(mgf_file_path).map_err(|err| Error::io(err).in_file(mgf_file_path))?;
+        let mut reader = Self::from_reader(BufReader::new(mgf_file), index).map_err(|err| err.in_file(mgf_file_path))?;
+        reader.file_path = Some(mgf_file_path.to_path_buf());
+        Ok(reader)
+    }
+}
+
+impl<R: BufRead + Seek> IndexedMgfReader<R> {
+    /// Creates a new Reader from any seekable source, reading its global header
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Seekable source of uncompressed MGF content
+    /// * `index` - Index of the content
+    ///
+    pub fn from_reader(mut reader: R, index: MgfIndex) -> Result<Self> {
+        reader.seek(SeekFrom::Start(0)).map_err(Error::io)?;
+        let global_header = MgfReader::from_reader(&mut reader).read_global_header()?.clone();
+
+        Ok(Self {
+            internal_reader: reader,
+            file_path: None,
+            index,
+            global_header,
+            apply_global_defaults: true,
+        })
+    }
+
+    /// Sets whether the global defaults (e.g. CHARGE) are applied to spectra lacking their own value (default: true)
+    ///
+    /// # Arguments
+    ///
+    /// * `apply_global_defaults` - Whether to apply the global defaults or not
+    ///
+    pub fn set_apply_global_defaults(&mut self, apply_global_defaults: bool) -> &mut Self {
+        self.apply_global_defaults = apply_global_defaults;
+        self
+    }
+
+    /// Returns the index
+    ///
+    pub fn get_index(&self) -> &MgfIndex {
+        &self.index
+    }
+
+    /// Returns the global parameters
+    ///
+    pub fn get_global_header(&self) -> &MgfGlobalHeader {
+        &self.global_header
+    }
+
+    /// Consumes the indexed reader and returns the underlying reader
+    ///
+    pub fn into_inner(self) -> R {
+        self.internal_reader
+    }
+
+    /// Reads the spectrum at the given ordinal (0-based).
+    /// Returns None if out of range.
+    /// Line numbers of the errors are counted from the start of the spectrum, the byte offsets are absolute.
+    ///
+    /// # Arguments
+    ///
+    /// * `spectrum_idx` - Ordinal of the spectrum within the file
+    ///
+    pub fn get_by_index(&mut self, spectrum_idx: usize) -> Result<Option<MgfSpectrum>> {
+        let record = match self.index.get_records().get(spectrum_idx) {
+            Some(record) => record.clone(),
+            None => return Ok(None)
+        };
+        let context = ErrorContext {
+            file_path: self.file_path.clone(),
+            record_index: Some(spectrum_idx),
+            record_id: Some(record.title.clone()).filter(|title| !title.is_empty()),
+            ..ErrorContext::new(record.offset, 0)
+        };
+
+        self.internal_reader.seek(SeekFrom::Start(record.offset)).map_err(|err| Error::io(err).at(context.clone()))?;
+
+        let mut spectrum_reader = MgfReader::from_reader(&mut self.internal_reader);
+        spectrum_reader.set_location(self.file_path.clone(), record.offset, 0, spectrum_idx);
+        spectrum_reader.set_apply_global_defaults(false);
+
+        let mut spectrum = match spectrum_reader.next()? {
+            Some(spectrum) => spectrum,
+            None => return Err(Error::invalid_index("no spectrum found at the indexed offset").at(context))
+        };
+        if spectrum.header.get_title() != &record.title {
+            return Err(Error::invalid_index("spectrum title does not match the index").at(context));
+        }
+
+        if self.apply_global_defaults {
+            self.global_header.apply_defaults(&mut spectrum.header);
+        }
+
+        Ok(Some(spectrum))
+    }
+
+    /// Reads the spectrum with the given title.
+    /// Returns None if not indexed.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - Spectrum title
+    ///
+    pub fn get_by_title(&mut self, title: &str) -> Result<Option<MgfSpectrum>> {
+        match self.index.find_by_title(title) {
+            Some(spectrum_idx) => self.get_by_index(spectrum_idx),
+            None => Ok(None)
+        }
+    }
+
+    /// Reads the spectrum with the given scan number, or whose scan range contains it.
+    /// Returns None if not indexed.
+    ///
+    /// # Arguments
+    ///
+    /// * `scan` - Scan number
+    ///
+    pub fn get_by_scan(&mut self, scan: u32) -> Result<Option<MgfSpectrum>> {
+        match self.index.find_by_scan(scan) {
+            Some(spectrum_idx) => self.get_by_index(spectrum_idx),
+            None => Ok(None)
+        }
+    }
+}
@@ -0,0 +1,433 @@
+//! Allocation-free MGF reading over memory-mapped files: spectra are yielded as views
+//! borrowing from the mapped bytes, their peaks being parsed only on demand.
+
+// std imports
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+// 3rd party imports
+use anyhow::bail;
+use fallible_iterator::FallibleIterator;
+use memmap2::Mmap;
+
+// internal imports
+use crate::compression::CompressionFormat;
+use crate::error::{Error, ErrorContext, Result};
+use crate::mgf::global_header::MgfGlobalHeader;
+use crate::mgf::reader::{MgfReader, parse_charges, parse_global_param, parse_number, parse_spectrum_param};
+use crate::mgf::spectrum::{MgfSpectrum, MgfSpectrumHeader};
+
+/// Memory-mapped MGF file, read as spectrum views
+pub struct MmapMgfReader {
+    mmap: Mmap,
+    file_path: PathBuf,
+}
+
+impl MmapMgfReader {
+    /// Maps the given MGF file into memory.
+    /// Compressed files are not supported, as they can't be read in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `mgf_file_path` - Path to MGF file
+    ///
+    pub fn new(mgf_file_path: &Path) -> anyhow::Result<Self> {
+        let file = File::open(mgf_file_path)?;
+
+        // Safety: the file must not be modified while it is mapped, as for any memory-mapped file
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let format = CompressionFormat::from_magic_bytes(&mmap);
+        if format != CompressionFormat::Uncompressed {
+            bail!("{:?} compressed files can't be memory-mapped, decompress {} first", format, mgf_file_path.display());
+        }
+
+        Ok(Self { mmap, file_path: mgf_file_path.to_path_buf() })
+    }
+
+    /// Returns the mapped content
+    ///
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    /// Returns the path of the mapped file
+    ///
+    pub fn get_file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Returns an iterator over views of the spectra, borrowing from the mapped content
+    ///
+    pub fn views(&self) -> MgfSpectrumViews<'_> {
+        let mut views = MgfSpectrumViews::new(&self.mmap);
+        views.file_path = Some(&self.file_path);
+        views
+    }
+}
+
+/// Iterator over views of the spectra of MGF content held in memory (memory-mapped file, buffer...)
+pub struct MgfSpectrumViews<'a> {
+    bytes: &'a [u8],
+    /// Path of the read file, used to locate errors
+    file_path: Option<&'a Path>,
+    /// Byte offset of the next line
+    byte_offset: usize,
+    /// Number of lines read so far
+    line_number: u64,
+    /// Number of spectra read so far
+    num_spectra_read: usize,
+    global_header: MgfGlobalHeader,
+    is_global_header_read: bool,
+}
+
+impl<'a> MgfSpectrumViews<'a> {
+    /// Creates a new iterator over the spectra of the given MGF content
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - MGF content
+    ///
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            file_path: None,
+            byte_offset: 0,
+            line_number: 0,
+            num_spectra_read: 0,
+            global_header: MgfGlobalHeader::new(),
+            is_global_header_read: false,
+        }
+    }
+
+    /// Reads the global parameters given before the first `BEGIN IONS`, if not read yet.
+    /// This is done automatically when reading the first spectrum.
+    ///
+    pub fn read_global_header(&mut self) -> Result<&MgfGlobalHeader> {
+        if !self.is_global_header_read {
+            self.is_global_header_read = true;
+            while let Some(line) = self.next_line() {
+                let line_start = self.byte_offset - line.len();
+                let trimmed_line = line.trim_ascii();
+                if trimmed_line.starts_with(b"BEGIN IONS") {
+                    // The spectrum is read by next()
+                    self.byte_offset = line_start;
+                    self.line_number -= 1;
+                    break;
+                }
+
+                let trimmed_line = self.decode(trimmed_line, line_start)?;
+                if let Some((key, value)) = trimmed_line.split_once('=') {
+                    parse_global_param(&mut self.global_header, key, value)
+                        .map_err(|err| self.locate(err, line_start, self.line_number))?;
+                }
+            }
+        }
+
+        Ok(&self.global_header)
+    }
+
+    /// Returns the global parameters.
+    /// Empty until read_global_header() is called or the first spectrum is read.
+    ///
+    pub fn get_global_header(&self) -> &MgfGlobalHeader {
+        &self.global_header
+    }
+
+    /// Returns the number of spectra read so far
+    ///
+    pub fn get_num_spectra_read(&self) -> usize {
+        self.num_spectra_read
+    }
+
+    /// Returns the next line, including its line terminator, or None at EOF
+    ///
+    fn next_line(&mut self) -> Option<&'a [u8]> {
+        let remaining = &self.bytes[self.byte_offset..];
+        if remaining.is_empty() {
+            return None;
+        }
+
+        let line_len = remaining.iter().position(|&byte| byte == b'\n').map_or(remaining.len(), |idx| idx + 1);
+        self.byte_offset += line_len;
+        self.line_number += 1;
+
+        Some(&remaining[..line_len])
+    }
+
+    /// Decodes the given UTF-8 content
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Content to decode
+    /// * `byte_offset` - Byte offset of the line containing the content
+    ///
+    fn decode(&self, bytes: &'a [u8], byte_offset: usize) -> Result<&'a str> {
+        std::str::from_utf8(bytes).map_err(|err| {
+            let error = Error::io(std::io::Error::new(std::io::ErrorKind::InvalidData, err));
+            self.locate(error, byte_offset, self.line_number)
+        })
+    }
+
+    /// Locates the given error at the given line
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - Error to locate
+    /// * `byte_offset` - Byte offset of the line
+    /// * `line_number` - Line number (1-based)
+    ///
+    fn locate(&self, error: Error, byte_offset: usize, line_number: u64) -> Error {
+        let context = ErrorContext {
+            file_path: self.file_path.map(Path::to_path_buf),
+            ..ErrorContext::new(byte_offset as u64, line_number)
+        };
+        error.at(context)
+    }
+}
+
+impl<'a> FallibleIterator for MgfSpectrumViews<'a> {
+    type Item = MgfSpectrumView<'a>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        self.read_global_header()?;
+
+        // Look for the next spectrum
+        let (spectrum_start, begin_line_number) = loop {
+            match self.next_line() {
+                Some(line) if line.trim_ascii().starts_with(b"BEGIN IONS") => {
+                    break (self.byte_offset - line.len(), self.line_number)
+                },
+                Some(_) => continue,
+                None => return Ok(None)
+            }
+        };
+
+        // Look for its end
+        let spectrum_end = loop {
+            let line = match self.next_line() {
+                Some(line) => line,
+                None => {
+                    let error = Error::truncated_record("reach EOF before END IONS (incomplete spectrum)");
+                    return Err(self.locate(error, self.byte_offset, self.line_number));
+                }
+            };
+
+            let trimmed_line = line.trim_ascii();
+            if trimmed_line.starts_with(b"END IONS") {
+                break self.byte_offset;
+            } else if trimmed_line.starts_with(b"BEGIN IONS") {
+                // The new spectrum will be read by the next call
+                self.byte_offset -= line.len();
+                self.line_number -= 1;
+                let error = Error::truncated_record("BEGIN IONS before END IONS (incomplete spectrum)");
+                return Err(self.locate(error, self.byte_offset, self.line_number + 1));
+            }
+        };
+
+        let text = self.decode(&self.bytes[spectrum_start..spectrum_end], spectrum_start)?;
+        let mut view = MgfSpectrumView {
+            text,
+            file_path: self.file_path,
+            byte_offset: spectrum_start,
+            line_number: begin_line_number,
+            spectrum_index: self.num_spectra_read,
+            title: "",
+            precursor_mz: 0.0,
+            precursor_intensity: None,
+        };
+
+        // Only the parameters needed to filter the spectra are parsed upfront
+        let mut header = MgfSpectrumHeader::new(String::new(), 0.0, None, None);
+        for (line_offset, line_number, line) in view.lines() {
+            if let Some(value) = line.strip_prefix("TITLE=") {
+                view.title = value;
+            } else if let Some(value) = line.strip_prefix("PEPMASS=") {
+                parse_spectrum_param(&mut header, "PEPMASS", value)
+                    .map_err(|err| view.locate(err, line_offset, line_number))?;
+            }
+        }
+        view.precursor_mz = header.precursor_mz;
+        view.precursor_intensity = header.precursor_intensity;
+
+        self.num_spectra_read += 1;
+
+        Ok(Some(view))
+    }
+}
+
+/// Lightweight view of a spectrum, borrowing from the MGF content.
+/// Only the title and precursor m/z are parsed upfront, the other parameters and the peaks on demand.
+#[derive(Clone, Debug)]
+pub struct MgfSpectrumView<'a> {
+    /// Content of the spectrum, from `BEGIN IONS` to `END IONS` included
+    text: &'a str,
+    file_path: Option<&'a Path>,
+    /// Byte offset of the `BEGIN IONS` line
+    byte_offset: usize,
+    /// Line number of the `BEGIN IONS` line (1-based)
+    line_number: u64,
+    /// Index of the spectrum in the file
+    spectrum_index: usize,
+    title: &'a str,
+    precursor_mz: f64,
+    precursor_intensity: Option<f64>,
+}
+
+impl<'a> MgfSpectrumView<'a> {
+    /// Returns the content of the spectrum, from `BEGIN IONS` to `END IONS` included
+    ///
+    pub fn as_str(&self) -> &'a str {
+        self.text
+    }
+
+    /// Returns the byte offset of the `BEGIN IONS` line
+    ///
+    pub fn get_byte_offset(&self) -> u64 {
+        self.byte_offset as u64
+    }
+
+    /// Returns the index of the spectrum in the file
+    ///
+    pub fn get_spectrum_index(&self) -> usize {
+        self.spectrum_index
+    }
+
+    /// Returns the title, empty if none
+    ///
+    pub fn get_title(&self) -> &'a str {
+        self.title
+    }
+
+    /// Returns the precursor m/z
+    ///
+    pub fn get_precursor_mz(&self) -> f64 {
+        self.precursor_mz
+    }
+
+    /// Returns the precursor intensity
+    ///
+    pub fn get_precursor_intensity(&self) -> Option<f64> {
+        self.precursor_intensity
+    }
+
+    /// Returns the raw value of the given parameter, if present
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Parameter key, e.g. `RTINSECONDS`
+    ///
+    pub fn get_param(&self, key: &str) -> Option<&'a str> {
+        self.params().find(|(param_key, _)| *param_key == key).map(|(_, value)| value)
+    }
+
+    /// Returns the raw parameters (`KEY=value` lines), in file order
+    ///
+    pub fn params(&self) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+        self.lines()
+            .filter(|(_, _, line)| !line.starts_with(|c: char| c.is_ascii_digit()))
+            .filter_map(|(_, _, line)| line.split_once('='))
+    }
+
+    /// Parses the candidate precursor charges (CHARGE), without applying the global defaults
+    ///
+    pub fn get_precursor_charges(&self) -> Result<Vec<i8>> {
+        match self.lines().find(|(_, _, line)| line.starts_with("CHARGE=")) {
+            Some((line_offset, line_number, line)) => parse_charges(&line["CHARGE=".len()..])
+                .map_err(|err| self.locate(err, line_offset, line_number)),
+            None => Ok(Vec::new())
+        }
+    }
+
+    /// Returns an iterator parsing the m/z and intensity of the peaks on the fly.
+    /// Extra columns (fragment charge, annotation) are ignored, see `to_spectrum()`.
+    ///
+    pub fn peaks(&self) -> MgfPeakViews<'a> {
+        MgfPeakViews { view: self.clone(), lines: self.text.split_inclusive('\n').enumerate() }
+    }
+
+    /// Parses the whole spectrum into an owned one.
+    /// The global defaults are not applied, see `MgfGlobalHeader::apply_defaults()`.
+    ///
+    pub fn to_spectrum(&self) -> Result<MgfSpectrum> {
+        let mut reader = MgfReader::from_reader(self.text.as_bytes());
+        reader.set_location(self.file_path.map(Path::to_path_buf), self.byte_offset as u64, self.line_number - 1, self.spectrum_index);
+        reader.set_apply_global_defaults(false);
+
+        match reader.next()? {
+            Some(spectrum) => Ok(spectrum),
+            None => Err(self.locate(Error::truncated_record("empty spectrum view"), self.byte_offset, self.line_number))
+        }
+    }
+
+    /// Returns the trimmed, non-empty lines between `BEGIN IONS` and `END IONS`,
+    /// with their byte offset and line number
+    ///
+    fn lines(&self) -> impl Iterator<Item = (usize, u64, &'a str)> + 'a {
+        let text = self.text;
+        let byte_offset = self.byte_offset;
+        let line_number = self.line_number;
+        text.split_inclusive('\n')
+            .enumerate()
+            .map(move |(line_idx, line)| {
+                let line_offset = byte_offset + (line.as_ptr() as usize - text.as_ptr() as usize);
+                (line_offset, line_number + line_idx as u64, line.trim())
+            })
+            .filter(|(_, _, line)| !line.is_empty() && !line.starts_with("BEGIN IONS") && !line.starts_with("END IONS"))
+    }
+
+    /// Locates the given error at the given line of the spectrum
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - Error to locate
+    /// * `byte_offset` - Byte offset of the line
+    /// * `line_number` - Line number (1-based)
+    ///
+    fn locate(&self, error: Error, byte_offset: usize, line_number: u64) -> Error {
+        let context = ErrorContext {
+            file_path: self.file_path.map(Path::to_path_buf),
+            record_index: Some(self.spectrum_index),
+            record_id: Some(self.title.to_owned()).filter(|title| !title.is_empty()),
+            ..ErrorContext::new(byte_offset as u64, line_number)
+        };
+        error.at(context)
+    }
+}
+
+/// Iterator parsing the m/z and intensity of the peaks of a spectrum view
+pub struct MgfPeakViews<'a> {
+    view: MgfSpectrumView<'a>,
+    lines: std::iter::Enumerate<std::str::SplitInclusive<'a, char>>,
+}
+
+impl FallibleIterator for MgfPeakViews<'_> {
+    type Item = (f64, f32);
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        for (line_idx, line) in self.lines.by_ref() {
+            // Offset of the line start, before trimming, like in the other error contexts
+            let line_offset = self.view.byte_offset + (line.as_ptr() as usize - self.view.text.as_ptr() as usize);
+            let line = line.trim();
+            if !line.starts_with(|c: char| c.is_ascii_digit()) {
+                continue;
+            }
+
+            let line_number = self.view.line_number + line_idx as u64;
+
+            let mut split = line.split_ascii_whitespace();
+            let mz: f64 = parse_number(split.next().unwrap_or_default())
+                .map_err(|err| self.view.locate(err, line_offset, line_number))?;
+            let intensity: f32 = match split.next() {
+                Some(intensity) => parse_number(intensity),
+                None => Err(Error::missing_value("intensity value"))
+            }.map_err(|err| self.view.locate(err, line_offset, line_number))?;
+
+            return Ok(Some((mz, intensity)));
+        }
+
+        Ok(None)
+    }
+}
@@ -1,5 +1,13 @@
 /// Module for dealing with MGF files
 
+pub mod diagnostics;
+pub mod global_header;
+pub mod index;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod number_format;
 pub mod reader;
 pub mod spectrum;
 pub mod writer;
@@ -58,4 +66,693 @@ mod test {
             )
         }
     }
+
+    #[test]
+    /// Parses spectra from an in-memory buffer instead of a file.
+    fn test_reading_from_buffer() {
+        let mgf_content = fs::read(MGF_FILE_PATH_STR).unwrap();
+
+        let mgf_reader = MgfReader::from_reader(mgf_content.as_slice());
+
+        let entries: Vec<MgfSpectrum> = mgf_reader.into_fallible_iter().collect().unwrap();
+        assert_eq!(entries.len(), EXPECTED_NUM_SPECTRA);
+        assert_eq!(entries[0].header.get_title(), "824.836730957031_212.9232");
+    }
+
+    #[test]
+    /// Checks that parameters without dedicated field survive a read/write round trip.
+    fn test_additional_params_round_trip() {
+        let mgf_content = "BEGIN IONS
+TITLE=Velos005137.1000.1000.2
+PEPMASS=824.836730957031
+RTINSECONDS=212.9232
+CHARGE=2+
+RAWFILE=Velos005137.raw
+ION_MOBILITY=0.92
+USER01=some=value
+118.936477661133 429.616
+END IONS
+";
+        let spectra: Vec<MgfSpectrum> = MgfReader::from_reader(mgf_content.as_bytes())
+            .into_fallible_iter().collect().unwrap();
+        assert_eq!(spectra.len(), 1);
+
+        let header = &spectra[0].header;
+        assert_eq!(header.get_additional_params().len(), 3);
+        assert_eq!(header.get_additional_param("RAWFILE").unwrap(), "Velos005137.raw");
+        assert_eq!(header.get_additional_param("USER01").unwrap(), "some=value");
+
+        let mut mgf_writer = MgfWriter::from_writer(Vec::new());
+        mgf_writer.write_all(spectra.iter()).unwrap();
+
+        assert_eq!(String::from_utf8(mgf_writer.into_inner()).unwrap(), mgf_content);
+    }
+
+    #[test]
+    /// Checks the parsing of the standard Mascot parameters and their canonical writing order.
+    fn test_mascot_params_round_trip() {
+        let mgf_content = "BEGIN IONS
+TITLE=Velos005137.1000.1005.2
+PEPMASS=824.836730957031
+RTINSECONDS=212.9232-218.5
+CHARGE=2+
+SCANS=1000-1005
+INSTRUMENT=ESI-TRAP
+TOL=10
+TOLU=ppm
+IT_MODS=Oxidation (M),Phospho (ST)
+SEQ=n-AC[DHK]
+SEQ=c-[KR]
+COMP=2[H]0[M]
+TAG=1,[1453.6],DKTV,[1821.7]
+ETAG=1,[1453.6],DKTV,[1821.7]
+RAWFILE=Velos005137.raw
+118.936477661133 429.616
+END IONS
+";
+        let spectra: Vec<MgfSpectrum> = MgfReader::from_reader(mgf_content.as_bytes())
+            .into_fallible_iter().collect().unwrap();
+
+        let header = &spectra[0].header;
+        assert_eq!(header.get_retention_time(), Some(212.9232));
+        assert_eq!(header.get_retention_time_end(), Some(218.5));
+        assert_eq!(header.get_first_scan(), Some(1000));
+        assert_eq!(header.get_last_scan(), Some(1005));
+        assert_eq!(header.get_instrument().unwrap(), "ESI-TRAP");
+        assert_eq!(header.get_tolerance(), Some(10.0));
+        assert_eq!(header.get_tolerance_unit(), Some(MgfToleranceUnit::Ppm));
+        assert_eq!(header.get_variable_modifications(), &vec!["Oxidation (M)".to_string(), "Phospho (ST)".to_string()]);
+        assert_eq!(header.get_sequence_qualifiers().len(), 2);
+        assert_eq!(header.get_composition_qualifiers().len(), 1);
+        assert_eq!(header.get_sequence_tags().len(), 1);
+        assert_eq!(header.get_error_tolerant_sequence_tags().len(), 1);
+        assert_eq!(header.get_additional_params().len(), 1);
+
+        let mut mgf_writer = MgfWriter::from_writer(Vec::new());
+        mgf_writer.write_all(spectra.iter()).unwrap();
+
+        assert_eq!(String::from_utf8(mgf_writer.into_inner()).unwrap(), mgf_content);
+    }
+
+    #[test]
+    /// Checks that exponents are not taken as range separators and that non-numeric scans are kept as is.
+    fn test_scientific_and_non_numeric_params() {
+        let mgf_content = "BEGIN IONS
+TITLE=Velos005137.1000.1000.2
+PEPMASS=824.836730957031
+RTINSECONDS=1.5e-3-2.5E-3
+SCANS=F1:1000
+118.936477661133 429.616
+END IONS
+";
+        let spectra: Vec<MgfSpectrum> = MgfReader::from_reader(mgf_content.as_bytes())
+            .into_fallible_iter().collect().unwrap();
+
+        let header = &spectra[0].header;
+        assert_eq!(header.get_retention_time(), Some(1.5e-3));
+        assert_eq!(header.get_retention_time_end(), Some(2.5e-3));
+        assert_eq!(header.get_first_scan(), None);
+        assert_eq!(header.get_additional_param("SCANS").unwrap(), "F1:1000");
+
+        let index = MgfIndex::build(mgf_content.as_bytes()).unwrap();
+        assert_eq!(index.get_records()[0].first_scan, None);
+    }
+
+    #[test]
+    /// Reads the global parameters, applies the global charge and writes the global header back.
+    fn test_global_header_round_trip() {
+        let mgf_content = "COM=Velos005137 search
+SEARCH=MIS
+MASS=Monoisotopic
+CHARGE=2+
+TOL=10
+TOLU=ppm
+ITOL=0.5
+ITOLU=Da
+CLE=Trypsin
+TAXONOMY=Mus musculus
+
+BEGIN IONS
+TITLE=first
+PEPMASS=824.836730957031
+118.936477661133 429.616
+END IONS
+BEGIN IONS
+TITLE=second
+PEPMASS=567.810852050781
+CHARGE=3+
+115.411254882813 417.992
+END IONS
+";
+        let mut mgf_reader = MgfReader::from_reader(mgf_content.as_bytes());
+
+        let global_header = mgf_reader.read_global_header().unwrap().clone();
+        assert_eq!(global_header.get_comment().unwrap(), "Velos005137 search");
+        assert_eq!(global_header.get_mass_type(), Some(MgfMassType::Monoisotopic));
+        assert_eq!(global_header.get_precursor_charges(), &vec![2]);
+        assert_eq!(global_header.get_fragment_tolerance_unit(), Some(MgfToleranceUnit::Da));
+        assert_eq!(global_header.get_enzyme().unwrap(), "Trypsin");
+        assert_eq!(global_header.get_additional_params().len(), 1);
+
+        let spectra: Vec<MgfSpectrum> = mgf_reader.collect().unwrap();
+        assert_eq!(spectra.len(), 2);
+        assert_eq!(spectra[0].header.get_precursor_charge(), Some(2));
+        assert_eq!(spectra[1].header.get_precursor_charge(), Some(3));
+
+        let mut mgf_writer = MgfWriter::from_writer(Vec::new());
+        mgf_writer.write_global_header(&global_header).unwrap();
+        let written_mgf_content = String::from_utf8(mgf_writer.into_inner()).unwrap();
+        assert_eq!(written_mgf_content, mgf_content[..mgf_content.find("BEGIN IONS").unwrap()]);
+    }
+
+    #[test]
+    /// Checks the parsing and writing of the candidate charges variants.
+    fn test_multiple_charges() {
+        let mgf_content = "BEGIN IONS
+TITLE=and
+PEPMASS=824.836730957031
+CHARGE=2+ and 3+
+END IONS
+BEGIN IONS
+TITLE=comma
+PEPMASS=824.836730957031
+CHARGE=2+,3+,4+
+END IONS
+BEGIN IONS
+TITLE=leading sign
+PEPMASS=824.836730957031
+CHARGE=-2
+END IONS
+BEGIN IONS
+TITLE=trailing sign
+PEPMASS=824.836730957031
+CHARGE=1-
+END IONS
+";
+        let spectra: Vec<MgfSpectrum> = MgfReader::from_reader(mgf_content.as_bytes())
+            .into_fallible_iter().collect().unwrap();
+
+        assert_eq!(spectra[0].header.get_precursor_charges(), &vec![2, 3]);
+        assert_eq!(spectra[0].header.get_precursor_charge(), None);
+        assert_eq!(spectra[1].header.get_precursor_charges(), &vec![2, 3, 4]);
+        assert_eq!(spectra[2].header.get_precursor_charge(), Some(-2));
+        assert_eq!(spectra[3].header.get_precursor_charge(), Some(-1));
+
+        let mut mgf_writer = MgfWriter::from_writer(Vec::new());
+        mgf_writer.write_all(spectra.iter()).unwrap();
+        let written_mgf_content = String::from_utf8(mgf_writer.into_inner()).unwrap();
+
+        let charge_lines: Vec<&str> = written_mgf_content.lines().filter(|line| line.starts_with("CHARGE=")).collect();
+        assert_eq!(charge_lines, vec!["CHARGE=2+ and 3+", "CHARGE=2+, 3+ and 4+", "CHARGE=2-", "CHARGE=1-"]);
+    }
+
+    #[test]
+    /// Checks that headers serialized with the former single `precursor_charge` field can still be deserialized.
+    fn test_former_precursor_charge_deserialization() {
+        let header = MgfSpectrumHeader::new("first".to_owned(), 824.8367, None, Some(12.5));
+        let json = serde_json::to_string(&header).unwrap();
+        assert!(json.contains("\"precursor_charges\":[]"), "{}", json);
+
+        let single_charge_json = json.replace("\"precursor_charges\":[]", "\"precursor_charge\":2");
+        let read_header: MgfSpectrumHeader = serde_json::from_str(&single_charge_json).unwrap();
+        assert_eq!(read_header.get_precursor_charges(), &vec![2]);
+
+        let null_charge_json = json.replace("\"precursor_charges\":[]", "\"precursor_charge\":null");
+        let read_header: MgfSpectrumHeader = serde_json::from_str(&null_charge_json).unwrap();
+        assert!(read_header.get_precursor_charges().is_empty());
+
+        let missing_charge_json = json.replace("\"precursor_charges\":[],", "");
+        let read_header: MgfSpectrumHeader = serde_json::from_str(&missing_charge_json).unwrap();
+        assert_eq!(read_header, header);
+
+        let multiple_charges_json = json.replace("\"precursor_charges\":[]", "\"precursor_charges\":[2,3]");
+        let read_header: MgfSpectrumHeader = serde_json::from_str(&multiple_charges_json).unwrap();
+        assert_eq!(read_header.get_precursor_charges(), &vec![2, 3]);
+    }
+
+    #[test]
+    /// Checks that the precursor intensity given by PEPMASS is read and written back.
+    fn test_precursor_intensity_round_trip() {
+        let mgf_content = "BEGIN IONS
+TITLE=with intensity
+PEPMASS=824.83 12345.6
+CHARGE=2+
+END IONS
+BEGIN IONS
+TITLE=without intensity
+PEPMASS=567.810852050781
+CHARGE=2+
+END IONS
+";
+        let spectra: Vec<MgfSpectrum> = MgfReader::from_reader(mgf_content.as_bytes())
+            .into_fallible_iter().collect().unwrap();
+
+        assert_eq!(spectra[0].header.get_precursor_mz(), 824.83);
+        assert_eq!(spectra[0].header.get_precursor_intensity(), Some(12345.6));
+        assert_eq!(spectra[1].header.get_precursor_intensity(), None);
+
+        let mut mgf_writer = MgfWriter::from_writer(Vec::new());
+        mgf_writer.write_all(spectra.iter()).unwrap();
+
+        assert_eq!(String::from_utf8(mgf_writer.into_inner()).unwrap(), mgf_content);
+    }
+
+    #[test]
+    /// Checks that the per-peak charge and annotation columns are read and written back.
+    fn test_peak_charges_and_annotations_round_trip() {
+        let mgf_content = "BEGIN IONS
+TITLE=annotated
+PEPMASS=824.83
+CHARGE=2+
+118.936477661133 429.616
+122.26781463623 354.588 2+
+138.923324584961 369.316 1+ y1
+188.516448974609 367.936 b2-H2O
+201.122543334961 310.551 1-
+212.1 98.5 2
+END IONS
+";
+        let spectra: Vec<MgfSpectrum> = MgfReader::from_reader(mgf_content.as_bytes())
+            .into_fallible_iter().collect().unwrap();
+
+        assert_eq!(spectra[0].get_charge_list(), Some(&vec![0, 2, 1, 0, -1, 0]));
+        assert_eq!(
+            spectra[0].get_annotation_list(),
+            Some(&vec![String::new(), String::new(), "y1".to_string(), "b2-H2O".to_string(), String::new(), "2".to_string()])
+        );
+
+        let mut mgf_writer = MgfWriter::from_writer(Vec::new());
+        mgf_writer.write_all(spectra.iter()).unwrap();
+
+        assert_eq!(String::from_utf8(mgf_writer.into_inner()).unwrap(), mgf_content);
+    }
+
+    #[test]
+    /// Checks that parsing errors are located by line, byte offset and spectrum.
+    fn test_error_location() {
+        use crate::error::Error;
+
+        let mgf_content = "BEGIN IONS\nTITLE=first\nPEPMASS=500.5\n100.1 10.0\nEND IONS\n\
+            BEGIN IONS\nTITLE=second\nPEPMASS=600.5\n100.1 1O.0\nEND IONS\n";
+
+        let mut mgf_reader = MgfReader::from_reader(mgf_content.as_bytes());
+        assert!(mgf_reader.next().unwrap().is_some());
+
+        let error = mgf_reader.next().unwrap_err();
+        assert!(matches!(error, Error::InvalidNumber { ref value, .. } if value == "1O.0"));
+        assert_eq!(error.context().line_number, 9);
+        assert_eq!(error.context().byte_offset, mgf_content.find("100.1 1O.0").unwrap() as u64);
+        assert_eq!(error.context().record_index, Some(1));
+        assert_eq!(error.context().record_id.as_deref(), Some("second"));
+
+        let truncated_content = "BEGIN IONS\nTITLE=truncated\n100.1 10.0\n";
+        let error = MgfReader::from_reader(truncated_content.as_bytes()).next().unwrap_err();
+        assert!(matches!(error, Error::TruncatedRecord { .. }));
+        assert_eq!(error.context().line_number, 3);
+    }
+
+    #[test]
+    /// Checks that the lenient parse policies keep the good spectra and report the dropped content.
+    fn test_parse_policies() {
+        let mgf_content = "MASS=Heavy
+BEGIN IONS
+TITLE=bad peak
+PEPMASS=500.5
+100.1 10.0
+100.2 garbage
+100.3 30.0
+END IONS
+BEGIN IONS
+TITLE=good
+PEPMASS=600.5
+100.1 10.0
+END IONS
+BEGIN IONS
+TITLE=missing end
+PEPMASS=700.5
+100.1 10.0
+BEGIN IONS
+TITLE=truncated
+PEPMASS=800.5
+100.1 10.0
+";
+        let mut mgf_reader = MgfReader::from_reader(mgf_content.as_bytes());
+        assert!(mgf_reader.next().is_err());
+
+        let mut mgf_reader = MgfReader::from_reader(mgf_content.as_bytes());
+        mgf_reader.set_parse_policy(MgfParsePolicy::SkipBadSpectrum);
+        let spectra: Vec<MgfSpectrum> = (&mut mgf_reader).collect().unwrap();
+        assert_eq!(spectra.len(), 1);
+        assert_eq!(spectra[0].header.get_title(), "good");
+
+        let report = mgf_reader.get_parse_report();
+        assert_eq!(report.get_num_skipped_lines(), 1);
+        assert_eq!(report.get_num_skipped_spectra(), 3);
+        let spectrum_diagnostic = &report.get_diagnostics()[1];
+        assert_eq!(spectrum_diagnostic.get_skipped_item(), MgfSkippedItem::Spectrum);
+        assert_eq!(spectrum_diagnostic.get_context().line_number, 6);
+        assert_eq!(spectrum_diagnostic.get_context().record_id.as_deref(), Some("bad peak"));
+        assert_eq!(report.get_diagnostics()[2].get_context().record_index, Some(2));
+
+        let mut mgf_reader = MgfReader::from_reader(mgf_content.as_bytes());
+        mgf_reader.set_parse_policy(MgfParsePolicy::SkipBadLine);
+        let spectra: Vec<MgfSpectrum> = (&mut mgf_reader).collect().unwrap();
+        assert_eq!(spectra.len(), 2);
+        assert_eq!(spectra[0].data.mz_list, vec![100.1, 100.3]);
+
+        let report = mgf_reader.get_parse_report();
+        assert_eq!(report.get_num_skipped_lines(), 2);
+        assert_eq!(report.get_num_skipped_spectra(), 2);
+        assert_eq!(report.get_num_omitted_diagnostics(), 0);
+
+        // only the first diagnostics are kept, but every drop is counted
+        let mut mgf_reader = MgfReader::from_reader(mgf_content.as_bytes());
+        mgf_reader.set_parse_policy(MgfParsePolicy::SkipBadLine).set_max_diagnostics(1);
+        let spectra: Vec<MgfSpectrum> = (&mut mgf_reader).collect().unwrap();
+        assert_eq!(spectra.len(), 2);
+
+        let report = mgf_reader.get_parse_report();
+        assert_eq!(report.get_num_skipped_lines(), 2);
+        assert_eq!(report.get_num_skipped_spectra(), 2);
+        assert_eq!(report.get_diagnostics().len(), 1);
+        assert_eq!(report.get_num_omitted_diagnostics(), 3);
+    }
+
+    #[test]
+    /// Reads only the spectrum headers and compares them with the ones of fully read spectra.
+    fn test_header_only_reading() {
+        let mgf_file_path = Path::new(MGF_FILE_PATH_STR);
+        let entries: Vec<MgfSpectrum> = MgfReader::new(mgf_file_path, 1024).unwrap().into_fallible_iter().collect().unwrap();
+
+        let mut mgf_reader = MgfReader::new(mgf_file_path, 1024).unwrap();
+        let headers: Vec<MgfSpectrumHeader> = mgf_reader.headers().collect().unwrap();
+        assert_eq!(mgf_reader.get_num_spectra_read(), EXPECTED_NUM_SPECTRA);
+        assert_eq!(headers.len(), EXPECTED_NUM_SPECTRA);
+        for (header, entry) in zip(headers.iter(), entries.iter()) {
+            assert_eq!(header, &entry.header);
+        }
+
+        // peak lines are not parsed, malformed header lines are still reported
+        let mgf_content = "CHARGE=2+
+BEGIN IONS
+TITLE=first
+PEPMASS=824.836730957031
+118.936477661133 abc
+END IONS
+BEGIN IONS
+TITLE=second
+PEPMASS=abc
+END IONS
+BEGIN IONS
+TITLE=third
+PEPMASS=567.810852050781
+END IONS
+";
+        let mut mgf_reader = MgfReader::from_reader(mgf_content.as_bytes());
+        mgf_reader.set_parse_policy(MgfParsePolicy::SkipBadSpectrum);
+        let titles: Vec<String> = mgf_reader.headers().map(|header| Ok(header.get_title().to_string())).collect().unwrap();
+        assert_eq!(titles, vec!["first", "third"]);
+        assert_eq!(mgf_reader.get_parse_report().get_num_skipped_spectra(), 1);
+    }
+
+    #[test]
+    /// Writes spectra with fixed number formats and reads them back.
+    fn test_writing_number_formats() {
+        let mut spectrum = MgfSpectrum::new("first".to_owned(), 824.836730957031, Some(2), Some(1234.5678), vec![118.936477661133, 1000.0], vec![429.616, 12.0]);
+        spectrum.header.set_precursor_intensity(Some(75478.5625));
+
+        let mut options = MgfWriterOptions::new();
+        options.set_mz_format(NumberFormat::Decimals(4))
+            .set_intensity_format(NumberFormat::SignificantDigits(3))
+            .set_precursor_mz_format(NumberFormat::Decimals(5))
+            .set_precursor_intensity_format(NumberFormat::Scientific(2))
+            .set_retention_time_format(NumberFormat::Decimals(1));
+
+        let mut mgf_writer = MgfWriter::from_writer(Vec::new());
+        mgf_writer.set_options(options);
+        mgf_writer.write_spectrum(&spectrum).unwrap();
+        let mgf_content = String::from_utf8(mgf_writer.into_inner()).unwrap();
+        assert_eq!(mgf_content, "BEGIN IONS
+TITLE=first
+PEPMASS=824.83673 7.55e4
+RTINSECONDS=1234.6
+CHARGE=2+
+118.9365 430
+1000.0000 12.0
+END IONS
+");
+
+        let read_spectrum = MgfReader::from_reader(mgf_content.as_bytes()).next().unwrap().unwrap();
+        assert_eq!(read_spectrum.header.get_precursor_intensity(), Some(75500.0));
+        assert_eq!(read_spectrum.get_mz_list(), &vec![118.9365, 1000.0]);
+
+        // retention times below 1 have a negative exponent, which must not be taken for a range
+        let mut spectrum = MgfSpectrum::new("second".to_owned(), 824.836730957031, Some(2), Some(0.0015), vec![118.936477661133], vec![429.616]);
+        spectrum.header.retention_time_end = Some(0.0025);
+        let mut options = MgfWriterOptions::new();
+        options.set_retention_time_format(NumberFormat::Scientific(2));
+
+        let mut mgf_writer = MgfWriter::from_writer(Vec::new());
+        mgf_writer.set_options(options);
+        mgf_writer.write_spectrum(&spectrum).unwrap();
+        let mgf_content = String::from_utf8(mgf_writer.into_inner()).unwrap();
+        assert!(mgf_content.contains("\nRTINSECONDS=1.50e-3-2.50e-3\n"), "{}", mgf_content);
+
+        let read_spectrum = MgfReader::from_reader(mgf_content.as_bytes()).next().unwrap().unwrap();
+        assert_eq!(read_spectrum.header.get_retention_time(), Some(0.0015));
+        assert_eq!(read_spectrum.header.get_retention_time_end(), Some(0.0025));
+
+        // tolerances follow the precursor format, in the global header and in the spectra
+        let mut global_header = MgfGlobalHeader::new();
+        global_header.tolerance = Some(10.0);
+        global_header.fragment_tolerance = Some(0.02);
+        spectrum.header.tolerance = Some(1.0 / 3.0);
+        let mut options = MgfWriterOptions::new();
+        options.set_precursor_mz_format(NumberFormat::Decimals(3));
+
+        let mut mgf_writer = MgfWriter::from_writer(Vec::new());
+        mgf_writer.set_options(options);
+        mgf_writer.write_global_header(&global_header).unwrap();
+        mgf_writer.write_spectrum(&spectrum).unwrap();
+        let mgf_content = String::from_utf8(mgf_writer.into_inner()).unwrap();
+        assert!(mgf_content.starts_with("TOL=10.000\nITOL=0.020\n\n"), "{}", mgf_content);
+        assert!(mgf_content.contains("\nTOL=0.333\n"), "{}", mgf_content);
+    }
+
+    #[test]
+    /// Writes spectra with peak filters.
+    fn test_writing_peak_filters() {
+        let spectrum = MgfSpectrum::new(
+            "first".to_owned(), 824.836730957031, Some(2), None,
+            vec![100.0, 200.0, 300.0, 400.0, 500.0, 600.0],
+            vec![0.0, 50.0, 1000.0, 5.0, 200.0, 50.0]
+        );
+
+        let write_peaks = |options: &MgfWriterOptions| -> Vec<f64> {
+            let mut mgf_writer = MgfWriter::from_writer(Vec::new());
+            mgf_writer.set_options(*options);
+            mgf_writer.write_spectrum(&spectrum).unwrap();
+            let mgf_content = mgf_writer.into_inner();
+            MgfReader::from_reader(mgf_content.as_slice()).next().unwrap().unwrap().get_mz_list().clone()
+        };
+
+        let mut options = MgfWriterOptions::new();
+        assert_eq!(write_peaks(&options).len(), 6);
+        options.set_skip_zero_intensities(true);
+        assert_eq!(write_peaks(&options), vec![200.0, 300.0, 400.0, 500.0, 600.0]);
+        options.set_min_intensity(Some(10.0));
+        assert_eq!(write_peaks(&options), vec![200.0, 300.0, 500.0, 600.0]);
+        options.set_min_relative_intensity(Some(0.1));
+        assert_eq!(write_peaks(&options), vec![300.0, 500.0]);
+
+        let mut options = MgfWriterOptions::new();
+        options.set_max_num_peaks(Some(3));
+        assert_eq!(write_peaks(&options), vec![200.0, 300.0, 500.0]);
+        options.set_mz_range(Some((250.0, 600.0)));
+        assert_eq!(write_peaks(&options), vec![300.0, 500.0, 600.0]);
+
+        // the spectrum is left unchanged
+        assert_eq!(spectrum.get_mz_list().len(), 6);
+    }
+
+    #[test]
+    /// Indexes a MGF file and reads single spectra by ordinal, title and scan number.
+    fn test_indexed_reading() {
+        let mgf_content = "CHARGE=2+
+BEGIN IONS
+TITLE=first
+PEPMASS=824.836730957031
+SCANS=1000
+118.936477661133 429.616
+END IONS
+BEGIN IONS
+TITLE=second
+PEPMASS=567.810852050781
+SCANS=1001-1005
+115.411254882813 417.992
+END IONS
+";
+        let index = MgfIndex::build(mgf_content.as_bytes()).unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.get_records()[1].offset, mgf_content.rfind("BEGIN IONS").unwrap() as u64);
+
+        // sidecar round trip
+        let mut index_content = Vec::new();
+        index.write(&mut index_content).unwrap();
+        let read_index = MgfIndex::read(index_content.as_slice()).unwrap();
+        assert_eq!(read_index.get_records(), index.get_records());
+
+        let mut indexed_reader = IndexedMgfReader::from_reader(std::io::Cursor::new(mgf_content.as_bytes()), read_index).unwrap();
+        assert_eq!(indexed_reader.get_by_index(1).unwrap().unwrap().header.get_title(), "second");
+        assert_eq!(indexed_reader.get_by_scan(1000).unwrap().unwrap().header.get_title(), "first");
+        assert_eq!(indexed_reader.get_by_scan(1003).unwrap().unwrap().header.get_title(), "second");
+        let spectrum = indexed_reader.get_by_title("first").unwrap().unwrap();
+        assert_eq!(spectrum.header.get_precursor_charge(), Some(2));
+        assert!(indexed_reader.get_by_title("unknown").unwrap().is_none());
+        assert!(indexed_reader.get_by_index(2).unwrap().is_none());
+
+        // all the spectra of a file are identical to a streaming read
+        let mgf_file_path = Path::new(MGF_FILE_PATH_STR);
+        let entries: Vec<MgfSpectrum> = MgfReader::new(mgf_file_path, 1024).unwrap().into_fallible_iter().collect().unwrap();
+        let mut indexed_reader = IndexedMgfReader::new(mgf_file_path).unwrap();
+        assert_eq!(indexed_reader.get_index().len(), EXPECTED_NUM_SPECTRA);
+        for (spectrum_idx, entry) in entries.iter().enumerate().rev() {
+            assert_eq!(&indexed_reader.get_by_index(spectrum_idx).unwrap().unwrap(), entry);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    /// Reads a MGF file in parallel, with small chunks, and compares the spectra with a sequential read.
+    fn test_parallel_reading() {
+        use rayon::prelude::*;
+
+        let mgf_file_path = Path::new(MGF_FILE_PATH_STR);
+        let entries: Vec<MgfSpectrum> = MgfReader::new(mgf_file_path, 1024).unwrap().into_fallible_iter().collect().unwrap();
+
+        let mut parallel_reader = ParallelMgfReader::new(mgf_file_path, 1024).unwrap();
+        parallel_reader.set_chunk_size(4096);
+        let parallel_entries: Vec<MgfSpectrum> = parallel_reader.by_ref().collect().unwrap();
+        assert_eq!(parallel_entries, entries);
+
+        let mut parallel_reader = ParallelMgfReader::new(mgf_file_path, 1024).unwrap();
+        parallel_reader.set_chunk_size(4096);
+        let mut unordered_entries: Vec<MgfSpectrum> = parallel_reader.par_iter_unordered().unwrap()
+            .collect::<crate::error::Result<Vec<MgfSpectrum>>>().unwrap();
+        assert_eq!(unordered_entries.len(), EXPECTED_NUM_SPECTRA);
+        unordered_entries.sort_by(|a, b| a.header.get_title().cmp(b.header.get_title()));
+        let mut sorted_entries = entries.clone();
+        sorted_entries.sort_by(|a, b| a.header.get_title().cmp(b.header.get_title()));
+        assert_eq!(unordered_entries, sorted_entries);
+
+        // errors are located in the whole file and dropped content is reported
+        let mgf_content = "BEGIN IONS
+TITLE=first
+PEPMASS=824.836730957031
+118.936477661133 429.616
+END IONS
+BEGIN IONS
+TITLE=second
+PEPMASS=abc
+115.411254882813 417.992
+END IONS
+BEGIN IONS
+TITLE=third
+PEPMASS=567.810852050781
+115.411254882813 417.992
+END IONS
+";
+        let mut parallel_reader = ParallelMgfReader::from_reader(mgf_content.as_bytes());
+        parallel_reader.set_chunk_size(1);
+        let error = parallel_reader.by_ref().collect::<Vec<MgfSpectrum>>().err().unwrap();
+        assert_eq!(error.context().line_number, 8);
+        assert_eq!(error.context().record_index, Some(1));
+
+        let mut parallel_reader = ParallelMgfReader::from_reader(mgf_content.as_bytes());
+        parallel_reader.set_chunk_size(1).set_parse_policy(MgfParsePolicy::SkipBadSpectrum);
+        let titles: Vec<String> = parallel_reader.by_ref().map(|spectrum| Ok(spectrum.header.get_title().to_string())).collect().unwrap();
+        assert_eq!(titles, vec!["first", "third"]);
+        assert_eq!(parallel_reader.get_parse_report().get_num_skipped_spectra(), 1);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    /// Reads a memory-mapped MGF file as spectrum views and compares them with a streaming read.
+    fn test_mmap_reading() {
+        let mgf_file_path = Path::new(MGF_FILE_PATH_STR);
+        let entries: Vec<MgfSpectrum> = MgfReader::new(mgf_file_path, 1024).unwrap()
+            .set_apply_global_defaults(false).into_fallible_iter().collect().unwrap();
+
+        let mmap_reader = MmapMgfReader::new(mgf_file_path).unwrap();
+        let mut views = mmap_reader.views();
+        let mut num_views = 0;
+        while let Some(view) = views.next().unwrap() {
+            let entry = &entries[view.get_spectrum_index()];
+            assert_eq!(view.get_title(), entry.header.get_title());
+            assert_eq!(view.get_precursor_mz(), entry.header.get_precursor_mz());
+            assert_eq!(&view.get_precursor_charges().unwrap(), entry.header.get_precursor_charges());
+
+            let (mz_list, intensity_list): (Vec<f64>, Vec<f32>) = view.peaks().collect::<Vec<(f64, f32)>>().unwrap().into_iter().unzip();
+            assert_eq!(&mz_list, entry.get_mz_list());
+            assert_eq!(&intensity_list, entry.get_intensity_list());
+
+            assert_eq!(&view.to_spectrum().unwrap(), entry);
+            num_views += 1;
+        }
+        assert_eq!(num_views, EXPECTED_NUM_SPECTRA);
+
+        // errors are located in the whole content
+        let mgf_content = "COM=test
+BEGIN IONS
+TITLE=first
+PEPMASS=824.836730957031
+RTINSECONDS=12.5
+118.936477661133 429.616
+  118.9 abc
+END IONS
+BEGIN IONS
+TITLE=second
+";
+        let mut views = MgfSpectrumViews::new(mgf_content.as_bytes());
+        let view = views.next().unwrap().unwrap();
+        assert_eq!(views.get_global_header().comment.as_deref(), Some("test"));
+        assert_eq!(view.get_byte_offset(), 9);
+        assert_eq!(view.get_param("RTINSECONDS"), Some("12.5"));
+        let mut peaks = view.peaks();
+        assert_eq!(peaks.next().unwrap(), Some((118.936477661133, 429.616)));
+        let error = peaks.next().err().unwrap();
+        assert_eq!(error.context().line_number, 7);
+        assert_eq!(error.context().byte_offset, mgf_content.find("  118.9 abc").unwrap() as u64);
+        assert_eq!(error.context().record_id.as_deref(), Some("first"));
+        assert_eq!(view.to_spectrum().err().unwrap().context().line_number, 7);
+
+        let error = views.next().err().unwrap();
+        assert!(matches!(error, crate::error::Error::TruncatedRecord { .. }));
+        assert!(views.next().unwrap().is_none());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    /// Writes the spectra into a gzip compressed file and reads them back.
+    fn test_reading_and_writing_gzip() {
+        use crate::compression::CompressionFormat;
+
+        let gz_mgf_file_path = std::env::temp_dir().join("mzio_Velos005137.mgf.gz");
+
+        let entries: Vec<MgfSpectrum> = MgfReader::new(Path::new(MGF_FILE_PATH_STR), 1024).unwrap()
+            .into_fallible_iter().collect().unwrap();
+
+        let mut mgf_writer = MgfWriter::new_compressed(
+            &gz_mgf_file_path,
+            CompressionFormat::from_extension(&gz_mgf_file_path)
+        ).unwrap();
+        mgf_writer.write_all(entries.iter()).unwrap();
+        mgf_writer.finish().unwrap();
+
+        let gz_entries: Vec<MgfSpectrum> = MgfReader::new(&gz_mgf_file_path, 1024).unwrap()
+            .into_fallible_iter().collect().unwrap();
+        fs::remove_file(&gz_mgf_file_path).unwrap();
+
+        assert_eq!(entries, gz_entries);
+    }
 }
@@ -0,0 +1,211 @@
+// std imports
+use std::fmt;
+use std::fmt::Write as FmtWrite;
+use std::str::FromStr;
+
+// internal imports
+use crate::error::Error;
+
+/// Formatting of the floating point numbers written into MGF files
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NumberFormat {
+    /// Shortest representation reading back to the same value, e.g. `824.836730957031` (default).
+    /// Uses `ryu` if the matching feature is enabled.
+    #[default]
+    Shortest,
+    /// Fixed number of decimal places, e.g. `824.8367` for 4
+    Decimals(usize),
+    /// Fixed number of significant digits, e.g. `824.84` for 5
+    SignificantDigits(usize),
+    /// Scientific notation with a fixed number of decimal places, e.g. `8.2484e2` for 4
+    Scientific(usize),
+}
+
+impl NumberFormat {
+    /// Formats a double precision number
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Number to format
+    ///
+    pub fn format_f64(&self, value: f64) -> String {
+        let mut formatted_value = String::new();
+        self.write_to(&mut formatted_value, value);
+        formatted_value
+    }
+
+    /// Formats a single precision number
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Number to format
+    ///
+    pub fn format_f32(&self, value: f32) -> String {
+        let mut formatted_value = String::new();
+        self.write_to(&mut formatted_value, value);
+        formatted_value
+    }
+
+    /// Appends the formatted number to the given buffer
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - Output buffer
+    /// * `value` - Number to format
+    ///
+    pub(crate) fn write_to<T: FormattableFloat>(&self, buffer: &mut String, value: T) {
+        // Writing into a String can't fail
+        let _ = match *self {
+            Self::Shortest => {
+                value.write_shortest(buffer);
+                Ok(())
+            },
+            Self::Decimals(num_decimals) => write!(buffer, "{:.*}", num_decimals, value),
+            Self::SignificantDigits(num_digits) => {
+                write_significant_digits(buffer, value, num_digits);
+                Ok(())
+            },
+            Self::Scientific(num_decimals) => write!(buffer, "{:.*e}", num_decimals, value),
+        };
+    }
+}
+
+impl FromStr for NumberFormat {
+    type Err = Error;
+
+    /// Parses a number format, e.g. `shortest`, `decimals:4`, `significant:6` or `scientific:3`
+    ///
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let trimmed_value = value.trim().to_ascii_lowercase();
+        let (name, precision) = match trimmed_value.split_once(':') {
+            Some((name, precision)) => {
+                let precision: usize = precision.trim().parse().map_err(|_| Error::invalid_number(precision))?;
+                (name.trim().to_owned(), Some(precision))
+            },
+            None => (trimmed_value, None)
+        };
+
+        match (name.as_str(), precision) {
+            ("shortest", None) => Ok(Self::Shortest),
+            ("decimals", Some(precision)) => Ok(Self::Decimals(precision)),
+            ("significant", Some(precision)) if precision > 0 => Ok(Self::SignificantDigits(precision)),
+            ("scientific", Some(precision)) => Ok(Self::Scientific(precision)),
+            _ => Err(Error::malformed_header(format!("unknown number format: {}", value)))
+        }
+    }
+}
+
+impl fmt::Display for NumberFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Shortest => write!(f, "shortest"),
+            Self::Decimals(precision) => write!(f, "decimals:{}", precision),
+            Self::SignificantDigits(precision) => write!(f, "significant:{}", precision),
+            Self::Scientific(precision) => write!(f, "scientific:{}", precision),
+        }
+    }
+}
+
+/// Floating point numbers supported by `NumberFormat`
+pub(crate) trait FormattableFloat: Copy + fmt::Display + fmt::LowerExp + FromStr {
+    /// Appends the shortest representation reading back to the same value,
+    /// identical to the `Display` one
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - Output buffer
+    ///
+    fn write_shortest(self, buffer: &mut String);
+}
+
+macro_rules! impl_formattable_float {
+    ($float_type:ty) => {
+        impl FormattableFloat for $float_type {
+            #[cfg(feature = "ryu")]
+            fn write_shortest(self, buffer: &mut String) {
+                if !self.is_finite() {
+                    let _ = write!(buffer, "{}", self);
+                    return;
+                }
+
+                let mut ryu_buffer = ryu::Buffer::new();
+                let formatted_value = ryu_buffer.format_finite(self);
+                if formatted_value.contains('e') {
+                    // ryu switches to the scientific notation for large and small numbers, Display doesn't
+                    let _ = write!(buffer, "{}", self);
+                } else {
+                    buffer.push_str(formatted_value.strip_suffix(".0").unwrap_or(formatted_value));
+                }
+            }
+
+            #[cfg(not(feature = "ryu"))]
+            fn write_shortest(self, buffer: &mut String) {
+                let _ = write!(buffer, "{}", self);
+            }
+        }
+    };
+}
+
+impl_formattable_float!(f32);
+impl_formattable_float!(f64);
+
+/// Appends the number rounded to the given number of significant digits, without scientific notation
+///
+/// # Arguments
+///
+/// * `buffer` - Output buffer
+/// * `value` - Number to format
+/// * `num_digits` - Number of significant digits (at least 1)
+///
+fn write_significant_digits<T: FormattableFloat>(buffer: &mut String, value: T, num_digits: usize) {
+    let num_digits = num_digits.max(1);
+
+    // The scientific notation does the rounding, including the carry to the next power of ten (e.g. 9.99 -> 1.0e1)
+    let scientific_value = format!("{:.*e}", num_digits - 1, value);
+    let exponent: i64 = match scientific_value.rsplit_once('e').and_then(|(_, exponent)| exponent.parse().ok()) {
+        Some(exponent) => exponent,
+        // inf or NaN
+        None => {
+            buffer.push_str(&scientific_value);
+            return;
+        }
+    };
+
+    let num_decimals = num_digits as i64 - 1 - exponent;
+    let _ = if num_decimals >= 0 {
+        write!(buffer, "{:.*}", num_decimals as usize, value)
+    } else {
+        // Digits beyond the significant ones are zeroed
+        match scientific_value.parse::<T>() {
+            Ok(rounded_value) => write!(buffer, "{:.0}", rounded_value),
+            Err(_) => write!(buffer, "{}", scientific_value)
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    /// Formats numbers with each format and parses the formats back from their names.
+    fn test_number_formats() {
+        assert_eq!(NumberFormat::Shortest.format_f64(824.836730957031), "824.836730957031");
+        assert_eq!(NumberFormat::Shortest.format_f64(2.0), "2");
+        assert_eq!(NumberFormat::Shortest.format_f64(1e-7), "0.0000001");
+        assert_eq!(NumberFormat::Shortest.format_f32(429.616), "429.616");
+        assert_eq!(NumberFormat::Decimals(4).format_f64(824.836730957031), "824.8367");
+        assert_eq!(NumberFormat::Decimals(2).format_f32(429.616), "429.62");
+        assert_eq!(NumberFormat::SignificantDigits(5).format_f64(824.836730957031), "824.84");
+        assert_eq!(NumberFormat::SignificantDigits(2).format_f64(9.99), "10");
+        assert_eq!(NumberFormat::SignificantDigits(3).format_f32(123456.0), "123000");
+        assert_eq!(NumberFormat::SignificantDigits(3).format_f64(0.00123456), "0.00123");
+        assert_eq!(NumberFormat::Scientific(4).format_f64(824.836730957031), "8.2484e2");
+
+        for number_format in [NumberFormat::Shortest, NumberFormat::Decimals(3), NumberFormat::SignificantDigits(7), NumberFormat::Scientific(2)] {
+            assert_eq!(number_format.to_string().parse::<NumberFormat>().unwrap(), number_format);
+        }
+        assert!("significant:0".parse::<NumberFormat>().is_err());
+        assert!("decimals".parse::<NumberFormat>().is_err());
+    }
+}
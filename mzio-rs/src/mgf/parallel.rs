@@ -0,0 +1,434 @@
+//! Parallel MGF reading: the input is split at `BEGIN IONS` boundaries into chunks,
+//! which are parsed on the rayon thread pool.
+
+// std imports
+use std::collections::VecDeque;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// 3rd party imports
+use fallible_iterator::FallibleIterator;
+use rayon::prelude::*;
+
+// internal imports
+use crate::compression;
+use crate::error::{Error, ErrorContext, Result};
+use crate::mgf::diagnostics::{MgfParsePolicy, MgfParseReport, MgfSkippedItem};
+use crate::mgf::global_header::MgfGlobalHeader;
+use crate::mgf::reader::{MgfReader, parse_global_param};
+use crate::mgf::spectrum::MgfSpectrum;
+
+/// Default minimal size of a chunk in bytes
+const DEFAULT_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Consecutive spectra of the input, with the location of their first line
+struct MgfChunk {
+    content: Vec<u8>,
+    byte_offset: u64,
+    /// Number of lines before the chunk
+    line_number: u64,
+    /// Number of spectra before the chunk
+    num_spectra_before: usize,
+}
+
+/// Splits the input into chunks of whole spectra
+struct MgfChunker<R: BufRead> {
+    internal_reader: R,
+    chunk_size: usize,
+    /// Number of bytes read so far
+    byte_offset: u64,
+    /// Number of lines read so far
+    line_number: u64,
+    /// Number of spectra read so far
+    num_spectra: usize,
+    /// `BEGIN IONS` line starting the next chunk, already read
+    pending_line: Vec<u8>,
+    is_eof: bool,
+}
+
+impl<R: BufRead> MgfChunker<R> {
+    /// Reads the next line, appending it to the given buffer.
+    /// Returns the number of bytes read, 0 at EOF.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Line buffer
+    ///
+    fn read_line(&mut self, line: &mut Vec<u8>) -> Result<usize> {
+        let num_bytes = match self.internal_reader.read_until(b'\n', line) {
+            Ok(num_bytes) => num_bytes,
+            Err(err) => {
+                self.is_eof = true;
+                return Err(Error::io(err).at(ErrorContext::new(self.byte_offset, self.line_number + 1)));
+            }
+        };
+
+        self.byte_offset += num_bytes as u64;
+        if num_bytes > 0 {
+            self.line_number += 1;
+        }
+
+        Ok(num_bytes)
+    }
+
+    /// Reads the global parameters given before the first `BEGIN IONS`
+    ///
+    /// # Arguments
+    ///
+    /// * `global_header` - Global header to fill
+    /// * `parse_policy` - Behaviour when encountering malformed parameters
+    /// * `parse_report` - Report of the dropped lines
+    ///
+    fn read_global_header(&mut self, global_header: &mut MgfGlobalHeader, parse_policy: MgfParsePolicy,
+        parse_report: &mut MgfParseReport) -> Result<()> {
+        let mut line: Vec<u8> = Vec::new();
+        loop {
+            line.clear();
+            if self.read_line(&mut line)? == 0 {
+                self.is_eof = true;
+                return Ok(());
+            }
+
+            let trimmed_line = String::from_utf8_lossy(&line);
+            let trimmed_line = trimmed_line.trim();
+            if trimmed_line.starts_with("BEGIN IONS") {
+                self.pending_line = line;
+                return Ok(());
+            } else if let Some((key, value)) = trimmed_line.split_once('=') {
+                if let Err(err) = parse_global_param(global_header, key, value) {
+                    let error = err.at(ErrorContext::new(self.byte_offset - line.len() as u64, self.line_number));
+                    if parse_policy == MgfParsePolicy::Strict {
+                        return Err(error);
+                    }
+                    parse_report.add(MgfSkippedItem::Line, error);
+                }
+            }
+        }
+    }
+
+    /// Reads the next chunk: whole spectra, up to the first `BEGIN IONS` following the chunk size
+    ///
+    fn next_chunk(&mut self) -> Result<Option<MgfChunk>> {
+        if self.pending_line.is_empty() && self.is_eof {
+            return Ok(None);
+        }
+
+        let mut content = Vec::with_capacity(self.chunk_size + self.chunk_size / 8);
+        content.append(&mut self.pending_line);
+        let byte_offset = self.byte_offset - content.len() as u64;
+        let line_number = if content.is_empty() { self.line_number } else { self.line_number - 1 };
+        let num_spectra_before = self.num_spectra;
+        if !content.is_empty() {
+            self.num_spectra += 1;
+        }
+
+        while !self.is_eof {
+            let line_start = content.len();
+            if self.read_line(&mut content)? == 0 {
+                self.is_eof = true;
+                break;
+            }
+
+            if content[line_start..].trim_ascii_start().starts_with(b"BEGIN IONS") {
+                if line_start >= self.chunk_size {
+                    // The spectrum starts the next chunk
+                    self.pending_line = content.split_off(line_start);
+                    break;
+                }
+                self.num_spectra += 1;
+            }
+        }
+
+        Ok(Some(MgfChunk { content, byte_offset, line_number, num_spectra_before }))
+    }
+}
+
+impl<R: BufRead> Iterator for MgfChunker<R> {
+    type Item = Result<MgfChunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_chunk().transpose()
+    }
+}
+
+/// Settings shared by the chunk parsers
+struct MgfChunkParser<'a> {
+    file_path: Option<&'a Path>,
+    global_header: &'a MgfGlobalHeader,
+    apply_global_defaults: bool,
+    parse_policy: MgfParsePolicy,
+    max_diagnostics: usize,
+}
+
+impl MgfChunkParser<'_> {
+    /// Parses the spectra of a chunk, including the ones after an error, like `MgfReader` does when iterated further.
+    /// Returns the spectra and errors in reading order, with the report of the dropped content.
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk` - Chunk to parse
+    ///
+    fn parse(&self, chunk: MgfChunk) -> (Vec<Result<MgfSpectrum>>, MgfParseReport) {
+        let mut chunk_reader = MgfReader::from_reader(chunk.content.as_slice());
+        chunk_reader.set_location(self.file_path.map(Path::to_path_buf), chunk.byte_offset, chunk.line_number, chunk.num_spectra_before);
+        chunk_reader.set_apply_global_defaults(false);
+        chunk_reader.set_parse_policy(self.parse_policy);
+        chunk_reader.set_max_diagnostics(self.max_diagnostics);
+
+        let mut spectra = Vec::new();
+        loop {
+            match chunk_reader.next() {
+                Ok(Some(mut spectrum)) => {
+                    if self.apply_global_defaults {
+                        self.global_header.apply_defaults(&mut spectrum.header);
+                    }
+                    spectra.push(Ok(spectrum));
+                },
+                Ok(None) => break,
+                Err(error) => {
+                    // The chunk content is in memory, an I/O error cannot be recovered from
+                    let is_io_error = matches!(error, Error::Io { .. });
+                    spectra.push(Err(error));
+                    if is_io_error {
+                        break;
+                    }
+                }
+            }
+        }
+
+        (spectra, chunk_reader.take_parse_report())
+    }
+}
+
+/// Reader for MGF parsing the spectra on the rayon thread pool.
+/// Iterating it with `FallibleIterator` yields the spectra in file order,
+/// `par_iter_unordered()` yields them as soon as they are parsed.
+pub struct ParallelMgfReader<R: BufRead> {
+    chunker: MgfChunker<R>,
+    /// Path of the read file, used to locate errors
+    file_path: Option<PathBuf>,
+    global_header: MgfGlobalHeader,
+    is_global_header_read: bool,
+    apply_global_defaults: bool,
+    parse_policy: MgfParsePolicy,
+    parse_report: Mutex<MgfParseReport>,
+    /// Number of chunks parsed at once when preserving the spectrum order
+    num_chunks_per_batch: usize,
+    /// Parsed spectra (and errors) not yielded yet, in file order
+    parsed_spectra: VecDeque<Result<MgfSpectrum>>,
+}
+
+impl ParallelMgfReader<Box<dyn BufRead + Send>> {
+    /// Creates a new Reader.
+    /// Compressed files (gzip, bzip2, zstd, xz) are detected by their magic bytes
+    /// and decompressed on the fly, if the matching feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `mgf_file_path` - Path to MGF file
+    /// * `buffer_size` - Buffer size to use when loading bytes from disk.
+    ///
+    pub fn new(mgf_file_path: &Path, buffer_size: usize) -> anyhow::Result<Self> {
+        let mut reader = Self::from_reader(compression::open_file(mgf_file_path, buffer_size)?);
+        reader.file_path = Some(mgf_file_path.to_path_buf());
+        Ok(reader)
+    }
+}
+
+impl<R: BufRead> ParallelMgfReader<R> {
+    /// Creates a new Reader from any buffered source
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Buffered source of MGF content
+    ///
+    pub fn from_reader(reader: R) -> Self {
+        Self {
+            chunker: MgfChunker {
+                internal_reader: reader,
+                chunk_size: DEFAULT_CHUNK_SIZE,
+                byte_offset: 0,
+                line_number: 0,
+                num_spectra: 0,
+                pending_line: Vec::new(),
+                is_eof: false,
+            },
+            file_path: None,
+            global_header: MgfGlobalHeader::new(),
+            is_global_header_read: false,
+            apply_global_defaults: true,
+            parse_policy: MgfParsePolicy::Strict,
+            parse_report: Mutex::new(MgfParseReport::new()),
+            num_chunks_per_batch: rayon::current_num_threads() * 2,
+            parsed_spectra: VecDeque::new(),
+        }
+    }
+
+    /// Sets the minimal size of the chunks parsed by a single thread (default: 4 MiB).
+    /// Chunks always end at a spectrum boundary.
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk_size` - Minimal chunk size in bytes
+    ///
+    pub fn set_chunk_size(&mut self, chunk_size: usize) -> &mut Self {
+        self.chunker.chunk_size = chunk_size;
+        self
+    }
+
+    /// Sets whether the global defaults (e.g. CHARGE) are applied to spectra lacking their own value (default: true)
+    ///
+    /// # Arguments
+    ///
+    /// * `apply_global_defaults` - Whether to apply the global defaults or not
+    ///
+    pub fn set_apply_global_defaults(&mut self, apply_global_defaults: bool) -> &mut Self {
+        self.apply_global_defaults = apply_global_defaults;
+        self
+    }
+
+    /// Sets the behaviour when encountering malformed content (default: strict)
+    ///
+    /// # Arguments
+    ///
+    /// * `parse_policy` - Parse policy
+    ///
+    pub fn set_parse_policy(&mut self, parse_policy: MgfParsePolicy) -> &mut Self {
+        self.parse_policy = parse_policy;
+        self
+    }
+
+    /// Sets the maximum number of diagnostics kept by the parse report (default: `DEFAULT_MAX_DIAGNOSTICS`).
+    /// Dropped content is still counted beyond this limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_diagnostics` - Maximum number of kept diagnostics
+    ///
+    pub fn set_max_diagnostics(&mut self, max_diagnostics: usize) -> &mut Self {
+        self.parse_report.get_mut().unwrap_or_else(|err| err.into_inner()).set_max_diagnostics(max_diagnostics);
+        self
+    }
+
+    /// Reads the global parameters given before the first `BEGIN IONS`, if not read yet.
+    /// This is done automatically when reading the first spectrum.
+    ///
+    pub fn read_global_header(&mut self) -> Result<&MgfGlobalHeader> {
+        if !self.is_global_header_read {
+            self.is_global_header_read = true;
+            let parse_report = self.parse_report.get_mut().unwrap_or_else(|err| err.into_inner());
+            self.chunker.read_global_header(&mut self.global_header, self.parse_policy, parse_report)
+                .map_err(|err| self.locate(err))?;
+        }
+
+        Ok(&self.global_header)
+    }
+
+    /// Returns the global parameters.
+    /// Empty until read_global_header() is called or the first spectrum is read.
+    ///
+    pub fn get_global_header(&self) -> &MgfGlobalHeader {
+        &self.global_header
+    }
+
+    /// Returns the report of the content dropped so far by a lenient parse policy
+    ///
+    pub fn get_parse_report(&mut self) -> &MgfParseReport {
+        self.parse_report.get_mut().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Sets the file path of the given error, if known
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - Error to locate
+    ///
+    fn locate(&self, error: Error) -> Error {
+        match &self.file_path {
+            Some(file_path) => error.in_file(file_path),
+            None => error
+        }
+    }
+
+    /// Splits the reader into the chunker, the settings of the chunk parsers and the parse report.
+    /// The fields are borrowed separately (not the whole reader through `&self`)
+    /// so that the chunks can be read from the chunker while the parsers hold the settings.
+    ///
+    fn split_for_parsing(&mut self) -> (&mut MgfChunker<R>, MgfChunkParser<'_>, &Mutex<MgfParseReport>) {
+        let max_diagnostics = self.parse_report.get_mut().unwrap_or_else(|err| err.into_inner()).get_max_diagnostics();
+        let chunk_parser = MgfChunkParser {
+            file_path: self.file_path.as_deref(),
+            global_header: &self.global_header,
+            apply_global_defaults: self.apply_global_defaults,
+            parse_policy: self.parse_policy,
+            max_diagnostics,
+        };
+        (&mut self.chunker, chunk_parser, &self.parse_report)
+    }
+}
+
+impl<R: BufRead + Send> ParallelMgfReader<R> {
+    /// Returns a parallel iterator yielding the spectra as soon as they are parsed, in no particular order.
+    /// Chunks are read sequentially and parsed on the rayon thread pool.
+    ///
+    pub fn par_iter_unordered(&mut self) -> Result<impl ParallelIterator<Item = Result<MgfSpectrum>> + '_> {
+        self.read_global_header()?;
+
+        // Spectra already parsed by the ordered iteration come first
+        let parsed_spectra: Vec<Result<MgfSpectrum>> = self.parsed_spectra.drain(..).collect();
+
+        let (chunker, chunk_parser, parse_report) = self.split_for_parsing();
+        let file_path = chunk_parser.file_path;
+
+        let unordered_spectra = chunker.par_bridge()
+            .flat_map_iter(move |chunk| {
+                let chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(error) => return vec![Err(match file_path {
+                        Some(path) => error.in_file(path),
+                        None => error
+                    })]
+                };
+                let (spectra, chunk_report) = chunk_parser.parse(chunk);
+                parse_report.lock().unwrap_or_else(|err| err.into_inner()).merge(chunk_report);
+                spectra
+            });
+
+        Ok(parsed_spectra.into_par_iter().chain(unordered_spectra))
+    }
+}
+
+impl<R: BufRead> FallibleIterator for ParallelMgfReader<R> {
+    type Item = MgfSpectrum;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        self.read_global_header()?;
+
+        if self.parsed_spectra.is_empty() {
+            // Read a batch of chunks and parse them in parallel
+            let mut chunks: Vec<MgfChunk> = Vec::with_capacity(self.num_chunks_per_batch);
+            while chunks.len() < self.num_chunks_per_batch {
+                match self.chunker.next_chunk() {
+                    Ok(Some(chunk)) => chunks.push(chunk),
+                    Ok(None) => break,
+                    Err(error) => return Err(self.locate(error))
+                }
+            }
+
+            let (_, chunk_parser, _) = self.split_for_parsing();
+            let parsed_chunks: Vec<(Vec<Result<MgfSpectrum>>, MgfParseReport)> = chunks.into_par_iter()
+                .map(|chunk| chunk_parser.parse(chunk))
+                .collect();
+
+            let parse_report = self.parse_report.get_mut().unwrap_or_else(|err| err.into_inner());
+            for (spectra, chunk_report) in parsed_chunks {
+                self.parsed_spectra.extend(spectra);
+                parse_report.merge(chunk_report);
+            }
+        }
+
+        self.parsed_spectra.pop_front().transpose()
+    }
+}
@@ -1,5 +1,13 @@
 pub use fallible_iterator::IntoFallibleIterator;
 pub use fallible_iterator::FallibleIterator;
-pub use crate::mgf::spectrum::MgfSpectrum;
-pub use crate::mgf::reader::MgfReader;
-pub use crate::mgf::writer::MgfWriter;
\ No newline at end of file
+pub use crate::mgf::spectrum::{MgfSpectrum, MgfSpectrumHeader, MgfToleranceUnit};
+pub use crate::mgf::diagnostics::{MgfParseDiagnostic, MgfParsePolicy, MgfParseReport, MgfSkippedItem};
+pub use crate::mgf::global_header::{MgfGlobalHeader, MgfMassType};
+pub use crate::mgf::index::{IndexedMgfReader, MgfIndex, MgfIndexRecord};
+#[cfg(feature = "mmap")]
+pub use crate::mgf::mmap::{MgfPeakViews, MgfSpectrumView, MgfSpectrumViews, MmapMgfReader};
+#[cfg(feature = "rayon")]
+pub use crate::mgf::parallel::ParallelMgfReader;
+pub use crate::mgf::reader::{MgfReader, MgfSpectrumHeaders};
+pub use crate::mgf::number_format::NumberFormat;
+pub use crate::mgf::writer::{MgfWriter, MgfWriterOptions};
\ No newline at end of file
@@ -1,126 +1,656 @@
 
 // std imports
-use std::fs::File;
-use std::io::BufReader;
 use std::io::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // 3rd party imports
-use anyhow::{Result, bail};
 use fallible_iterator::FallibleIterator;
 
 // internal imports
-use crate::mgf::spectrum::MgfSpectrum;
+use crate::compression;
+use crate::error::{Error, ErrorContext, Result};
+use crate::mgf::diagnostics::{MgfParsePolicy, MgfParseReport, MgfSkippedItem};
+use crate::mgf::global_header::MgfGlobalHeader;
+use crate::mgf::spectrum::{MgfSpectrum, MgfSpectrumHeader};
+use mzcore::ms::spectrum::SpectrumData;
 
 /// Reader for MGF
-pub struct MgfReader {
-    internal_reader: BufReader<File>
+/// Works on any buffered source (file, stdin, in-memory buffer, archive entry...).
+pub struct MgfReader<R: BufRead> {
+    internal_reader: R,
+    /// Path of the read file, used to locate errors
+    file_path: Option<PathBuf>,
+    /// Number of bytes read so far
+    byte_offset: u64,
+    /// Byte offset of the start of the current line
+    line_byte_offset: u64,
+    /// Number of the current line (1-based)
+    line_number: u64,
+    /// Number of spectra read so far
+    num_spectra_read: usize,
+    global_header: MgfGlobalHeader,
+    is_global_header_read: bool,
+    /// True if the `BEGIN IONS` line of the next spectrum was already consumed while reading the global header
+    is_at_spectrum_start: bool,
+    apply_global_defaults: bool,
+    parse_policy: MgfParsePolicy,
+    parse_report: MgfParseReport
 }
 
-impl MgfReader {
-    /// Creates a new Reader
+impl MgfReader<Box<dyn BufRead + Send>> {
+    /// Creates a new Reader.
+    /// Compressed files (gzip, bzip2, zstd, xz) are detected by their magic bytes
+    /// and decompressed on the fly, if the matching feature is enabled.
     /// 
     /// # Arguments
     ///
     /// * `mgf_file_path` - Path to MGF file
+    /// * `buffer_size` - Buffer size to use when loading bytes from disk.
     /// 
-    pub fn new(mgf_file_path: & Path, buffer_size: usize) -> Result<Self> {
-        let mgf_file: File = File::open(mgf_file_path)?;
-        Ok(Self {
-            internal_reader: BufReader::with_capacity(buffer_size, mgf_file),
-        })
+    pub fn new(mgf_file_path: & Path, buffer_size: usize) -> anyhow::Result<Self> {
+        let mut reader = Self::from_reader(compression::open_file(mgf_file_path, buffer_size)?);
+        reader.file_path = Some(mgf_file_path.to_path_buf());
+        Ok(reader)
     }
 }
 
-impl FallibleIterator for MgfReader {
-    type Item = MgfSpectrum;
-    type Error = anyhow::Error;
+impl<R: BufRead> MgfReader<R> {
+    /// Creates a new Reader from any buffered source
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Buffered source of MGF content, e.g. `std::io::stdin().lock()` or `&[u8]`
+    ///
+    pub fn from_reader(reader: R) -> Self {
+        Self {
+            internal_reader: reader,
+            file_path: None,
+            byte_offset: 0,
+            line_byte_offset: 0,
+            line_number: 0,
+            num_spectra_read: 0,
+            global_header: MgfGlobalHeader::new(),
+            is_global_header_read: false,
+            is_at_spectrum_start: false,
+            apply_global_defaults: true,
+            parse_policy: MgfParsePolicy::Strict,
+            parse_report: MgfParseReport::new()
+        }
+    }
 
-    fn next(&mut self) -> Result<Option<Self::Item>> {
+    /// Sets whether the global defaults (e.g. CHARGE) are applied to spectra lacking their own value (default: true)
+    ///
+    /// # Arguments
+    ///
+    /// * `apply_global_defaults` - Whether to apply the global defaults or not
+    ///
+    pub fn set_apply_global_defaults(&mut self, apply_global_defaults: bool) -> &mut Self {
+        self.apply_global_defaults = apply_global_defaults;
+        self
+    }
+
+    /// Sets the behaviour when encountering malformed content (default: strict).
+    /// Content dropped by lenient policies is recorded in the parse report.
+    ///
+    /// # Arguments
+    ///
+    /// * `parse_policy` - Parse policy
+    ///
+    pub fn set_parse_policy(&mut self, parse_policy: MgfParsePolicy) -> &mut Self {
+        self.parse_policy = parse_policy;
+        self
+    }
+
+    /// Returns the parse policy
+    ///
+    pub fn get_parse_policy(&self) -> MgfParsePolicy {
+        self.parse_policy
+    }
+
+    /// Returns the report of the content dropped so far by a lenient parse policy
+    ///
+    pub fn get_parse_report(&self) -> &MgfParseReport {
+        &self.parse_report
+    }
+
+    /// Sets the maximum number of diagnostics kept by the parse report (default: `DEFAULT_MAX_DIAGNOSTICS`).
+    /// Dropped content is still counted beyond this limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_diagnostics` - Maximum number of kept diagnostics
+    ///
+    pub fn set_max_diagnostics(&mut self, max_diagnostics: usize) -> &mut Self {
+        self.parse_report.set_max_diagnostics(max_diagnostics);
+        self
+    }
+
+    /// Reads the global parameters given before the first `BEGIN IONS`, if not read yet.
+    /// This is done automatically when reading the first spectrum.
+    ///
+    pub fn read_global_header(&mut self) -> Result<&MgfGlobalHeader> {
+        if !self.is_global_header_read {
+            let mut line = String::new();
+            while self.read_line(&mut line)? > 0 {
+                let trimmed_line = line.trim();
+                if trimmed_line.starts_with("BEGIN IONS") {
+                    self.is_at_spectrum_start = true;
+                    break;
+                } else if let Some((key, value)) = trimmed_line.split_once('=') {
+                    if let Err(err) = parse_global_param(&mut self.global_header, key, value) {
+                        let error = self.locate(err, None);
+                        if self.parse_policy == MgfParsePolicy::Strict {
+                            return Err(error);
+                        }
+                        self.parse_report.add(MgfSkippedItem::Line, error);
+                    }
+                }
+            }
+            self.is_global_header_read = true;
+        }
+
+        Ok(&self.global_header)
+    }
+
+    /// Returns the global parameters.
+    /// Empty until read_global_header() is called or the first spectrum is read.
+    ///
+    pub fn get_global_header(&self) -> &MgfGlobalHeader {
+        &self.global_header
+    }
 
-        let mut in_spectrum: bool = false;
-        let mut title = String::new();
-        let mut precursor_mz: f64 = 0.0;
-        let mut precursor_charge: Option<i8> = None;
-        let mut retention_time: Option<f64> = None;
+    /// Returns the number of spectra read so far
+    ///
+    pub fn get_num_spectra_read(&self) -> usize {
+        self.num_spectra_read
+    }
+
+    /// Returns a reference to the underlying reader
+    ///
+    pub fn get_ref(&self) -> &R {
+        &self.internal_reader
+    }
+
+    /// Consumes the MGF reader and returns the underlying reader
+    ///
+    pub fn into_inner(self) -> R {
+        self.internal_reader
+    }
+
+    /// Sets the location of the start of the underlying reader within the file, used to locate errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - Path of the read file, if any
+    /// * `byte_offset` - Byte offset of the current position of the underlying reader
+    /// * `line_number` - Number of lines located before the current position, 0 if unknown
+    /// * `num_spectra_before` - Number of spectra located before the current position
+    ///
+    pub(crate) fn set_location(&mut self, file_path: Option<PathBuf>, byte_offset: u64, line_number: u64, num_spectra_before: usize) {
+        self.file_path = file_path;
+        self.byte_offset = byte_offset;
+        self.line_byte_offset = byte_offset;
+        self.line_number = line_number;
+        self.num_spectra_read = num_spectra_before;
+    }
+
+    /// Takes the report of the content dropped so far, leaving an empty one with the same limit
+    ///
+    #[cfg(feature = "rayon")]
+    pub(crate) fn take_parse_report(&mut self) -> MgfParseReport {
+        let max_diagnostics = self.parse_report.get_max_diagnostics();
+        std::mem::replace(&mut self.parse_report, MgfParseReport::with_max_diagnostics(max_diagnostics))
+    }
+
+    /// Reads the next line into the given buffer (cleared beforehand) and keeps track of the position.
+    /// Returns the number of bytes read, 0 at EOF.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Line buffer
+    ///
+    fn read_line(&mut self, line: &mut String) -> Result<usize> {
+        line.clear();
+        self.line_byte_offset = self.byte_offset;
+
+        let num_bytes = match self.internal_reader.read_line(line) {
+            Ok(num_bytes) => num_bytes,
+            Err(err) => return Err(self.locate(Error::io(err), None))
+        };
+
+        if num_bytes > 0 {
+            self.byte_offset += num_bytes as u64;
+            self.line_number += 1;
+        }
+
+        Ok(num_bytes)
+    }
+
+    /// Locates the given error at the current line.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - Error to locate
+    /// * `spectrum_header` - Header of the spectrum being read, if any
+    ///
+    fn locate(&self, error: Error, spectrum_header: Option<&MgfSpectrumHeader>) -> Error {
+        let context = ErrorContext {
+            file_path: self.file_path.clone(),
+            record_index: spectrum_header.map(|_| self.num_spectra_read + self.parse_report.num_skipped_spectra),
+            record_id: spectrum_header
+                .map(|header| header.get_title())
+                .filter(|title| !title.is_empty())
+                .cloned(),
+            ..ErrorContext::new(self.line_byte_offset, self.line_number)
+        };
+        error.at(context)
+    }
+}
+
+impl<R: BufRead> MgfReader<R> {
+    /// Reads the next spectrum, dropping the malformed lines if the parse policy allows it
+    ///
+    /// # Arguments
+    ///
+    /// * `parse_peaks` - Whether to parse the peak lines or to skip them, leaving the peak lists empty
+    ///
+    fn read_spectrum(&mut self, parse_peaks: bool) -> Result<Option<MgfSpectrum>> {
+        let mut in_spectrum: bool = std::mem::take(&mut self.is_at_spectrum_start);
+        let mut header = MgfSpectrumHeader::new(String::new(), 0.0, None, None);
         let mut mz_list: Vec<f64> = Vec::new();
         let mut intensity_list: Vec<f32> = Vec::new();
+        let mut charge_list: Option<Vec<i8>> = None;
+        let mut annotation_list: Option<Vec<String>> = None;
 
+        let mut line_buffer = String::new();
         loop {
-            let mut line = String::new();
-            if let Ok(num_bytes) = self.internal_reader.read_line(&mut line) {
-                if num_bytes == 0 {
-                    if in_spectrum {
-                        bail!("reach EOF before END IONS (incomplete spectrum)".to_string());
-                    }
-                    return Ok(None);
+            if self.read_line(&mut line_buffer)? == 0 {
+                if in_spectrum {
+                    let error = Error::truncated_record("reach EOF before END IONS (incomplete spectrum)");
+                    return Err(self.locate(error, Some(&header)));
                 }
+                return Ok(None);
+            }
+
+            let line = line_buffer.trim();
+            if line.is_empty() {
+                continue
+            }
 
-                line = line.as_mut_str().trim().to_string();
-                if line.is_empty() {
-                    continue
+            let first_char = line.chars().next().unwrap();
+
+            if first_char == 'B' && line.starts_with("BEGIN IONS") {
+                if in_spectrum {
+                    // The new spectrum will be read by the next call
+                    self.is_at_spectrum_start = true;
+                    let error = Error::truncated_record("BEGIN IONS before END IONS (incomplete spectrum)");
+                    return Err(self.locate(error, Some(&header)));
                 }
+                in_spectrum = true;
+            } else if first_char == 'E' && line.starts_with("END IONS") {
+
+                //in_spectrum = false;
 
-                let first_char = line.chars().next().unwrap();
+                if self.apply_global_defaults {
+                    self.global_header.apply_defaults(&mut header);
+                }
 
-                if first_char == 'B' && line.starts_with("BEGIN IONS") {
-                    in_spectrum = true;
-                } else if first_char == 'E' && line.starts_with("END IONS") {
+                // Peaks read before the first extra column have no charge/annotation
+                let num_peaks = mz_list.len();
+                if let Some(charge_list) = charge_list.as_mut() {
+                    charge_list.resize(num_peaks, 0);
+                }
+                if let Some(annotation_list) = annotation_list.as_mut() {
+                    annotation_list.resize(num_peaks, String::new());
+                }
 
-                    //in_spectrum = false;
+                self.num_spectra_read += 1;
 
-                    return Ok(Some(MgfSpectrum::new(
-                        title,
-                        precursor_mz,
-                        precursor_charge,
-                        retention_time,
+                return Ok(Some(MgfSpectrum {
+                    header,
+                    data: SpectrumData {
                         mz_list,
-                        intensity_list
-                    )));
-                } else if in_spectrum {
-                    // if line contains a peak
-                    if first_char.is_numeric() {
-                        let mut split = line.split_ascii_whitespace();
-
-                        match split.next() {
-                            Some(mz) => mz_list.push(fast_float::parse(mz)?),
-                            None => bail!("m/z value is missing")
-                        };
-
-                        match split.next() {
-                            Some(intens) => intensity_list.push(fast_float::parse(intens)?),
-                            None => bail!("intensity value is missing")
-                        };
-
-                    } else {
-                        if line.starts_with("TITLE=") {
-                            title = line[6..].to_owned();
-                        } else if line.starts_with("PEPMASS=") {
-                            let prec_mz_str_opt = &line[8..].split_ascii_whitespace().next();
-                            precursor_mz = fast_float::parse(prec_mz_str_opt.unwrap_or_else(|| "0.0"))?;
-                        } else if line.starts_with("RTINSECONDS=") {
-                            retention_time = Some(fast_float::parse(&line[12..])?);
-                        } else if line.starts_with("CHARGE=") {
-                            // Locate the value and its sign within the string
-                            let value = &line[7..];
-                            let mut chars = value.chars();
-                            let charge_sign_idx = chars.position(|c| !c.is_numeric()).unwrap_or(value.len());
-
-                            // Parse the charge value
-                            let charge_str = &value[0..charge_sign_idx];
-                            let mut charge = charge_str.parse()?;
-
-                            // Parse the charge sign and update the value accordingly
-                            let sign = chars.nth(charge_sign_idx).unwrap_or('+');
-                            if sign == '-' {
-                                charge *= -1;
-                            }
-
-                            precursor_charge = Some(charge);
+                        intensity_list,
+                    },
+                    charge_list,
+                    annotation_list,
+                }));
+            } else if in_spectrum {
+                // if line contains a peak
+                if first_char.is_numeric() {
+                    if !parse_peaks {
+                        continue
+                    }
+
+                    let (mz, intensity, peak_charge, peak_annotation) = match parse_peak(line) {
+                        Ok(peak) => peak,
+                        Err(err) => {
+                            let error = self.locate(err, Some(&header));
+                            self.skip_bad_line(error)?;
+                            continue
                         }
+                    };
+
+                    let peak_idx = mz_list.len();
+                    mz_list.push(mz);
+                    intensity_list.push(intensity);
+
+                    // Optional extra columns: fragment charge and/or annotation
+                    if let Some(charge) = peak_charge {
+                        let charges = charge_list.get_or_insert_with(Vec::new);
+                        charges.resize(peak_idx, 0);
+                        charges.push(charge);
+                    }
+                    if let Some(annotation) = peak_annotation {
+                        let annotations = annotation_list.get_or_insert_with(Vec::new);
+                        annotations.resize(peak_idx, String::new());
+                        annotations.push(annotation);
+                    }
+                } else if let Some((key, value)) = line.split_once('=') {
+                    if let Err(err) = parse_spectrum_param(&mut header, key, value) {
+                        let error = self.locate(err, Some(&header));
+                        self.skip_bad_line(error)?;
                     }
-                } // ends else if in_spectrum
-            } // ends read_line
+                }
+            } // ends else if in_spectrum
         } // ends loop
     }
-}
\ No newline at end of file
+
+    /// Records the given malformed line as dropped if the parse policy allows it, otherwise returns the error
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - Located parsing error
+    ///
+    fn skip_bad_line(&mut self, error: Error) -> Result<()> {
+        if self.parse_policy != MgfParsePolicy::SkipBadLine {
+            return Err(error);
+        }
+        self.parse_report.add(MgfSkippedItem::Line, error);
+        Ok(())
+    }
+
+    /// Skips the remaining lines of a malformed spectrum, up to its END IONS or the next BEGIN IONS
+    ///
+    fn skip_to_spectrum_end(&mut self) -> Result<()> {
+        let mut line = String::new();
+        while self.read_line(&mut line)? > 0 {
+            let trimmed_line = line.trim();
+            if trimmed_line.starts_with("END IONS") {
+                break;
+            } else if trimmed_line.starts_with("BEGIN IONS") {
+                self.is_at_spectrum_start = true;
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead> FallibleIterator for MgfReader<R> {
+    type Item = MgfSpectrum;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        self.next_spectrum(true)
+    }
+}
+
+impl<R: BufRead> MgfReader<R> {
+    /// Returns an iterator over the spectrum headers, skipping the peak lines without parsing them.
+    /// This is much faster when only the precursor information (m/z, charge, RT, title...) is needed.
+    ///
+    pub fn headers(&mut self) -> MgfSpectrumHeaders<'_, R> {
+        MgfSpectrumHeaders { reader: self }
+    }
+
+    /// Reads the next spectrum, applying the parse policy to the malformed ones
+    ///
+    /// # Arguments
+    ///
+    /// * `parse_peaks` - Whether to parse the peak lines or to skip them, leaving the peak lists empty
+    ///
+    fn next_spectrum(&mut self, parse_peaks: bool) -> Result<Option<MgfSpectrum>> {
+        self.read_global_header()?;
+
+        loop {
+            let error = match self.read_spectrum(parse_peaks) {
+                Ok(spectrum) => return Ok(spectrum),
+                Err(error) => error
+            };
+
+            // I/O errors can't be recovered from
+            if self.parse_policy == MgfParsePolicy::Strict || matches!(error, Error::Io { .. }) {
+                return Err(error);
+            }
+
+            // Truncated spectra are already fully consumed
+            let must_skip_lines = !matches!(error, Error::TruncatedRecord { .. });
+            self.parse_report.add(MgfSkippedItem::Spectrum, error);
+            if must_skip_lines {
+                self.skip_to_spectrum_end()?;
+            }
+        }
+    }
+}
+
+/// Iterator over the spectrum headers of a MGF reader, see `MgfReader::headers()`
+pub struct MgfSpectrumHeaders<'r, R: BufRead> {
+    reader: &'r mut MgfReader<R>,
+}
+
+impl<R: BufRead> FallibleIterator for MgfSpectrumHeaders<'_, R> {
+    type Item = MgfSpectrumHeader;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        Ok(self.reader.next_spectrum(false)?.map(|spectrum| spectrum.header))
+    }
+}
+
+/// Parses a peak line: m/z, intensity and optional fragment charge and annotation columns.
+/// The first extra column is the charge if it parses as one, unless it is the only extra column
+/// and is not explicitly signed, e.g. `100.1 50.0 2` has the annotation `2` and no charge.
+///
+/// # Arguments
+///
+/// * `line` - Trimmed peak line, e.g. `138.9233 369.316 1+ y1`
+///
+pub(crate) fn parse_peak(line: &str) -> Result<(f64, f32, Option<i8>, Option<String>)> {
+    let mut split = line.split_ascii_whitespace();
+
+    let mz: f64 = match split.next() {
+        Some(mz) => parse_number(mz)?,
+        None => return Err(Error::missing_value("m/z value"))
+    };
+
+    let intensity: f32 = match split.next() {
+        Some(intens) => parse_number(intens)?,
+        None => return Err(Error::missing_value("intensity value"))
+    };
+
+    let mut extra_columns: Vec<&str> = split.collect();
+    if extra_columns.is_empty() {
+        return Ok((mz, intensity, None, None));
+    }
+
+    // A lone extra column is only taken as charge if explicitly signed (`2+`, `+2`, `2-`, `-2`),
+    // an unsigned number (e.g. an ion count) is kept as annotation
+    let is_charge_candidate = extra_columns.len() > 1 || extra_columns[0].starts_with(['+', '-'])
+        || extra_columns[0].ends_with(['+', '-']);
+    let charge = if is_charge_candidate { parse_charge(extra_columns[0]).ok() } else { None };
+    if charge.is_some() {
+        extra_columns.remove(0);
+    }
+    let annotation = if extra_columns.is_empty() { None } else { Some(extra_columns.join(" ")) };
+
+    Ok((mz, intensity, charge, annotation))
+}
+
+/// Parses a spectrum parameter line (`KEY=value`) into the given header.
+/// Parameters without dedicated field are kept as additional parameters.
+///
+/// # Arguments
+///
+/// * `header` - Header of the spectrum being read
+/// * `key` - Parameter key, e.g. `PEPMASS`
+/// * `value` - Raw parameter value
+///
+pub(crate) fn parse_spectrum_param(header: &mut MgfSpectrumHeader, key: &str, value: &str) -> Result<()> {
+    match key {
+        "TITLE" => header.title = value.to_owned(),
+        "PEPMASS" => {
+            let mut split = value.split_ascii_whitespace();
+            header.precursor_mz = parse_number(split.next().unwrap_or("0.0"))?;
+            header.precursor_intensity = split.next().map(parse_number).transpose()?;
+        },
+        "RTINSECONDS" => {
+            let (start, end) = split_range(value);
+            header.retention_time = Some(parse_number(start)?);
+            header.retention_time_end = end.map(parse_number).transpose()?;
+        },
+        "CHARGE" => header.precursor_charges = parse_charges(value)?,
+        "SCANS" => match parse_scan_range(value) {
+            Some((first_scan, last_scan)) => {
+                header.first_scan = Some(first_scan);
+                header.last_scan = last_scan;
+            },
+            // Non-numeric scan identifiers (e.g. `F1:1000`) are kept as is
+            None => header.additional_params.push((key.to_owned(), value.to_owned())),
+        },
+        "INSTRUMENT" => header.instrument = Some(value.to_owned()),
+        "TOL" => header.tolerance = Some(parse_number(value)?),
+        "TOLU" => header.tolerance_unit = Some(value.parse()?),
+        "IT_MODS" => header.variable_modifications = value.split(',').map(|m| m.trim().to_owned()).collect(),
+        "SEQ" => header.sequence_qualifiers.push(value.to_owned()),
+        "COMP" => header.composition_qualifiers.push(value.to_owned()),
+        "TAG" => header.sequence_tags.push(value.to_owned()),
+        "ETAG" => header.error_tolerant_sequence_tags.push(value.to_owned()),
+        // Keep any other parameter (RAWFILE, ION_MOBILITY, USER01...) as is
+        _ => header.additional_params.push((key.to_owned(), value.to_owned())),
+    }
+
+    Ok(())
+}
+
+/// Parses a global parameter line (`KEY=value`) into the given global header.
+/// Parameters without dedicated field are kept as additional parameters.
+///
+/// # Arguments
+///
+/// * `global_header` - Global header of the file being read
+/// * `key` - Parameter key, e.g. `CHARGE`
+/// * `value` - Raw parameter value
+///
+pub(crate) fn parse_global_param(global_header: &mut MgfGlobalHeader, key: &str, value: &str) -> Result<()> {
+    match key {
+        "COM" => global_header.comment = Some(value.to_owned()),
+        "SEARCH" => global_header.search = Some(value.to_owned()),
+        "MASS" => global_header.mass_type = Some(value.parse()?),
+        "CHARGE" => global_header.precursor_charges = parse_charges(value)?,
+        "TOL" => global_header.tolerance = Some(parse_number(value)?),
+        "TOLU" => global_header.tolerance_unit = Some(value.parse()?),
+        "ITOL" => global_header.fragment_tolerance = Some(parse_number(value)?),
+        "ITOLU" => global_header.fragment_tolerance_unit = Some(value.parse()?),
+        "CLE" => global_header.enzyme = Some(value.to_owned()),
+        "DB" => global_header.database = Some(value.to_owned()),
+        "MODS" => global_header.fixed_modifications = value.split(',').map(|m| m.trim().to_owned()).collect(),
+        "IT_MODS" => global_header.variable_modifications = value.split(',').map(|m| m.trim().to_owned()).collect(),
+        "INSTRUMENT" => global_header.instrument = Some(value.to_owned()),
+        _ => global_header.additional_params.push((key.to_owned(), value.to_owned())),
+    }
+
+    Ok(())
+}
+
+/// Parses a floating point number
+///
+/// # Arguments
+///
+/// * `value` - Raw number
+///
+pub(crate) fn parse_number<T: fast_float::FastFloat>(value: &str) -> Result<T> {
+    fast_float::parse(value).map_err(|_| Error::invalid_number(value))
+}
+
+/// Parses a scan number
+///
+/// # Arguments
+///
+/// * `value` - Raw scan number
+///
+pub(crate) fn parse_scan(value: &str) -> Result<u32> {
+    value.parse().map_err(|_| Error::invalid_number(value))
+}
+
+/// Parses a scan number or range, e.g. `2000` or `2000-2005`.
+/// Returns None if the scans are not numeric, e.g. `F1:1000`.
+///
+/// # Arguments
+///
+/// * `value` - Raw SCANS value
+///
+pub(crate) fn parse_scan_range(value: &str) -> Option<(u32, Option<u32>)> {
+    let (first, last) = split_range(value);
+    let first_scan = parse_scan(first).ok()?;
+    let last_scan = match last {
+        Some(last) => Some(parse_scan(last).ok()?),
+        None => None
+    };
+    Some((first_scan, last_scan))
+}
+
+/// Parses a list of candidate charges, as documented by Mascot,
+/// e.g. `2+`, `+2`, `2-`, `-2`, `2`, `2+ and 3+`, `2+,3+` or `2+, 3+ and 4+`
+///
+/// # Arguments
+///
+/// * `value` - Raw charge value
+///
+pub(crate) fn parse_charges(value: &str) -> Result<Vec<i8>> {
+    value.split(',')
+        .flat_map(|chunk| chunk.split(" and "))
+        .map(str::trim)
+        .filter(|charge_str| !charge_str.is_empty())
+        .map(parse_charge)
+        .collect()
+}
+
+/// Parses a single charge, with optional leading or trailing sign, e.g. `2+`, `+2`, `2-` or `-2`
+///
+/// # Arguments
+///
+/// * `value` - Raw charge value
+///
+fn parse_charge(value: &str) -> Result<i8> {
+    // Locate the sign, which may be leading or trailing
+    let (charge_str, is_negative) = if let Some(charge_str) = value.strip_suffix('-').or_else(|| value.strip_prefix('-')) {
+        (charge_str, true)
+    } else {
+        (value.strip_suffix('+').or_else(|| value.strip_prefix('+')).unwrap_or(value), false)
+    };
+
+    let charge: i8 = match charge_str.trim().parse() {
+        Ok(charge) => charge,
+        Err(_) => return Err(Error::malformed_header(format!("invalid charge: {}", value)))
+    };
+
+    Ok(if is_negative { -charge } else { charge })
+}
+
+/// Splits a value which may be given as a range, e.g. `2000-2005` or `120.5-130.2`.
+/// A leading minus sign and the sign of an exponent (e.g. `1.5e-3`) are not taken as separator.
+///
+/// # Arguments
+///
+/// * `value` - Raw value, single or range
+///
+pub(crate) fn split_range(value: &str) -> (&str, Option<&str>) {
+    let bytes = value.as_bytes();
+    let separator_idx = (1..bytes.len())
+        .find(|&idx| bytes[idx] == b'-' && !matches!(bytes[idx - 1], b'e' | b'E'));
+    match separator_idx {
+        Some(idx) => (value[..idx].trim(), Some(value[idx + 1..].trim())),
+        None => (value.trim(), None)
+    }
+}
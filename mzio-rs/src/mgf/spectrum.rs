@@ -1,19 +1,122 @@
 
+// std imports
+use std::fmt;
+use std::str::FromStr;
+
 // 3rd party imports
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 // internal imports
+use crate::error::Error;
 use mzcore::ms::spectrum::SpectrumData;
 use mzcore::ms::utils::mz_to_mass;
 
+/// Unit of the peptide mass tolerance (TOLU)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum MgfToleranceUnit {
+    Percent,
+    Ppm,
+    Mmu,
+    Da,
+}
+
+impl FromStr for MgfToleranceUnit {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "%" => Ok(Self::Percent),
+            "ppm" => Ok(Self::Ppm),
+            "mmu" => Ok(Self::Mmu),
+            "da" => Ok(Self::Da),
+            _ => Err(Error::malformed_header(format!("unknown tolerance unit: {}", value)))
+        }
+    }
+}
+
+impl fmt::Display for MgfToleranceUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Percent => write!(f, "%"),
+            Self::Ppm => write!(f, "ppm"),
+            Self::Mmu => write!(f, "mmu"),
+            Self::Da => write!(f, "Da"),
+        }
+    }
+}
+
 /// Spectrum representation for MGF files.
 #[derive(Clone, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct MgfSpectrumHeader {
     pub title: String,
     pub precursor_mz: f64,
-    pub precursor_charge: Option<i8>,
+    /// Precursor intensity (second value of PEPMASS)
+    #[serde(default)]
+    pub precursor_intensity: Option<f64>,
+    /// Candidate precursor charges (CHARGE=2+ and 3+), empty if unknown.
+    /// Also deserialized from the former single `precursor_charge` field.
+    #[serde(default, alias = "precursor_charge", deserialize_with = "deserialize_charges")]
+    pub precursor_charges: Vec<i8>,
     pub precursor_mass: Option<f64>, // Not in the header (for post-processing convenience)
     pub retention_time: Option<f64>,
+    /// End of the retention time range (RTINSECONDS=start-end)
+    #[serde(default)]
+    pub retention_time_end: Option<f64>,
+    /// Scan number, or first scan of the range (SCANS)
+    #[serde(default)]
+    pub first_scan: Option<u32>,
+    /// Last scan of the range (SCANS=first-last)
+    #[serde(default)]
+    pub last_scan: Option<u32>,
+    /// Instrument type (INSTRUMENT)
+    #[serde(default)]
+    pub instrument: Option<String>,
+    /// Peptide mass tolerance (TOL)
+    #[serde(default)]
+    pub tolerance: Option<f64>,
+    /// Unit of the peptide mass tolerance (TOLU)
+    #[serde(default)]
+    pub tolerance_unit: Option<MgfToleranceUnit>,
+    /// Variable modifications (IT_MODS)
+    #[serde(default)]
+    pub variable_modifications: Vec<String>,
+    /// Amino acid sequence qualifiers (SEQ)
+    #[serde(default)]
+    pub sequence_qualifiers: Vec<String>,
+    /// Amino acid composition qualifiers (COMP)
+    #[serde(default)]
+    pub composition_qualifiers: Vec<String>,
+    /// Sequence tags (TAG)
+    #[serde(default)]
+    pub sequence_tags: Vec<String>,
+    /// Error tolerant sequence tags (ETAG)
+    #[serde(default)]
+    pub error_tolerant_sequence_tags: Vec<String>,
+    /// Parameters not handled explicitly (e.g. RAWFILE, USER01, non-numeric SCANS), in reading order
+    #[serde(default)]
+    pub additional_params: Vec<(String, String)>,
+}
+
+/// Deserializes the precursor charges from a list of charges, a single charge or null,
+/// so that data serialized with the former `precursor_charge: Option<i8>` field can still be read
+///
+/// # Arguments
+///
+/// * `deserializer` - Serde deserializer
+///
+fn deserialize_charges<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<i8>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Charges {
+        Multiple(Vec<i8>),
+        Single(i8),
+    }
+
+    Ok(match Option::<Charges>::deserialize(deserializer)? {
+        Some(Charges::Multiple(charges)) => charges,
+        Some(Charges::Single(charge)) => vec![charge],
+        None => Vec::new()
+    })
 }
 
 impl MgfSpectrumHeader {
@@ -37,9 +140,22 @@ impl MgfSpectrumHeader {
         Self {
             title,
             precursor_mz,
-            precursor_charge,
+            precursor_intensity: None,
+            precursor_charges: precursor_charge.into_iter().collect(),
             precursor_mass: None,
             retention_time,
+            retention_time_end: None,
+            first_scan: None,
+            last_scan: None,
+            instrument: None,
+            tolerance: None,
+            tolerance_unit: None,
+            variable_modifications: Vec::new(),
+            sequence_qualifiers: Vec::new(),
+            composition_qualifiers: Vec::new(),
+            sequence_tags: Vec::new(),
+            error_tolerant_sequence_tags: Vec::new(),
+            additional_params: Vec::new(),
         }
     }
 
@@ -55,10 +171,39 @@ impl MgfSpectrumHeader {
         self.precursor_mz
     }
 
-    /// Returns precursor charge
+    /// Returns the precursor intensity
+    ///
+    pub fn get_precursor_intensity(&self) -> Option<f64> {
+        self.precursor_intensity
+    }
+
+    /// Sets the precursor intensity
+    ///
+    pub fn set_precursor_intensity(&mut self, precursor_intensity: Option<f64>) -> &MgfSpectrumHeader {
+        self.precursor_intensity = precursor_intensity;
+        self
+    }
+
+    /// Returns precursor charge, if a single candidate charge is given
     /// 
     pub fn get_precursor_charge(&self) -> Option<i8> {
-        self.precursor_charge
+        match self.precursor_charges.as_slice() {
+            [charge] => Some(*charge),
+            _ => None
+        }
+    }
+
+    /// Returns all candidate precursor charges
+    ///
+    pub fn get_precursor_charges(&self) -> &Vec<i8> {
+        &self.precursor_charges
+    }
+
+    /// Sets the candidate precursor charges
+    ///
+    pub fn set_precursor_charges(&mut self, precursor_charges: Vec<i8>) -> &MgfSpectrumHeader {
+        self.precursor_charges = precursor_charges;
+        self
     }
 
     /// Returns precursor mass
@@ -74,10 +219,10 @@ impl MgfSpectrumHeader {
         self
     }
 
-    /// Calculates and sets precursor mass
+    /// Calculates and sets precursor mass (only if a single candidate charge is given)
     ///
     pub fn calc_precursor_mass(&mut self) -> &MgfSpectrumHeader {
-        self.precursor_mass = self.precursor_charge.map(|z| mz_to_mass(self.precursor_mz, z as i32));
+        self.precursor_mass = self.get_precursor_charge().map(|z| mz_to_mass(self.precursor_mz, z as i32));
         self
     }
 
@@ -87,12 +232,118 @@ impl MgfSpectrumHeader {
         self.retention_time
     }
 
+    /// Returns the end of the retention time range, if RTINSECONDS is given as a range
+    ///
+    pub fn get_retention_time_end(&self) -> Option<f64> {
+        self.retention_time_end
+    }
+
+    /// Returns the scan number, or the first scan of the range
+    ///
+    pub fn get_first_scan(&self) -> Option<u32> {
+        self.first_scan
+    }
+
+    /// Returns the last scan of the range, if SCANS is given as a range
+    ///
+    pub fn get_last_scan(&self) -> Option<u32> {
+        self.last_scan
+    }
+
+    /// Returns the instrument type
+    ///
+    pub fn get_instrument(&self) -> Option<&String> {
+        self.instrument.as_ref()
+    }
+
+    /// Returns the peptide mass tolerance
+    ///
+    pub fn get_tolerance(&self) -> Option<f64> {
+        self.tolerance
+    }
+
+    /// Returns the unit of the peptide mass tolerance
+    ///
+    pub fn get_tolerance_unit(&self) -> Option<MgfToleranceUnit> {
+        self.tolerance_unit
+    }
+
+    /// Returns the variable modifications
+    ///
+    pub fn get_variable_modifications(&self) -> &Vec<String> {
+        &self.variable_modifications
+    }
+
+    /// Returns the amino acid sequence qualifiers
+    ///
+    pub fn get_sequence_qualifiers(&self) -> &Vec<String> {
+        &self.sequence_qualifiers
+    }
+
+    /// Returns the amino acid composition qualifiers
+    ///
+    pub fn get_composition_qualifiers(&self) -> &Vec<String> {
+        &self.composition_qualifiers
+    }
+
+    /// Returns the sequence tags
+    ///
+    pub fn get_sequence_tags(&self) -> &Vec<String> {
+        &self.sequence_tags
+    }
+
+    /// Returns the error tolerant sequence tags
+    ///
+    pub fn get_error_tolerant_sequence_tags(&self) -> &Vec<String> {
+        &self.error_tolerant_sequence_tags
+    }
+
+    /// Returns the parameters not handled explicitly, in reading order
+    ///
+    pub fn get_additional_params(&self) -> &Vec<(String, String)> {
+        &self.additional_params
+    }
+
+    /// Returns the value of the first additional parameter with the given key
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Parameter key, e.g. `RAWFILE`
+    ///
+    pub fn get_additional_param(&self, key: &str) -> Option<&String> {
+        self.additional_params.iter()
+            .find(|(param_key, _)| param_key == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Sets an additional parameter, replacing the value of an existing key
+    /// or appending the parameter otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Parameter key, e.g. `RAWFILE`
+    /// * `value` - Parameter value
+    ///
+    pub fn set_additional_param(&mut self, key: String, value: String) -> &MgfSpectrumHeader {
+        match self.additional_params.iter_mut().find(|(param_key, _)| *param_key == key) {
+            Some(param) => param.1 = value,
+            None => self.additional_params.push((key, value)),
+        }
+        self
+    }
+
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct MgfSpectrum {
     pub header: MgfSpectrumHeader,
     pub data: SpectrumData,
+    /// Per-peak fragment charges (third peak column), 0 if unknown for a peak
+    #[serde(default)]
+    pub charge_list: Option<Vec<i8>>,
+    /// Per-peak annotations (last peak column), empty if missing for a peak
+    #[serde(default)]
+    pub annotation_list: Option<Vec<String>>,
 }
 
 impl MgfSpectrum {
@@ -125,7 +376,9 @@ impl MgfSpectrum {
 
         Self {
             header: mgf_header,
-            data: data,
+            data,
+            charge_list: None,
+            annotation_list: None,
         }
     }
 
@@ -141,6 +394,32 @@ impl MgfSpectrum {
         &self.data.intensity_list
     }
 
+    /// Returns the per-peak fragment charges, if any (0 if unknown for a peak)
+    ///
+    pub fn get_charge_list(&self) -> Option<&Vec<i8>> {
+        self.charge_list.as_ref()
+    }
+
+    /// Sets the per-peak fragment charges (0 if unknown for a peak)
+    ///
+    pub fn set_charge_list(&mut self, charge_list: Option<Vec<i8>>) -> &MgfSpectrum {
+        self.charge_list = charge_list;
+        self
+    }
+
+    /// Returns the per-peak annotations, if any (empty if missing for a peak)
+    ///
+    pub fn get_annotation_list(&self) -> Option<&Vec<String>> {
+        self.annotation_list.as_ref()
+    }
+
+    /// Sets the per-peak annotations (empty if missing for a peak)
+    ///
+    pub fn set_annotation_list(&mut self, annotation_list: Option<Vec<String>>) -> &MgfSpectrum {
+        self.annotation_list = annotation_list;
+        self
+    }
+
 }
 
 
@@ -8,15 +8,213 @@ use std::path::Path;
 use anyhow::Result;
 
 // internal imports 
+use crate::compression::{self, CompressedFile, CompressionFormat};
+use crate::mgf::global_header::MgfGlobalHeader;
+use crate::mgf::number_format::NumberFormat;
 use crate::mgf::spectrum::MgfSpectrum;
 
+/// Output settings of the MGF writer: number formats and peak filters.
+/// Filters only affect the written peaks, the spectra are left unchanged.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MgfWriterOptions {
+    /// Format of the peak m/z values
+    pub mz_format: NumberFormat,
+    /// Format of the peak intensities
+    pub intensity_format: NumberFormat,
+    /// Format of the precursor m/z (PEPMASS) and of the mass tolerances (TOL, ITOL)
+    pub precursor_mz_format: NumberFormat,
+    /// Format of the precursor intensity (PEPMASS)
+    pub precursor_intensity_format: NumberFormat,
+    /// Format of the retention times (RTINSECONDS)
+    pub retention_time_format: NumberFormat,
+    /// Whether to drop the peaks with a zero intensity
+    pub skip_zero_intensities: bool,
+    /// Minimal intensity of the written peaks
+    pub min_intensity: Option<f32>,
+    /// Minimal intensity of the written peaks, relative to the most intense peak of the spectrum (0.0 to 1.0)
+    pub min_relative_intensity: Option<f32>,
+    /// Maximal number of written peaks, the most intense ones being kept
+    pub max_num_peaks: Option<usize>,
+    /// Range of m/z values of the written peaks (inclusive)
+    pub mz_range: Option<(f64, f64)>,
+}
+
+impl MgfWriterOptions {
+    /// Creates new options, writing all the numbers with their shortest representation
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the format of the peak m/z values
+    ///
+    /// # Arguments
+    ///
+    /// * `mz_format` - Number format
+    ///
+    pub fn set_mz_format(&mut self, mz_format: NumberFormat) -> &mut Self {
+        self.mz_format = mz_format;
+        self
+    }
+
+    /// Sets the format of the peak intensities
+    ///
+    /// # Arguments
+    ///
+    /// * `intensity_format` - Number format
+    ///
+    pub fn set_intensity_format(&mut self, intensity_format: NumberFormat) -> &mut Self {
+        self.intensity_format = intensity_format;
+        self
+    }
+
+    /// Sets the format of the precursor m/z and of the mass tolerances
+    ///
+    /// # Arguments
+    ///
+    /// * `precursor_mz_format` - Number format
+    ///
+    pub fn set_precursor_mz_format(&mut self, precursor_mz_format: NumberFormat) -> &mut Self {
+        self.precursor_mz_format = precursor_mz_format;
+        self
+    }
+
+    /// Sets the format of the precursor intensity
+    ///
+    /// # Arguments
+    ///
+    /// * `precursor_intensity_format` - Number format
+    ///
+    pub fn set_precursor_intensity_format(&mut self, precursor_intensity_format: NumberFormat) -> &mut Self {
+        self.precursor_intensity_format = precursor_intensity_format;
+        self
+    }
+
+    /// Sets the format of the retention times
+    ///
+    /// # Arguments
+    ///
+    /// * `retention_time_format` - Number format
+    ///
+    pub fn set_retention_time_format(&mut self, retention_time_format: NumberFormat) -> &mut Self {
+        self.retention_time_format = retention_time_format;
+        self
+    }
+
+    /// Sets whether to drop the peaks with a zero intensity (default: false)
+    ///
+    /// # Arguments
+    ///
+    /// * `skip_zero_intensities` - Whether to drop the zero-intensity peaks or not
+    ///
+    pub fn set_skip_zero_intensities(&mut self, skip_zero_intensities: bool) -> &mut Self {
+        self.skip_zero_intensities = skip_zero_intensities;
+        self
+    }
+
+    /// Sets the minimal intensity of the written peaks
+    ///
+    /// # Arguments
+    ///
+    /// * `min_intensity` - Absolute intensity threshold, None to disable
+    ///
+    pub fn set_min_intensity(&mut self, min_intensity: Option<f32>) -> &mut Self {
+        self.min_intensity = min_intensity;
+        self
+    }
+
+    /// Sets the minimal intensity of the written peaks, relative to the most intense peak of each spectrum
+    ///
+    /// # Arguments
+    ///
+    /// * `min_relative_intensity` - Relative intensity threshold, e.g. 0.01 for 1% of the base peak, None to disable
+    ///
+    pub fn set_min_relative_intensity(&mut self, min_relative_intensity: Option<f32>) -> &mut Self {
+        self.min_relative_intensity = min_relative_intensity;
+        self
+    }
+
+    /// Sets the maximal number of written peaks per spectrum, the most intense ones being kept
+    ///
+    /// # Arguments
+    ///
+    /// * `max_num_peaks` - Maximal number of peaks, None to disable
+    ///
+    pub fn set_max_num_peaks(&mut self, max_num_peaks: Option<usize>) -> &mut Self {
+        self.max_num_peaks = max_num_peaks;
+        self
+    }
+
+    /// Sets the range of m/z values of the written peaks
+    ///
+    /// # Arguments
+    ///
+    /// * `mz_range` - Lowest and highest m/z values (inclusive), None to disable
+    ///
+    pub fn set_mz_range(&mut self, mz_range: Option<(f64, f64)>) -> &mut Self {
+        self.mz_range = mz_range;
+        self
+    }
+
+    /// Returns true if some peaks may be filtered out
+    ///
+    pub fn has_peak_filters(&self) -> bool {
+        self.skip_zero_intensities
+            || self.min_intensity.is_some()
+            || self.min_relative_intensity.is_some()
+            || self.max_num_peaks.is_some()
+            || self.mz_range.is_some()
+    }
+
+    /// Selects the peaks passing the filters, in m/z order
+    ///
+    /// # Arguments
+    ///
+    /// * `mz_list` - Peak m/z values
+    /// * `intensity_list` - Peak intensities
+    /// * `peak_indices` - Indices of the selected peaks (cleared beforehand)
+    ///
+    fn select_peaks(&self, mz_list: &[f64], intensity_list: &[f32], peak_indices: &mut Vec<usize>) {
+        peak_indices.clear();
+
+        let mut min_intensity = self.min_intensity.unwrap_or(f32::NEG_INFINITY);
+        if let Some(min_relative_intensity) = self.min_relative_intensity {
+            let max_intensity = intensity_list.iter().copied().fold(0.0, f32::max);
+            min_intensity = min_intensity.max(max_intensity * min_relative_intensity);
+        }
+
+        for (peak_idx, (mz, intensity)) in zip(mz_list, intensity_list).enumerate() {
+            let is_in_mz_range = match self.mz_range {
+                Some((min_mz, max_mz)) => *mz >= min_mz && *mz <= max_mz,
+                None => true
+            };
+            if is_in_mz_range && *intensity >= min_intensity && !(self.skip_zero_intensities && *intensity == 0.0) {
+                peak_indices.push(peak_idx);
+            }
+        }
+
+        if let Some(max_num_peaks) = self.max_num_peaks.filter(|max_num_peaks| peak_indices.len() > *max_num_peaks) {
+            // Stable sort: the first peaks are kept on ties
+            peak_indices.sort_by(|a, b| intensity_list[*b].total_cmp(&intensity_list[*a]));
+            peak_indices.truncate(max_num_peaks);
+            peak_indices.sort_unstable();
+        }
+    }
+}
+
 /// Writer for MGF files
+/// Works on any destination (file, stdout, `Vec<u8>`, socket, compressed stream...).
 /// Use flush() to make ensure the buffer is written completely.
-pub struct MgfWriter {
-    internal_writer: BufWriter<File>
+pub struct MgfWriter<W: Write> {
+    internal_writer: W,
+    options: MgfWriterOptions,
+    /// Reused buffer for the formatted lines
+    line_buffer: String,
+    /// Reused buffer for the indices of the peaks passing the filters
+    peak_indices: Vec<usize>,
 }
 
-impl MgfWriter {
+impl MgfWriter<BufWriter<File>> {
     /// Creates a new Writer
     /// 
     /// # Arguments
@@ -25,9 +223,136 @@ impl MgfWriter {
     /// 
     pub fn new(mgf_file_path: &Path) -> Result<Self> {
         let mgf_file: File = File::create(mgf_file_path)?;
-        Ok(Self {
-            internal_writer: BufWriter::new(mgf_file)
-        })
+        Ok(Self::from_writer(BufWriter::new(mgf_file)))
+    }
+}
+
+impl MgfWriter<CompressedFile> {
+    /// Creates a new Writer compressing the output file.
+    /// Call `finish()` once everything is written to finalize the compressed stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `mgf_file_path` - Path to MGF file
+    /// * `compression_format` - Compression format, e.g. `CompressionFormat::from_extension(mgf_file_path)`
+    ///
+    pub fn new_compressed(mgf_file_path: &Path, compression_format: CompressionFormat) -> Result<Self> {
+        Ok(Self::from_writer(compression::create_file(mgf_file_path, compression_format)?))
+    }
+
+    /// Flushes the remaining content, finalizes the compressed stream and returns the file.
+    /// Unlike dropping the writer, this reports the errors occurring while finalizing the stream.
+    ///
+    pub fn finish(self) -> Result<File> {
+        compression::finish_file(self.into_inner())
+    }
+}
+
+impl<W: Write> MgfWriter<W> {
+    /// Creates a new Writer on top of any destination.
+    /// The given writer is used as is, wrap it into a `BufWriter` if it is unbuffered.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Destination of the MGF content
+    ///
+    pub fn from_writer(writer: W) -> Self {
+        Self {
+            internal_writer: writer,
+            options: MgfWriterOptions::new(),
+            line_buffer: String::new(),
+            peak_indices: Vec::new(),
+        }
+    }
+
+    /// Sets the output settings, e.g. the number formats
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Output settings
+    ///
+    pub fn set_options(&mut self, options: MgfWriterOptions) -> &mut Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the output settings
+    ///
+    pub fn get_options(&self) -> &MgfWriterOptions {
+        &self.options
+    }
+
+    /// Returns a reference to the underlying writer
+    ///
+    pub fn get_ref(&self) -> &W {
+        &self.internal_writer
+    }
+
+    /// Consumes the MGF writer and returns the underlying writer.
+    /// Call flush() beforehand when the underlying writer is buffered.
+    ///
+    pub fn into_inner(self) -> W {
+        self.internal_writer
+    }
+
+    /// Writes the global parameters block, which has to come before the first spectrum.
+    ///
+    /// # Arguments
+    ///
+    /// * `global_header` - Global parameters
+    ///
+    pub fn write_global_header(&mut self, global_header: &MgfGlobalHeader) -> Result<usize> {
+        if global_header.is_empty() {
+            return Ok(0);
+        }
+
+        let mut written_bytes: usize = 0;
+
+        if let Some(comment) = global_header.get_comment() {
+            written_bytes += self._write_string(format!("COM={}\n", comment))?;
+        }
+        if let Some(search) = global_header.get_search() {
+            written_bytes += self._write_string(format!("SEARCH={}\n", search))?;
+        }
+        if let Some(mass_type) = global_header.get_mass_type() {
+            written_bytes += self._write_string(format!("MASS={}\n", mass_type))?;
+        }
+        if !global_header.get_precursor_charges().is_empty() {
+            written_bytes += self._write_string(format!("CHARGE={}\n", format_charges(global_header.get_precursor_charges())))?;
+        }
+        if let Some(tolerance) = global_header.get_tolerance() {
+            written_bytes += self._write_string(format!("TOL={}\n", self.options.precursor_mz_format.format_f64(tolerance)))?;
+        }
+        if let Some(tolerance_unit) = global_header.get_tolerance_unit() {
+            written_bytes += self._write_string(format!("TOLU={}\n", tolerance_unit))?;
+        }
+        if let Some(fragment_tolerance) = global_header.get_fragment_tolerance() {
+            written_bytes += self._write_string(format!("ITOL={}\n", self.options.precursor_mz_format.format_f64(fragment_tolerance)))?;
+        }
+        if let Some(fragment_tolerance_unit) = global_header.get_fragment_tolerance_unit() {
+            written_bytes += self._write_string(format!("ITOLU={}\n", fragment_tolerance_unit))?;
+        }
+        if let Some(enzyme) = global_header.get_enzyme() {
+            written_bytes += self._write_string(format!("CLE={}\n", enzyme))?;
+        }
+        if let Some(database) = global_header.get_database() {
+            written_bytes += self._write_string(format!("DB={}\n", database))?;
+        }
+        if !global_header.get_fixed_modifications().is_empty() {
+            written_bytes += self._write_string(format!("MODS={}\n", global_header.get_fixed_modifications().join(",")))?;
+        }
+        if !global_header.get_variable_modifications().is_empty() {
+            written_bytes += self._write_string(format!("IT_MODS={}\n", global_header.get_variable_modifications().join(",")))?;
+        }
+        if let Some(instrument) = global_header.get_instrument() {
+            written_bytes += self._write_string(format!("INSTRUMENT={}\n", instrument))?;
+        }
+        for (key, value) in global_header.get_additional_params() {
+            written_bytes += self._write_string(format!("{key}={value}\n"))?;
+        }
+        written_bytes += self._write_str("\n")?;
+
+        Ok(written_bytes)
     }
 
     /// Writes a spectrum into the file.
@@ -43,18 +368,99 @@ impl MgfWriter {
 
         written_bytes += self._write_str("BEGIN IONS\n")?;
         written_bytes += self._write_string(format!("TITLE={}\n", spec_header.get_title()))?;
-        written_bytes += self._write_string(format!("PEPMASS={}", spec_header.get_precursor_mz()))?;
+
+        // Numbers are formatted into a reused buffer
+        let options = self.options;
+        let mut line = std::mem::take(&mut self.line_buffer);
+        line.clear();
+        line.push_str("PEPMASS=");
+        options.precursor_mz_format.write_to(&mut line, spec_header.get_precursor_mz());
+        if let Some(precursor_intensity) = spec_header.get_precursor_intensity() {
+            line.push(' ');
+            options.precursor_intensity_format.write_to(&mut line, precursor_intensity);
+        }
 
         if let Some(retention_time) = spec_header.get_retention_time() {
-            written_bytes += self._write_string(format!("\nRTINSECONDS={}", retention_time))?;
+            line.push_str("\nRTINSECONDS=");
+            options.retention_time_format.write_to(&mut line, retention_time);
+            if let Some(retention_time_end) = spec_header.get_retention_time_end() {
+                line.push('-');
+                options.retention_time_format.write_to(&mut line, retention_time_end);
+            }
         }
-        if let Some(charge) = spec_header.get_precursor_charge() {
-            let charge_sign = if charge < 0 { '-'} else { '+' };
-            written_bytes += self._write_string(format!("\nCHARGE={}{}", charge, charge_sign))?;
+        written_bytes += self._write_str(&line)?;
+        if !spec_header.get_precursor_charges().is_empty() {
+            written_bytes += self._write_string(format!("\nCHARGE={}", format_charges(spec_header.get_precursor_charges())))?;
         }
-        for (mz, intensity) in zip(spectrum.get_mz_list(), spectrum.get_intensity_list()) {
-            written_bytes += self._write_string(format!("\n{mz} {intensity}"))?;
+
+        // Standard Mascot parameters, in canonical order
+        if let Some(first_scan) = spec_header.get_first_scan() {
+            written_bytes += self._write_string(format!("\nSCANS={}", first_scan))?;
+            if let Some(last_scan) = spec_header.get_last_scan() {
+                written_bytes += self._write_string(format!("-{}", last_scan))?;
+            }
         }
+        if let Some(instrument) = spec_header.get_instrument() {
+            written_bytes += self._write_string(format!("\nINSTRUMENT={}", instrument))?;
+        }
+        if let Some(tolerance) = spec_header.get_tolerance() {
+            written_bytes += self._write_string(format!("\nTOL={}", options.precursor_mz_format.format_f64(tolerance)))?;
+        }
+        if let Some(tolerance_unit) = spec_header.get_tolerance_unit() {
+            written_bytes += self._write_string(format!("\nTOLU={}", tolerance_unit))?;
+        }
+        if !spec_header.get_variable_modifications().is_empty() {
+            written_bytes += self._write_string(format!("\nIT_MODS={}", spec_header.get_variable_modifications().join(",")))?;
+        }
+        for sequence_qualifier in spec_header.get_sequence_qualifiers() {
+            written_bytes += self._write_string(format!("\nSEQ={}", sequence_qualifier))?;
+        }
+        for composition_qualifier in spec_header.get_composition_qualifiers() {
+            written_bytes += self._write_string(format!("\nCOMP={}", composition_qualifier))?;
+        }
+        for sequence_tag in spec_header.get_sequence_tags() {
+            written_bytes += self._write_string(format!("\nTAG={}", sequence_tag))?;
+        }
+        for error_tolerant_sequence_tag in spec_header.get_error_tolerant_sequence_tags() {
+            written_bytes += self._write_string(format!("\nETAG={}", error_tolerant_sequence_tag))?;
+        }
+
+        for (key, value) in spec_header.get_additional_params() {
+            written_bytes += self._write_string(format!("\n{key}={value}"))?;
+        }
+
+        let mz_list = spectrum.get_mz_list();
+        let intensity_list = spectrum.get_intensity_list();
+        let num_peaks = mz_list.len().min(intensity_list.len());
+        let mut peak_indices = std::mem::take(&mut self.peak_indices);
+        if options.has_peak_filters() {
+            options.select_peaks(mz_list, intensity_list, &mut peak_indices);
+        } else {
+            peak_indices.clear();
+            peak_indices.extend(0..num_peaks);
+        }
+
+        for &peak_idx in &peak_indices {
+            line.clear();
+            line.push('\n');
+            options.mz_format.write_to(&mut line, mz_list[peak_idx]);
+            line.push(' ');
+            options.intensity_format.write_to(&mut line, intensity_list[peak_idx]);
+
+            let peak_charge = spectrum.get_charge_list().and_then(|charges| charges.get(peak_idx));
+            if let Some(charge) = peak_charge.filter(|charge| **charge != 0) {
+                line.push(' ');
+                line.push_str(&format_charges(&[*charge]));
+            }
+            let peak_annotation = spectrum.get_annotation_list().and_then(|annotations| annotations.get(peak_idx));
+            if let Some(annotation) = peak_annotation.filter(|annotation| !annotation.is_empty()) {
+                line.push(' ');
+                line.push_str(annotation);
+            }
+            written_bytes += self._write_str(&line)?;
+        }
+        self.line_buffer = line;
+        self.peak_indices = peak_indices;
         written_bytes += self._write_str("\nEND IONS\n")?;
 
         Ok(written_bytes)
@@ -62,12 +468,14 @@ impl MgfWriter {
 
     #[inline(always)]
     fn _write_str(&mut self, str: &str) -> Result<usize> {
-        Ok(self.internal_writer.write(str.as_bytes())?)
+        self.internal_writer.write_all(str.as_bytes())?;
+        Ok(str.len())
     }
 
     #[inline(always)]
     fn _write_string(&mut self, string: String) -> Result<usize> {
-        Ok(self.internal_writer.write(string.as_bytes())?)
+        self.internal_writer.write_all(string.as_bytes())?;
+        Ok(string.len())
     }
 
     /// Writes multiple spectra to file.
@@ -93,4 +501,21 @@ impl MgfWriter {
         self.internal_writer.flush()?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Formats candidate charges the way Mascot documents them, e.g. `2+`, `2+ and 3+` or `2+, 3+ and 4+`
+///
+/// # Arguments
+///
+/// * `charges` - Candidate charges (not empty)
+///
+fn format_charges(charges: &[i8]) -> String {
+    let formatted_charges: Vec<String> = charges.iter()
+        .map(|charge| format!("{}{}", charge.unsigned_abs(), if *charge < 0 { '-' } else { '+' }))
+        .collect();
+
+    match formatted_charges.split_last() {
+        Some((last, others)) if !others.is_empty() => format!("{} and {}", others.join(", "), last),
+        _ => formatted_charges.join("")
+    }
+}
@@ -0,0 +1,330 @@
+// std imports
+use std::fmt;
+
+// 3rd party imports
+use serde::{Serialize, Deserialize};
+
+// internal imports
+use crate::error::{Error, Result};
+
+/// Modification of one or several residues (`\ModResPsi`, `\ModResUnimod` or `\ModRes`),
+/// written `(58,62|MOD:00046|O-phospho-L-serine)`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeffModification {
+    /// Positions of the modified residues (1-based)
+    pub positions: Vec<usize>,
+    /// Accession of the modification in the controlled vocabulary, e.g. `MOD:00046` (empty for `\ModRes`)
+    pub accession: String,
+    /// Modification name
+    pub name: String,
+    /// Optional tag, e.g. the source of the annotation
+    pub tag: Option<String>,
+}
+
+/// Single residue substitution (`\VariantSimple` or the legacy `\Variant`), written `(12|A)`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeffVariantSimple {
+    /// Position of the substituted residue (1-based)
+    pub position: usize,
+    /// New residue, `*` for a stop codon
+    pub residue: String,
+    /// Optional tag, e.g. the dbSNP identifier
+    pub tag: Option<String>,
+}
+
+/// Replacement of a segment of the sequence (`\VariantComplex`), written `(12|14|AC)`.
+/// An empty sequence is a deletion.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeffVariantComplex {
+    /// Position of the first replaced residue (1-based)
+    pub start: usize,
+    /// Position of the last replaced residue (1-based, inclusive)
+    pub end: usize,
+    /// Replacing sequence
+    pub sequence: String,
+    /// Optional tag, e.g. the dbSNP identifier
+    pub tag: Option<String>,
+}
+
+/// Chain produced by the processing of the protein (`\Processed`), e.g. a signal peptide or the mature protein,
+/// written `(1|22|PEFF:0001021|signal peptide)`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeffProcessed {
+    /// Position of the first residue of the chain (1-based)
+    pub start: usize,
+    /// Position of the last residue of the chain (1-based, inclusive)
+    pub end: usize,
+    /// Accession of the chain type in the controlled vocabulary, e.g. `PEFF:0001021`
+    pub accession: String,
+    /// Chain type name, e.g. `signal peptide`
+    pub name: String,
+    /// Optional tag, e.g. the source of the annotation
+    pub tag: Option<String>,
+}
+
+/// `\Key=Value` annotation of a PEFF entry header.
+/// Variants, modifications and processed chains are typed, other keys (e.g. ID, PName, Length) are kept as read.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PeffAnnotation {
+    /// Modifications referenced in the PSI-MOD vocabulary (`\ModResPsi`)
+    ModResPsi(Vec<PeffModification>),
+    /// Modifications referenced in Unimod (`\ModResUnimod`)
+    ModResUnimod(Vec<PeffModification>),
+    /// Modifications not referenced in a controlled vocabulary (`\ModRes`)
+    ModRes(Vec<PeffModification>),
+    /// Single residue substitutions, as written by the PEFF drafts (`\Variant`)
+    Variant(Vec<PeffVariantSimple>),
+    /// Single residue substitutions (`\VariantSimple`)
+    VariantSimple(Vec<PeffVariantSimple>),
+    /// Segment replacements, insertions and deletions (`\VariantComplex`)
+    VariantComplex(Vec<PeffVariantComplex>),
+    /// Processed chains (`\Processed`)
+    Processed(Vec<PeffProcessed>),
+    /// Any other annotation, e.g. `\PName=Serum albumin`
+    Other(String, String),
+}
+
+impl PeffAnnotation {
+    /// Parses the annotation of the given key
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Annotation key, without backslash, e.g. `VariantSimple`
+    /// * `value` - Annotation value, e.g. `(12|A)(45|T)`
+    ///
+    pub fn parse(key: &str, value: &str) -> Result<Self> {
+        match key {
+            "ModResPsi" => Ok(Self::ModResPsi(parse_terms(key, value)?)),
+            "ModResUnimod" => Ok(Self::ModResUnimod(parse_terms(key, value)?)),
+            "ModRes" => Ok(Self::ModRes(parse_terms(key, value)?)),
+            "Variant" => Ok(Self::Variant(parse_terms(key, value)?)),
+            "VariantSimple" => Ok(Self::VariantSimple(parse_terms(key, value)?)),
+            "VariantComplex" => Ok(Self::VariantComplex(parse_terms(key, value)?)),
+            "Processed" => Ok(Self::Processed(parse_terms(key, value)?)),
+            _ => Ok(Self::Other(key.to_owned(), value.to_owned()))
+        }
+    }
+
+    /// Returns the annotation key, without backslash
+    ///
+    pub fn get_key(&self) -> &str {
+        match self {
+            Self::ModResPsi(_) => "ModResPsi",
+            Self::ModResUnimod(_) => "ModResUnimod",
+            Self::ModRes(_) => "ModRes",
+            Self::Variant(_) => "Variant",
+            Self::VariantSimple(_) => "VariantSimple",
+            Self::VariantComplex(_) => "VariantComplex",
+            Self::Processed(_) => "Processed",
+            Self::Other(key, _) => key,
+        }
+    }
+}
+
+impl fmt::Display for PeffAnnotation {
+    /// Formats the annotation as written in the entry header, e.g. `\VariantSimple=(12|A)(45|T)`
+    ///
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\\{}=", self.get_key())?;
+        match self {
+            Self::ModResPsi(modifications) | Self::ModResUnimod(modifications) | Self::ModRes(modifications) => {
+                modifications.iter().try_for_each(|modification| write!(f, "{}", modification))
+            },
+            Self::Variant(variants) | Self::VariantSimple(variants) => {
+                variants.iter().try_for_each(|variant| write!(f, "{}", variant))
+            },
+            Self::VariantComplex(variants) => variants.iter().try_for_each(|variant| write!(f, "{}", variant)),
+            Self::Processed(chains) => chains.iter().try_for_each(|chain| write!(f, "{}", chain)),
+            Self::Other(_, value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Term of a typed annotation, i.e. the `|` separated fields of one of its parenthesized groups
+trait PeffTerm: Sized {
+    /// Number of mandatory fields
+    const NUM_REQUIRED_FIELDS: usize;
+
+    /// Creates the term from its fields, at least `NUM_REQUIRED_FIELDS` of them
+    ///
+    /// # Arguments
+    ///
+    /// * `fields` - Term fields
+    ///
+    fn from_fields(fields: &[&str]) -> Result<Self>;
+}
+
+impl PeffTerm for PeffModification {
+    const NUM_REQUIRED_FIELDS: usize = 3;
+
+    fn from_fields(fields: &[&str]) -> Result<Self> {
+        Ok(Self {
+            positions: fields[0].split(',').map(parse_position).collect::<Result<Vec<usize>>>()?,
+            accession: fields[1].trim().to_owned(),
+            name: fields[2].trim().to_owned(),
+            tag: parse_tag(fields.get(3)),
+        })
+    }
+}
+
+impl fmt::Display for PeffModification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let positions: Vec<String> = self.positions.iter().map(|position| position.to_string()).collect();
+        write!(f, "({}|{}|{}", positions.join(","), self.accession, self.name)?;
+        write_tag(f, &self.tag)
+    }
+}
+
+impl PeffTerm for PeffVariantSimple {
+    const NUM_REQUIRED_FIELDS: usize = 2;
+
+    fn from_fields(fields: &[&str]) -> Result<Self> {
+        Ok(Self {
+            position: parse_position(fields[0])?,
+            residue: fields[1].trim().to_owned(),
+            tag: parse_tag(fields.get(2)),
+        })
+    }
+}
+
+impl fmt::Display for PeffVariantSimple {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}|{}", self.position, self.residue)?;
+        write_tag(f, &self.tag)
+    }
+}
+
+impl PeffTerm for PeffVariantComplex {
+    const NUM_REQUIRED_FIELDS: usize = 3;
+
+    fn from_fields(fields: &[&str]) -> Result<Self> {
+        Ok(Self {
+            start: parse_position(fields[0])?,
+            end: parse_position(fields[1])?,
+            sequence: fields[2].trim().to_owned(),
+            tag: parse_tag(fields.get(3)),
+        })
+    }
+}
+
+impl fmt::Display for PeffVariantComplex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}|{}|{}", self.start, self.end, self.sequence)?;
+        write_tag(f, &self.tag)
+    }
+}
+
+impl PeffTerm for PeffProcessed {
+    const NUM_REQUIRED_FIELDS: usize = 4;
+
+    fn from_fields(fields: &[&str]) -> Result<Self> {
+        Ok(Self {
+            start: parse_position(fields[0])?,
+            end: parse_position(fields[1])?,
+            accession: fields[2].trim().to_owned(),
+            name: fields[3].trim().to_owned(),
+            tag: parse_tag(fields.get(4)),
+        })
+    }
+}
+
+impl fmt::Display for PeffProcessed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}|{}|{}|{}", self.start, self.end, self.accession, self.name)?;
+        write_tag(f, &self.tag)
+    }
+}
+
+/// Parses the terms of a typed annotation, e.g. `(12|A)(45|T)`
+///
+/// # Arguments
+///
+/// * `key` - Annotation key, used in the error messages
+/// * `value` - Annotation value
+///
+fn parse_terms<T: PeffTerm>(key: &str, value: &str) -> Result<Vec<T>> {
+    split_groups(key, value)?.into_iter()
+        .map(|group| {
+            let fields: Vec<&str> = group.split('|').collect();
+            if fields.len() < T::NUM_REQUIRED_FIELDS {
+                return Err(Error::malformed_header(format!(
+                    "expected at least {} fields in \\{} term: ({})", T::NUM_REQUIRED_FIELDS, key, group
+                )));
+            }
+            T::from_fields(&fields)
+        })
+        .collect()
+}
+
+/// Splits an annotation value into the contents of its parenthesized groups.
+/// Nested parentheses (e.g. in modification names) are kept in the group content.
+///
+/// # Arguments
+///
+/// * `key` - Annotation key, used in the error messages
+/// * `value` - Annotation value, e.g. `(12|A)(45|T)`
+///
+fn split_groups<'a>(key: &str, value: &'a str) -> Result<Vec<&'a str>> {
+    let mut groups = Vec::new();
+    let mut depth: usize = 0;
+    let mut group_start: usize = 0;
+    for (idx, character) in value.char_indices() {
+        match character {
+            '(' => {
+                if depth == 0 {
+                    group_start = idx + 1;
+                }
+                depth += 1;
+            },
+            ')' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    groups.push(&value[group_start..idx]);
+                }
+            },
+            _ if depth == 0 && !character.is_whitespace() => {
+                return Err(Error::malformed_header(format!("unexpected '{}' outside of the \\{} terms: {}", character, key, value)));
+            },
+            _ => {}
+        }
+    }
+    if depth > 0 {
+        return Err(Error::malformed_header(format!("unclosed parenthesis in \\{} value: {}", key, value)));
+    }
+
+    Ok(groups)
+}
+
+/// Parses a residue position (1-based)
+///
+/// # Arguments
+///
+/// * `value` - Position field
+///
+fn parse_position(value: &str) -> Result<usize> {
+    value.trim().parse().map_err(|_| Error::invalid_number(value.trim()))
+}
+
+/// Returns the optional tag field, None if missing or empty
+///
+/// # Arguments
+///
+/// * `field` - Tag field, if any
+///
+fn parse_tag(field: Option<&&str>) -> Option<String> {
+    field.map(|tag| tag.trim()).filter(|tag| !tag.is_empty()).map(str::to_owned)
+}
+
+/// Writes the optional tag field and closes the term
+///
+/// # Arguments
+///
+/// * `f` - Formatter
+/// * `tag` - Tag field, if any
+///
+fn write_tag(f: &mut fmt::Formatter<'_>, tag: &Option<String>) -> fmt::Result {
+    match tag {
+        Some(tag) => write!(f, "|{})", tag),
+        None => write!(f, ")")
+    }
+}
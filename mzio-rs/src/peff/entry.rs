@@ -0,0 +1,222 @@
+// 3rd party imports
+use serde::{Serialize, Deserialize};
+
+// internal imports
+use crate::error::{Error, Result};
+use crate::fasta::entry::FastaEntry;
+use crate::peff::annotation::{PeffAnnotation, PeffModification, PeffProcessed, PeffVariantComplex, PeffVariantSimple};
+
+/// UniProt keyword attributes and their PEFF counterparts, in writing order
+const UNIPROT_TO_PEFF_KEYS: [(&str, &str); 5] = [
+    ("OX", "NcbiTaxId"),
+    ("OS", "TaxName"),
+    ("GN", "GName"),
+    ("SV", "SV"),
+    ("PE", "PE"),
+];
+
+/// Entry of a PEFF file, e.g. `>sp:P02768 \ID=ALBU_HUMAN \PName=Serum albumin \VariantSimple=(12|A)`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeffEntry {
+    /// Prefix of the database the entry belongs to, e.g. `sp` (may be empty)
+    pub prefix: String,
+    /// Entry accession
+    pub accession: String,
+    /// Header annotations, in reading order
+    pub annotations: Vec<PeffAnnotation>,
+    /// Amino acid sequence
+    pub sequence: String,
+}
+
+impl PeffEntry {
+    /// Creates a new PEFF entry without annotation
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Database prefix, e.g. `sp`
+    /// * `accession` - Entry accession
+    /// * `sequence` - Amino acid sequence
+    ///
+    pub fn new(prefix: String, accession: String, sequence: String) -> Self {
+        Self {
+            prefix,
+            accession,
+            annotations: Vec::new(),
+            sequence,
+        }
+    }
+
+    /// Creates a new PEFF entry from the given header and sequence
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - A PEFF entry header, e.g. `>sp:P02768 \ID=ALBU_HUMAN \Length=609`
+    /// * `sequence` - Amino acid sequence
+    ///
+    pub fn from_header(header: &str, sequence: &str) -> Result<Self> {
+        let header = header.trim();
+        let header = header.strip_prefix('>').unwrap_or(header);
+        let (identifier, description) = match header.split_once(char::is_whitespace) {
+            Some((identifier, description)) => (identifier, description.trim_start()),
+            None => (header, "")
+        };
+        if identifier.is_empty() {
+            return Err(Error::missing_value("PEFF entry identifier"));
+        }
+        let (prefix, accession) = identifier.split_once(':').unwrap_or(("", identifier));
+
+        let mut entry = Self::new(prefix.to_owned(), accession.to_owned(), sequence.replace('\n', ""));
+        if description.is_empty() {
+            return Ok(entry);
+        }
+
+        let description = description.strip_prefix('\\').ok_or_else(|| {
+            Error::malformed_header(format!("PEFF description should start with a \\Key=Value annotation: {}", description))
+        })?;
+        for annotation in description.split(" \\") {
+            let (key, value) = annotation.split_once('=').ok_or_else(|| {
+                Error::malformed_header(format!("missing '=' in PEFF annotation: \\{}", annotation.trim()))
+            })?;
+            entry.annotations.push(PeffAnnotation::parse(key.trim(), value.trim())?);
+        }
+
+        Ok(entry)
+    }
+
+    /// Returns the entry identifier, i.e. `prefix:accession`
+    ///
+    pub fn get_identifier(&self) -> String {
+        if self.prefix.is_empty() {
+            self.accession.clone()
+        } else {
+            format!("{}:{}", self.prefix, self.accession)
+        }
+    }
+
+    /// Returns the value of the given untyped annotation, e.g. `PName`
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Annotation key, without backslash
+    ///
+    pub fn get_attribute(&self, key: &str) -> Option<&String> {
+        self.annotations.iter().find_map(|annotation| match annotation {
+            PeffAnnotation::Other(annotation_key, value) if annotation_key == key => Some(value),
+            _ => None
+        })
+    }
+
+    /// Sets the value of the given untyped annotation, replacing the existing one if any
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Annotation key, without backslash, e.g. `PName`
+    /// * `value` - Annotation value
+    ///
+    pub fn set_attribute(&mut self, key: &str, value: &str) -> &mut Self {
+        let existing_value = self.annotations.iter_mut().find_map(|annotation| match annotation {
+            PeffAnnotation::Other(annotation_key, value) if annotation_key == key => Some(value),
+            _ => None
+        });
+        match existing_value {
+            Some(existing_value) => *existing_value = value.to_owned(),
+            None => self.annotations.push(PeffAnnotation::Other(key.to_owned(), value.to_owned()))
+        }
+        self
+    }
+
+    /// Appends an annotation
+    ///
+    /// # Arguments
+    ///
+    /// * `annotation` - Header annotation
+    ///
+    pub fn add_annotation(&mut self, annotation: PeffAnnotation) -> &mut Self {
+        self.annotations.push(annotation);
+        self
+    }
+
+    /// Returns the modifications, whatever their vocabulary (`\ModResPsi`, `\ModResUnimod` and `\ModRes`)
+    ///
+    pub fn modifications(&self) -> impl Iterator<Item = &PeffModification> {
+        self.annotations.iter().flat_map(|annotation| match annotation {
+            PeffAnnotation::ModResPsi(modifications)
+            | PeffAnnotation::ModResUnimod(modifications)
+            | PeffAnnotation::ModRes(modifications) => modifications.as_slice(),
+            _ => &[]
+        })
+    }
+
+    /// Returns the single residue substitutions (`\VariantSimple` and `\Variant`)
+    ///
+    pub fn simple_variants(&self) -> impl Iterator<Item = &PeffVariantSimple> {
+        self.annotations.iter().flat_map(|annotation| match annotation {
+            PeffAnnotation::Variant(variants) | PeffAnnotation::VariantSimple(variants) => variants.as_slice(),
+            _ => &[]
+        })
+    }
+
+    /// Returns the segment replacements (`\VariantComplex`)
+    ///
+    pub fn complex_variants(&self) -> impl Iterator<Item = &PeffVariantComplex> {
+        self.annotations.iter().flat_map(|annotation| match annotation {
+            PeffAnnotation::VariantComplex(variants) => variants.as_slice(),
+            _ => &[]
+        })
+    }
+
+    /// Returns the processed chains (`\Processed`)
+    ///
+    pub fn processed_chains(&self) -> impl Iterator<Item = &PeffProcessed> {
+        self.annotations.iter().flat_map(|annotation| match annotation {
+            PeffAnnotation::Processed(chains) => chains.as_slice(),
+            _ => &[]
+        })
+    }
+
+    /// Creates the header of the entry, e.g. `>sp:P02768 \ID=ALBU_HUMAN \Length=609`
+    ///
+    pub fn create_header(&self) -> String {
+        let mut header = format!(">{}", self.get_identifier());
+        for annotation in &self.annotations {
+            header.push(' ');
+            header.push_str(&annotation.to_string());
+        }
+        header
+    }
+}
+
+impl From<&FastaEntry> for PeffEntry {
+    /// Converts a FASTA entry: the database becomes the prefix, the entry and protein names
+    /// the `\ID` and `\PName` annotations, and the UniProt keyword attributes (OX, OS, GN, SV, PE)
+    /// their PEFF counterparts. The other keyword attributes are kept, sorted by key.
+    /// Attribute values are trimmed, as when they are read back.
+    ///
+    fn from(entry: &FastaEntry) -> Self {
+        let mut peff_entry = Self::new(entry.get_database().clone(), entry.get_accession().clone(), entry.get_sequence().clone());
+        if !entry.get_entry_name().is_empty() {
+            peff_entry.set_attribute("ID", entry.get_entry_name());
+        }
+        if !entry.get_protein_name().is_empty() {
+            peff_entry.set_attribute("PName", entry.get_protein_name());
+        }
+
+        let keyword_attributes = entry.get_keyword_attributes();
+        for (uniprot_key, peff_key) in UNIPROT_TO_PEFF_KEYS {
+            if let Some(value) = keyword_attributes.get(uniprot_key) {
+                peff_entry.set_attribute(peff_key, value.trim());
+            }
+        }
+        peff_entry.set_attribute("Length", &entry.get_sequence().len().to_string());
+
+        let mut other_keys: Vec<&String> = keyword_attributes.keys()
+            .filter(|key| UNIPROT_TO_PEFF_KEYS.iter().all(|(uniprot_key, _)| uniprot_key != key))
+            .collect();
+        other_keys.sort();
+        for key in other_keys {
+            peff_entry.set_attribute(key, keyword_attributes[key].trim());
+        }
+
+        peff_entry
+    }
+}
@@ -0,0 +1,130 @@
+// std imports
+use std::fmt;
+
+// 3rd party imports
+use serde::{Serialize, Deserialize};
+
+/// Version written in the file header by default
+pub const PEFF_VERSION: &str = "1.0";
+
+/// Description of one of the databases gathered in a PEFF file,
+/// i.e. a block of `# Key=Value` lines of the file header, ended by `# //`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeffDatabase {
+    /// Database attributes (e.g. DbName, Prefix, DbVersion, NumberOfEntries), in reading order
+    pub attributes: Vec<(String, String)>,
+}
+
+impl PeffDatabase {
+    /// Creates a new database description
+    ///
+    /// # Arguments
+    ///
+    /// * `db_name` - Database name (DbName)
+    /// * `prefix` - Prefix of the entry identifiers of the database (Prefix), e.g. `sp`
+    ///
+    pub fn new(db_name: &str, prefix: &str) -> Self {
+        let mut database = Self::default();
+        database.set_attribute("DbName", db_name).set_attribute("Prefix", prefix);
+        database
+    }
+
+    /// Returns the value of the given attribute
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Attribute key, e.g. `DbVersion`
+    ///
+    pub fn get_attribute(&self, key: &str) -> Option<&String> {
+        self.attributes.iter().find(|(attribute_key, _)| attribute_key == key).map(|(_, value)| value)
+    }
+
+    /// Sets the value of the given attribute, replacing the existing one if any
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Attribute key, e.g. `DbVersion`
+    /// * `value` - Attribute value
+    ///
+    pub fn set_attribute(&mut self, key: &str, value: &str) -> &mut Self {
+        match self.attributes.iter_mut().find(|(attribute_key, _)| attribute_key == key) {
+            Some((_, existing_value)) => *existing_value = value.to_owned(),
+            None => self.attributes.push((key.to_owned(), value.to_owned()))
+        }
+        self
+    }
+
+    /// Returns the database name (DbName)
+    ///
+    pub fn get_db_name(&self) -> Option<&String> {
+        self.get_attribute("DbName")
+    }
+
+    /// Returns the prefix of the entry identifiers of the database (Prefix)
+    ///
+    pub fn get_prefix(&self) -> Option<&String> {
+        self.get_attribute("Prefix")
+    }
+}
+
+/// File header of a PEFF file: the `# PEFF <version>` line followed by the database descriptions
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeffFileHeader {
+    /// Format version, e.g. `1.0`
+    pub version: String,
+    /// Descriptions of the databases gathered in the file
+    pub databases: Vec<PeffDatabase>,
+}
+
+impl Default for PeffFileHeader {
+    fn default() -> Self {
+        Self {
+            version: PEFF_VERSION.to_owned(),
+            databases: Vec::new(),
+        }
+    }
+}
+
+impl PeffFileHeader {
+    /// Creates a new file header of the current version, without database
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a database description
+    ///
+    /// # Arguments
+    ///
+    /// * `database` - Database description
+    ///
+    pub fn add_database(&mut self, database: PeffDatabase) -> &mut Self {
+        self.databases.push(database);
+        self
+    }
+
+    /// Returns the description of the database using the given prefix
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Prefix of the entry identifiers, e.g. `sp`
+    ///
+    pub fn get_database(&self, prefix: &str) -> Option<&PeffDatabase> {
+        self.databases.iter().find(|database| database.get_prefix().is_some_and(|db_prefix| db_prefix == prefix))
+    }
+}
+
+impl fmt::Display for PeffFileHeader {
+    /// Formats the file header lines, each one ended by a line feed
+    ///
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# PEFF {}", self.version)?;
+        for database in &self.databases {
+            for (key, value) in &database.attributes {
+                writeln!(f, "# {}={}", key, value)?;
+            }
+            writeln!(f, "# //")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+//! Module for dealing with PEFF (PSI Extended FASTA Format) files
+
+pub mod annotation;
+pub mod entry;
+pub mod header;
+pub mod reader;
+pub mod writer;
+pub mod prelude;
+
+pub use prelude::*;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::path::Path;
+
+    use crate::fasta::reader::FastaReader;
+
+    const FASTA_FILE_PATH_STR: &str = "../test_files/fasta/partial_mouse.fasta";
+    const TEST_PEFF_CONTENT: &str = "# PEFF 1.0
+# DbName=uniprot_sp_human
+# Prefix=sp
+# NumberOfEntries=2
+# SequenceType=AA
+# //
+>sp:P0TEST1 \\ID=TEST1_HUMAN \\PName=Test protein (fragment) \\NcbiTaxId=9606 \\Length=24 \\ModResPsi=(3,5|MOD:00046|O-phospho-L-serine)(7|MOD:00064|N6-acetyl-L-lysine) \\ModRes=(10||Hydroxylation) \\VariantSimple=(12|A)(14|*|rs1234) \\VariantComplex=(16|18|)(20|20|WW) \\Processed=(1|8|PEFF:0001021|signal peptide)(9|24|PEFF:0001022|mature protein)
+MASKSPKEGSKAPTKLLHDRMWYV
+>sp:P0TEST2 \\ID=TEST2_HUMAN \\Variant=(1|V)
+MKVLAT
+";
+
+    #[test]
+    /// Reads PEFF content, checks the typed annotations and writes it back
+    fn test_reading_and_writing() {
+        let mut reader = PeffReader::from_reader(TEST_PEFF_CONTENT.as_bytes()).unwrap();
+        let file_header = reader.get_file_header().clone();
+        assert_eq!(file_header.version, "1.0");
+        assert_eq!(file_header.databases.len(), 1);
+        assert_eq!(file_header.get_database("sp").unwrap().get_db_name().unwrap(), "uniprot_sp_human");
+        assert_eq!(file_header.databases[0].get_attribute("NumberOfEntries").unwrap(), "2");
+
+        let entries: Vec<PeffEntry> = reader.by_ref().collect().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(reader.get_num_entries_read(), 2);
+
+        let entry = &entries[0];
+        assert_eq!(entry.get_identifier(), "sp:P0TEST1");
+        assert_eq!(entry.get_attribute("PName").unwrap(), "Test protein (fragment)");
+        assert_eq!(entry.get_attribute("Length").unwrap(), "24");
+        assert_eq!(entry.sequence, "MASKSPKEGSKAPTKLLHDRMWYV");
+
+        let modifications: Vec<&PeffModification> = entry.modifications().collect();
+        assert_eq!(modifications.len(), 3);
+        assert_eq!(modifications[0].positions, vec![3, 5]);
+        assert_eq!(modifications[0].accession, "MOD:00046");
+        assert_eq!(modifications[1].name, "N6-acetyl-L-lysine");
+        assert_eq!(modifications[2].accession, "");
+
+        let simple_variants: Vec<&PeffVariantSimple> = entry.simple_variants().collect();
+        assert_eq!(simple_variants.len(), 2);
+        assert_eq!(simple_variants[1], &PeffVariantSimple { position: 14, residue: "*".to_owned(), tag: Some("rs1234".to_owned()) });
+
+        let complex_variants: Vec<&PeffVariantComplex> = entry.complex_variants().collect();
+        assert_eq!(complex_variants[0].sequence, "");
+        assert_eq!((complex_variants[1].start, complex_variants[1].end), (20, 20));
+
+        let processed_chains: Vec<&PeffProcessed> = entry.processed_chains().collect();
+        assert_eq!(processed_chains.len(), 2);
+        assert_eq!(processed_chains[1].name, "mature protein");
+
+        assert_eq!(entries[1].simple_variants().count(), 1);
+        assert_eq!(entries[1].annotations[1].get_key(), "Variant");
+
+        let mut writer = PeffWriter::from_writer(Vec::new(), None);
+        writer.write_file_header(&file_header).unwrap();
+        writer.write_all(entries.iter()).unwrap();
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), TEST_PEFF_CONTENT);
+    }
+
+    #[test]
+    /// Converts FASTA entries into PEFF ones and reads them back
+    fn test_fasta_conversion() {
+        let fasta_reader = FastaReader::new(Path::new(FASTA_FILE_PATH_STR), 1024, false).unwrap();
+        let fasta_entries: Vec<_> = fasta_reader.collect().unwrap();
+        let peff_entries: Vec<PeffEntry> = fasta_entries.iter().map(PeffEntry::from).collect();
+
+        let header = peff_entries[0].create_header();
+        assert!(header.starts_with(">sp:A0A024B7W1 \\ID=POLG_ZIKVF \\PName=Genome polyprotein \\NcbiTaxId=2043570 \\TaxName=Zika virus"), "{}", header);
+        assert!(header.ends_with("\\SV=1 \\PE=1 \\Length=3423"), "{}", header);
+
+        let mut file_header = PeffFileHeader::new();
+        file_header.add_database(PeffDatabase::new("uniprot_sp", "sp"));
+        let mut writer = PeffWriter::from_writer(Vec::new(), Some(60));
+        writer.write_file_header(&file_header).unwrap();
+        writer.write_all(peff_entries.iter()).unwrap();
+        let peff_content = writer.into_inner();
+
+        let reader = PeffReader::from_reader(peff_content.as_slice()).unwrap();
+        assert_eq!(reader.get_file_header(), &file_header);
+        let read_entries: Vec<PeffEntry> = reader.collect().unwrap();
+        assert_eq!(read_entries, peff_entries);
+    }
+
+    #[test]
+    /// Checks the location of the errors
+    fn test_reading_errors() {
+        let error = PeffReader::from_reader(">sp:P0TEST1\nMKV\n".as_bytes()).err().unwrap();
+        assert_eq!(error.context().line_number, 1);
+
+        let content = "# PEFF 1.0\n>sp:P0TEST1 \\ID=TEST1\nMKV\n>sp:P0TEST2 \\VariantSimple=(x|A)\nMKV\n";
+        let mut reader = PeffReader::from_reader(content.as_bytes()).unwrap();
+        assert!(reader.next().unwrap().is_some());
+        let error = reader.next().err().unwrap();
+        assert_eq!(error.context().line_number, 4);
+        assert_eq!(error.context().record_id.as_deref(), Some("sp:P0TEST2"));
+    }
+}
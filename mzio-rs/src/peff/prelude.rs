@@ -0,0 +1,7 @@
+pub use fallible_iterator::IntoFallibleIterator;
+pub use fallible_iterator::FallibleIterator;
+pub use crate::peff::annotation::{PeffAnnotation, PeffModification, PeffProcessed, PeffVariantComplex, PeffVariantSimple};
+pub use crate::peff::entry::PeffEntry;
+pub use crate::peff::header::{PeffDatabase, PeffFileHeader, PEFF_VERSION};
+pub use crate::peff::reader::PeffReader;
+pub use crate::peff::writer::PeffWriter;
\ No newline at end of file
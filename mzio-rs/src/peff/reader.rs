@@ -0,0 +1,177 @@
+// std imports
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+// 3rd party imports
+use fallible_iterator::FallibleIterator;
+
+// internal imports
+use crate::compression;
+use crate::error::{Error, ErrorContext, Result};
+use crate::fasta::header::RawHeaderParser;
+use crate::fasta::reader::FastaReader;
+use crate::peff::entry::PeffEntry;
+use crate::peff::header::{PeffDatabase, PeffFileHeader};
+
+/// Reader for PEFF (PSI Extended FASTA Format) files.
+/// The file header is read on creation, the entries are then read as with `FastaReader`.
+/// Works on any buffered source (file, stdin, in-memory buffer...).
+pub struct PeffReader<R: BufRead> {
+    fasta_reader: FastaReader<R>,
+    file_header: PeffFileHeader,
+}
+
+impl PeffReader<Box<dyn BufRead + Send>> {
+    /// Creates a new Reader and reads the file header.
+    /// Compressed files (gzip, bzip2, zstd, xz) are detected by their magic bytes
+    /// and decompressed on the fly, if the matching feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `peff_file_path` - Path to PEFF file
+    /// * `buffer_size` - Buffer size to use when loading bytes from disk.
+    ///
+    pub fn new(peff_file_path: &Path, buffer_size: usize) -> anyhow::Result<Self> {
+        let reader = compression::open_file(peff_file_path, buffer_size)?;
+        Ok(Self::with_file_path(reader, Some(peff_file_path.to_path_buf()))?)
+    }
+}
+
+impl<R: BufRead> PeffReader<R> {
+    /// Creates a new Reader from any buffered source and reads the file header
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Buffered source of PEFF content, e.g. `std::io::stdin().lock()` or `&[u8]`
+    ///
+    pub fn from_reader(reader: R) -> Result<Self> {
+        Self::with_file_path(reader, None)
+    }
+
+    /// Creates a new Reader and reads the file header
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Buffered source of PEFF content
+    /// * `file_path` - Path of the read file, if any, used to locate errors
+    ///
+    fn with_file_path(mut reader: R, file_path: Option<PathBuf>) -> Result<Self> {
+        let mut byte_offset: u64 = 0;
+        let mut line_number: u64 = 0;
+        let located = |error: Error, byte_offset: u64, line_number: u64| error.at(ErrorContext {
+            file_path: file_path.clone(),
+            ..ErrorContext::new(byte_offset, line_number)
+        });
+
+        let mut file_header: Option<PeffFileHeader> = None;
+        let mut database = PeffDatabase::default();
+        let mut line = String::new();
+        loop {
+            // The file header ends with the first line which is not a comment (usually the first entry header)
+            let is_header_line = match reader.fill_buf() {
+                Ok(buffer) => buffer.first().is_some_and(|byte| matches!(byte, b'#' | b'\n' | b'\r')),
+                Err(err) => return Err(located(Error::io(err), byte_offset, line_number + 1))
+            };
+            if !is_header_line {
+                break;
+            }
+
+            line.clear();
+            let num_bytes = reader.read_line(&mut line)
+                .map_err(|err| located(Error::io(err), byte_offset, line_number + 1))?;
+            let line_context = (byte_offset, line_number + 1);
+            byte_offset += num_bytes as u64;
+            line_number += 1;
+
+            let content = line.trim().trim_start_matches('#').trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            let header = match file_header.as_mut() {
+                Some(header) => header,
+                None => {
+                    // The first line gives the format version
+                    let version = content.strip_prefix("PEFF").map(str::trim).filter(|version| !version.is_empty());
+                    match version {
+                        Some(version) => {
+                            file_header = Some(PeffFileHeader { version: version.to_owned(), databases: Vec::new() });
+                            continue;
+                        },
+                        None => return Err(located(
+                            Error::malformed_header(format!("expected '# PEFF <version>', found: {}", line.trim())),
+                            line_context.0,
+                            line_context.1
+                        ))
+                    }
+                }
+            };
+
+            if content == "//" {
+                // End of a database description
+                if !database.attributes.is_empty() {
+                    header.databases.push(std::mem::take(&mut database));
+                }
+            } else {
+                match content.split_once('=') {
+                    Some((key, value)) => database.attributes.push((key.trim().to_owned(), value.trim().to_owned())),
+                    None => return Err(located(
+                        Error::malformed_header(format!("expected '# Key=Value' in PEFF file header, found: {}", line.trim())),
+                        line_context.0,
+                        line_context.1
+                    ))
+                }
+            }
+        }
+
+        let mut file_header = file_header.ok_or_else(|| {
+            located(Error::missing_value("PEFF file header ('# PEFF <version>')"), byte_offset, line_number + 1)
+        })?;
+        // Unterminated database description
+        if !database.attributes.is_empty() {
+            file_header.databases.push(database);
+        }
+
+        let mut fasta_reader = FastaReader::from_reader(reader, true);
+        fasta_reader.set_header_parser(RawHeaderParser);
+        fasta_reader.set_location(file_path, byte_offset, line_number);
+
+        Ok(Self { fasta_reader, file_header })
+    }
+
+    /// Returns the file header
+    ///
+    pub fn get_file_header(&self) -> &PeffFileHeader {
+        &self.file_header
+    }
+
+    /// Returns the number of entries read so far
+    ///
+    pub fn get_num_entries_read(&self) -> usize {
+        self.fasta_reader.get_num_entries_read()
+    }
+
+    /// Consumes the PEFF reader and returns the underlying reader
+    ///
+    pub fn into_inner(self) -> R {
+        self.fasta_reader.into_inner()
+    }
+}
+
+impl<R: BufRead> FallibleIterator for PeffReader<R> {
+    type Item = PeffEntry;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        let fasta_entry = match self.fasta_reader.next()? {
+            Some(fasta_entry) => fasta_entry,
+            None => return Ok(None)
+        };
+
+        // The plain header is always kept by the underlying reader
+        let header = fasta_entry.get_plain_header().as_deref().unwrap_or_default();
+        PeffEntry::from_header(header, fasta_entry.get_sequence())
+            .map(Some)
+            .map_err(|error| error.at(self.fasta_reader.get_entry_context().clone()))
+    }
+}
@@ -0,0 +1,148 @@
+// std imports
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::prelude::*;
+use std::path::Path;
+
+// 3rd party imports
+use anyhow::Result;
+
+// internal imports
+use crate::fasta::writer::FastaWriter;
+use crate::peff::entry::PeffEntry;
+use crate::peff::header::PeffFileHeader;
+
+/// DEFAULT max amino acids per sequence line.
+const DEFAULT_MAX_AMINO_ACIDS_PER_LINE: usize = 60;
+
+/// Writer for PEFF (PSI Extended FASTA Format) files.
+/// The file header has to be written before the entries.
+/// Works on any destination (file, stdout, `Vec<u8>`, socket, compressed stream...).
+/// Use flush() to make sure the buffer is written completely.
+pub struct PeffWriter<W: Write> {
+    /// Max amino acids per sequence line.
+    max_amino_acids_per_line: Option<usize>,
+    internal_writer: W
+}
+
+impl PeffWriter<BufWriter<File>> {
+    /// Creates a new Writer
+    ///
+    /// # Arguments
+    ///
+    /// * `peff_file_path` - Path to PEFF file
+    /// * `max_amino_acids_per_line` - If Some(), will format the sequence line to not exceed the given length.
+    ///
+    pub fn new(peff_file_path: &Path, max_amino_acids_per_line: Option<usize>) -> Result<Self> {
+        let peff_file: File = File::create(peff_file_path)?;
+
+        Ok(Self::from_writer(BufWriter::new(peff_file), max_amino_acids_per_line))
+    }
+
+    /// Creates a new Writer, formatting the sequences on lines of 60 amino acids
+    ///
+    /// # Arguments
+    ///
+    /// * `peff_file_path` - Path to PEFF file
+    ///
+    pub fn new_with_default_seq_formatting(peff_file_path: &Path) -> Result<Self> {
+        Self::new(peff_file_path, Some(DEFAULT_MAX_AMINO_ACIDS_PER_LINE))
+    }
+}
+
+impl<W: Write> PeffWriter<W> {
+    /// Creates a new Writer on top of any destination.
+    /// The given writer is used as is, wrap it into a `BufWriter` if it is unbuffered.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Destination of the PEFF content
+    /// * `max_amino_acids_per_line` - If Some(), will format the sequence line to not exceed the given length.
+    ///
+    pub fn from_writer(writer: W, max_amino_acids_per_line: Option<usize>) -> Self {
+        Self {
+            max_amino_acids_per_line,
+            internal_writer: writer,
+        }
+    }
+
+    /// Returns a reference to the underlying writer
+    ///
+    pub fn get_ref(&self) -> &W {
+        &self.internal_writer
+    }
+
+    /// Consumes the PEFF writer and returns the underlying writer.
+    /// Call flush() beforehand when the underlying writer is buffered.
+    ///
+    pub fn into_inner(self) -> W {
+        self.internal_writer
+    }
+
+    /// Creates the text of an entry: its header and its sequence, not ended by a line feed
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - PEFF entry
+    /// * `max_amino_acids_per_line` - If Some(), will format the sequence line to not exceed the given length.
+    ///
+    pub fn stringify_entry(entry: &PeffEntry, max_amino_acids_per_line: Option<usize>) -> String {
+        let seq_as_string = match max_amino_acids_per_line {
+            Some(max_amino_acids_per_line) => FastaWriter::format_sequence(&entry.sequence, max_amino_acids_per_line),
+            None => entry.sequence.clone()
+        };
+
+        [entry.create_header(), seq_as_string].join("\n")
+    }
+
+    /// Writes the file header. Returns the number of written bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_header` - PEFF file header
+    ///
+    pub fn write_file_header(&mut self, file_header: &PeffFileHeader) -> Result<usize> {
+        let file_header_as_string = file_header.to_string();
+        self.internal_writer.write_all(file_header_as_string.as_bytes())?;
+
+        Ok(file_header_as_string.len())
+    }
+
+    /// Writes entry into the PEFF file. Returns the number of written bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - PEFF entry
+    ///
+    pub fn write_entry(&mut self, entry: &PeffEntry) -> Result<usize> {
+        let mut entry_as_string = Self::stringify_entry(entry, self.max_amino_acids_per_line);
+        entry_as_string.push('\n');
+        self.internal_writer.write_all(entry_as_string.as_bytes())?;
+
+        Ok(entry_as_string.len())
+    }
+
+    /// Writes multiple PEFF entries. Returns the number of written bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - Iterator of PEFF entries
+    ///
+    pub fn write_all<'b, I>(&mut self, entries: I) -> Result<usize>
+    where
+        I: Iterator<Item = &'b PeffEntry>,
+    {
+        let mut written_bytes: usize = 0;
+        for entry in entries {
+            written_bytes += self.write_entry(entry)?;
+        }
+        Ok(written_bytes)
+    }
+
+    /// Flushes the buffer
+    ///
+    pub fn flush(&mut self) -> Result<()> {
+        self.internal_writer.flush()?;
+        Ok(())
+    }
+}